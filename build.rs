@@ -8,6 +8,11 @@ fn main() {
 
     let harness_path =
         env::var("HARNESS").unwrap_or_else(|_| String::from("./harnesses/prometheus"));
+    // Declaring any of these replaces cargo's default of rebuilding on any change in the package,
+    // so the harness sources are declared as well
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={harness_path}");
+    println!("cargo:rerun-if-env-changed=HARNESS");
 
     // Define the output directory for the Go library
     let out_dir = match env::var("OUT_DIR") {
@@ -19,9 +24,15 @@ fn main() {
     };
 
     // Build the Go code as a static library
-    let status = Command::new("go")
+    let mut go = Command::new("go");
+    go.arg("build");
+    // RACE=1 builds the harness with Go's race detector, for `fuzz --races`
+    println!("cargo:rerun-if-env-changed=RACE");
+    if env::var("RACE").is_ok_and(|race| race == "1") {
+        go.arg("-race");
+    }
+    let status = go
         .args([
-            "build",
             "-buildmode=c-archive",
             "-tags=libfuzzer,gofuzz",
             "-gcflags=all=-d=libfuzzer", // Enable coverage instrumentation for libfuzzer
@@ -51,10 +62,21 @@ fn main() {
         }
     }
 
+    // Location of the harness, where `golibafl cov` runs the Go coverage tooling
+    let harness_dir = PathBuf::from(&harness_path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(&harness_path));
+    println!("cargo:rustc-env=GOLIBAFL_HARNESS={}", harness_dir.display());
+
     // Tell cargo to look for the library in the output directory
     println!("cargo:rustc-link-search=native={}", out_dir.display());
     // Tell cargo to link the static Go library
     println!("cargo:rustc-link-lib=static=harness");
+    // Export the symbols of the executable, so that `fuzz` finds the sanitizer runtime linked
+    // statically into it, whose functions it looks up at runtime
+    if env::var("CARGO_CFG_TARGET_OS").is_ok_and(|os| os == "linux") {
+        println!("cargo:rustc-link-arg=-Wl,--export-dynamic");
+    }
     
     // For macOS users, please add your frameworks your target depends on here.
     // This is necessary to resolve undefined symbols that may occur during linking.
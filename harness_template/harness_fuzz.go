@@ -14,12 +14,27 @@ import "C"
 //export LLVMFuzzerTestOneInput
 func LLVMFuzzerTestOneInput(data *C.char, size C.size_t) C.int {
 	defer catchPanics()
-	// Call your harness function here
+	input := inputBytes(data, size)
+	// Call your harness function here, e.g. parse(input)
+	_ = input
 	return 0
 }
 
+// View of the input in place, without copying or allocating it on every execution: GoLibAFL
+// passes the buffer of the input itself, which is only valid during the call. If the target keeps
+// references to the input, e.g. sub-slices stored in a cache, copy it with
+// C.GoBytes(unsafe.Pointer(data), C.int(size)) instead.
+func inputBytes(data *C.char, size C.size_t) []byte {
+	if size == 0 {
+		return nil
+	}
+	return unsafe.Slice((*byte)(unsafe.Pointer(data)), int(size))
+}
+
 func catchPanics() {
-	if recover() != nil {
+	if r := recover(); r != nil {
+		// Print the panic like the Go runtime would, so that `golibafl triage` can classify it
+		fmt.Fprintf(os.Stderr, "panic: %v\n\n%s\n", r, debug.Stack())
 		syscall.Kill(os.Getpid(), syscall.SIGABRT)
 	}
 }
@@ -32,6 +47,8 @@ func LLVMFuzzerInitialize(argc *C.int, argv ***C.char) C.int {
 	debug.SetGCPercent(-1)
 	// Set soft memory limit for each process of the runtime to max of 1GiB RAM.
 	debug.SetMemoryLimit(1024 * 1024 * 1024)
+	// Abort on runtime fatal errors, e.g. "concurrent map writes", instead of exiting, so that they are saved as crashes
+	debug.SetTraceback("crash")
 	return 0
 }
 
@@ -26,11 +26,14 @@ func LLVMFuzzerTestOneInput(data *C.char, size C.size_t) C.int {
 func LLVMFuzzerInitialize(argc *C.int, argv ***C.char) C.int {
 	debug.SetGCPercent(-1)
 	debug.SetMemoryLimit(1024 * 1024 * 1024) // set a max of 1G RAM usage per process
+	debug.SetTraceback("crash")              // abort on runtime fatal errors, so that they are saved as crashes
 	return 0
 }
 
 func catchPanics() {
-	if recover() != nil {
+	if r := recover(); r != nil {
+		// Print the panic like the Go runtime would, so that `golibafl triage` can classify it
+		fmt.Fprintf(os.Stderr, "panic: %v\n\n%s\n", r, debug.Stack())
 		syscall.Kill(os.Getpid(), syscall.SIGABRT)
 	}
 }
@@ -11,7 +11,11 @@ import (
 )
 
 func FuzzMe(f *testing.F) {
-	path := "./output/queue/"
+	// Corpus replayed by `golibafl cov`, defaults to the fuzzer's queue
+	path := os.Getenv("GOLIBAFL_CORPUS")
+	if path == "" {
+		path = "./output/queue/"
+	}
 	addCorpusFilesAsSeeds(f, path)
 	f.Fuzz(func(t *testing.T, input []byte) {
 		harness(input)
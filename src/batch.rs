@@ -0,0 +1,223 @@
+use crate::{diff::test_one_input, filter::skips, restarts::record_batched_input};
+use libafl::{
+    corpus::Corpus,
+    executors::{Executor, ExitKind, HasObservers, HasTimeout},
+    feedbacks::{Feedback, MapFeedbackMetadata, StateInitializer},
+    fuzzer::{Evaluator, ExecutesInput},
+    inputs::{BytesInput, HasTargetBytes},
+    mutators::{MutationResult, Mutator},
+    observers::{MapObserver, StdMapObserver},
+    stages::{Restartable, Stage},
+    state::{HasCorpus, HasCurrentCorpusId, HasExecutions},
+    Error, HasNamedMetadata,
+};
+use libafl_bolts::{
+    tuples::{Handle, MatchNameRef},
+    Named,
+};
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Batches run per scheduled corpus entry
+const BATCHES_PER_ENTRY: usize = 16;
+
+// Set while the harness executes a batch instead of its input
+static IN_BATCH: AtomicBool = AtomicBool::new(false);
+
+// Set when the harness rejected the input it executed on its own, by returning -1 like libFuzzer
+// harnesses do for the inputs which must not be added to the corpus
+static REJECTED: AtomicBool = AtomicBool::new(false);
+
+// Inputs of the batch being executed
+static mut BATCH: Vec<BytesInput> = Vec::new();
+
+// Execute an input, or the whole pending batch if one is running, in a tight loop. Each input of
+// the batch is recorded as the last input first, so that the one crashing is known. The harness
+// gets the buffer of the input itself: `target_bytes` borrows it, nothing is copied. The inputs
+// the filter skips aren't executed: the mutational stages drop them before the executor, the
+// ones of the initial corpus or of other fuzzers are rejected here.
+#[allow(static_mut_refs)]
+pub(crate) fn execute(input: &BytesInput) {
+    if IN_BATCH.load(Ordering::Relaxed) {
+        for input in unsafe { BATCH.iter() } {
+            let target = input.target_bytes();
+            record_batched_input(&target);
+            test_one_input(&target);
+        }
+        // Inputs of an interesting batch are evaluated again on their own
+        REJECTED.store(false, Ordering::Relaxed);
+    } else {
+        let target = input.target_bytes();
+        // Skipped inputs are rejected without running the harness
+        let rejected = skips(&target) || test_one_input(&target) == -1;
+        REJECTED.store(rejected, Ordering::Relaxed);
+    }
+}
+
+// Mutational stage executing its mutants in batches of `size`, with a single observers sweep per
+// batch. The coverage of a batch is the sum of the coverage of its inputs: when it hits an edge
+// no input hit before, its inputs are evaluated again one by one, which attributes the new
+// coverage to the right ones. Only new edges are looked for, as summed hit counts exceed the
+// recorded ones for any edge every input hits, so the new hit count bucket of a single input is
+// missed. An input crashing or timing out in a batch kills the client before the batch can be
+// split, so it is executed again on its own by the restarted client.
+pub(crate) struct BatchStage<C, M> {
+    // Its map feedback's history is named after it
+    edges_handle: Handle<C>,
+    mutator: M,
+    size: usize,
+}
+
+impl<C, M> BatchStage<C, M> {
+    pub(crate) fn new(edges_handle: Handle<C>, mutator: M, size: usize) -> Self {
+        Self {
+            edges_handle,
+            mutator,
+            size,
+        }
+    }
+
+    // Whether the batch hit an edge which the edges feedback never saw hit
+    fn hits_new_edges<E, S>(&self, executor: &E, state: &S) -> Result<bool, Error>
+    where
+        C: AsRef<StdMapObserver<'static, u8, false>>,
+        E: HasObservers,
+        E::Observers: MatchNameRef,
+        S: HasNamedMetadata,
+    {
+        let observers = executor.observers();
+        let map = observers
+            .get(&self.edges_handle)
+            .ok_or_else(|| Error::key_not_found("Edges observer not found"))?
+            .as_ref();
+        let history = &state
+            .named_metadata::<MapFeedbackMetadata<u8>>(self.edges_handle.name())?
+            .history_map;
+        Ok((0..map.usable_count())
+            .any(|i| MapObserver::get(map, i) != 0 && history.get(i).is_none_or(|&h| h == 0)))
+    }
+}
+
+impl<C, E, EM, M, S, Z> Stage<E, EM, S, Z> for BatchStage<C, M>
+where
+    C: AsRef<StdMapObserver<'static, u8, false>>,
+    E: Executor<EM, BytesInput, S, Z> + HasObservers + HasTimeout,
+    E::Observers: MatchNameRef,
+    M: Mutator<BytesInput, S>,
+    S: HasCorpus<BytesInput> + HasCurrentCorpusId + HasExecutions + HasNamedMetadata,
+    Z: Evaluator<E, EM, BytesInput, S> + ExecutesInput<E, EM, BytesInput, S>,
+{
+    #[allow(static_mut_refs)]
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let Some(id) = state.current_corpus_id()? else {
+            return Err(Error::empty("No corpus entry is scheduled"));
+        };
+        let base = state.corpus().cloned_input_for_id(id)?;
+        for _ in 0..BATCHES_PER_ENTRY {
+            let mut batch = Vec::with_capacity(self.size);
+            for _ in 0..self.size {
+                let mut input = base.clone();
+                if self.mutator.mutate(state, &mut input)? == MutationResult::Mutated
+                    && !skips(&input.target_bytes())
+                {
+                    batch.push(input);
+                }
+            }
+            let Some(first) = batch.first().cloned() else {
+                continue;
+            };
+            // Each input of the batch gets the whole timeout
+            let timeout = executor.timeout();
+            executor.set_timeout(timeout * batch.len() as u32);
+            unsafe {
+                BATCH = batch;
+            }
+            IN_BATCH.store(true, Ordering::Relaxed);
+            let exit_kind = fuzzer.execute_input(state, executor, manager, &first);
+            IN_BATCH.store(false, Ordering::Relaxed);
+            executor.set_timeout(timeout);
+            exit_kind?;
+            let batch = unsafe { std::mem::take(&mut BATCH) };
+            // The batch was counted as a single execution
+            *state.executions_mut() += batch.len() as u64 - 1;
+
+            if self.hits_new_edges(executor, state)? {
+                for input in &batch {
+                    fuzzer.evaluate_input(state, executor, manager, input)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C, M, S> Restartable<S> for BatchStage<C, M> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Objective feedback gate, false while a batch is executed: the objectives would be attributed to
+// the first input of the batch. Their inputs are found again when executed on their own.
+pub(crate) struct NotInBatchFeedback;
+
+impl Named for NotInBatchFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("NotInBatchFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for NotInBatchFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for NotInBatchFeedback {
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        _observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        Ok(!IN_BATCH.load(Ordering::Relaxed))
+    }
+}
+
+// Feedback gate, false when the harness rejected the input: its coverage is neither added to the
+// corpus nor recorded, as it may be spurious, e.g. from the parsing of an input the harness then
+// finds to be invalid
+pub(crate) struct NotRejectedFeedback;
+
+impl Named for NotRejectedFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("NotRejectedFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for NotRejectedFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for NotRejectedFeedback {
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        _observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        Ok(!REJECTED.load(Ordering::Relaxed))
+    }
+}
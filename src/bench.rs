@@ -0,0 +1,111 @@
+use crate::{
+    edges::{edges_observer, sync_edges},
+    gocorpus::read_testcase,
+    run::{collect_inputs, initialize},
+};
+use libafl::{
+    corpus::InMemoryCorpus,
+    events::SimpleEventManager,
+    executors::{inprocess::InProcessExecutor, ExitKind},
+    feedback_or,
+    feedbacks::{CrashFeedback, MaxMapFeedback, TimeFeedback},
+    fuzzer::{Evaluator, StdFuzzer},
+    inputs::{BytesInput, HasTargetBytes},
+    monitors::SimpleMonitor,
+    observers::{CanTrack, TimeObserver},
+    schedulers::QueueScheduler,
+    state::StdState,
+};
+use libafl_bolts::{rands::StdRand, tuples::tuple_list};
+use libafl_targets::libfuzzer::libfuzzer_test_one_input;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// Throughput of the pipeline, as a share of the harness' own, below which its overhead dominates
+const PIPELINE_OVERHEAD_HINT: f64 = 50.0;
+
+// Run the inputs in `input`, a file or a small corpus directory, in a loop for `duration` twice:
+// calling the harness directly, then through the fuzzer's executor, with its edges and time
+// observers and feedbacks. Comparing both tells whether the target itself is slow, or the
+// fuzzing pipeline around it.
+pub(crate) fn bench(input: &Path, duration: Duration, timeout: Duration) {
+    let inputs = collect_inputs(input)
+        .iter()
+        .map(|f| read_testcase(f).unwrap_or_else(|_| panic!("Unable to read file {}", f.display())))
+        .collect::<Vec<_>>();
+    if inputs.is_empty() {
+        panic!("No input to run in {}", input.display());
+    }
+    initialize();
+    println!(
+        "Running {} inputs for {}s in each mode",
+        inputs.len(),
+        duration.as_secs()
+    );
+
+    let harness_alone = execs_per_sec(duration, inputs.len(), |i| unsafe {
+        libfuzzer_test_one_input(&inputs[i]);
+    });
+    println!("Harness alone:   {harness_alone:.0} execs/s");
+
+    let edges_observer = edges_observer().track_indices();
+    let time_observer = TimeObserver::new("time");
+    let mut feedback = feedback_or!(
+        MaxMapFeedback::new(&edges_observer),
+        TimeFeedback::new(&time_observer)
+    );
+    let mut objective = CrashFeedback::new();
+    let mut state = StdState::new(
+        StdRand::new(),
+        InMemoryCorpus::<BytesInput>::new(),
+        InMemoryCorpus::new(),
+        &mut feedback,
+        &mut objective,
+    )
+    .expect("Failed to create the benchmark state");
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+    let mut mgr = SimpleEventManager::new(SimpleMonitor::new(|_| {}));
+    let mut harness = |input: &BytesInput| {
+        unsafe {
+            libfuzzer_test_one_input(&input.target_bytes());
+        }
+        sync_edges();
+        ExitKind::Ok
+    };
+    let mut executor = InProcessExecutor::with_timeout(
+        &mut harness,
+        tuple_list!(edges_observer, time_observer),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+        timeout,
+    )
+    .expect("Failed to create the executor");
+    let inputs = inputs.into_iter().map(BytesInput::new).collect::<Vec<_>>();
+    let with_observers = execs_per_sec(duration, inputs.len(), |i| {
+        fuzzer
+            .evaluate_input(&mut state, &mut executor, &mut mgr, &inputs[i])
+            .expect("Failed to run the input");
+    });
+    let share = 100.0 * with_observers / harness_alone;
+    println!("With observers:  {with_observers:.0} execs/s ({share:.1}% of the harness alone)");
+    if share < PIPELINE_OVERHEAD_HINT {
+        println!(
+            "The fuzzing pipeline dominates the cost of an execution, consider fuzzing with \
+             --batch-size"
+        );
+    }
+}
+
+// Executions per second of `execute`, called on each input in turn for `duration`
+fn execs_per_sec(duration: Duration, inputs: usize, mut execute: impl FnMut(usize)) -> f64 {
+    let start = Instant::now();
+    let mut execs = 0u64;
+    while start.elapsed() < duration {
+        execute(execs as usize % inputs);
+        execs += 1;
+    }
+    execs as f64 / start.elapsed().as_secs_f64()
+}
@@ -0,0 +1,118 @@
+use libafl::{
+    corpus::{Corpus, SchedulerTestcaseMetadata},
+    executors::{ExitKind, HasObservers},
+    fuzzer::ExecutesInput,
+    inputs::BytesInput,
+    observers::{MapObserver, StdMapObserver},
+    schedulers::powersched::SchedulerMetadata,
+    stages::{Restartable, Stage},
+    state::{HasCorpus, HasCurrentCorpusId},
+    Error, HasMetadata,
+};
+use libafl_bolts::tuples::{Handle, MatchNameRef};
+use std::time::{Duration, Instant};
+
+// Execution time given to the entries skipping the calibration before any entry was calibrated
+const DEFAULT_EXEC_TIME: Duration = Duration::from_millis(1);
+
+// Calibration of the new corpus entries with a fixed number of runs, possibly none, instead of
+// LibAFL's `CalibrationStage`, which runs each of them 4 to 8 times to find their unstable edges.
+// It fills the metadata the power schedules and the weighted scheduler rely on: entries which
+// aren't run get the average execution time and coverage of the calibrated ones, so that they
+// are neither favored nor penalized. The unstable edges aren't tracked.
+pub(crate) struct QuickCalibrationStage<C> {
+    edges_handle: Handle<C>,
+    cycles: usize,
+}
+
+impl<C> QuickCalibrationStage<C> {
+    pub(crate) fn new(edges_handle: Handle<C>, cycles: usize) -> Self {
+        Self {
+            edges_handle,
+            cycles,
+        }
+    }
+}
+
+impl<C, E, EM, S, Z> Stage<E, EM, S, Z> for QuickCalibrationStage<C>
+where
+    C: AsRef<StdMapObserver<'static, u8, false>>,
+    E: HasObservers,
+    E::Observers: MatchNameRef,
+    S: HasCorpus<BytesInput> + HasCurrentCorpusId + HasMetadata,
+    Z: ExecutesInput<E, EM, BytesInput, S>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let Some(id) = state.current_corpus_id()? else {
+            return Err(Error::empty("No corpus entry is scheduled"));
+        };
+        // Only once per entry, before it is fuzzed for the first time
+        if state.corpus().get(id)?.borrow().scheduled_count() > 0 {
+            return Ok(());
+        }
+
+        let input = state.corpus().cloned_input_for_id(id)?;
+        let mut total_time = Duration::ZERO;
+        let mut runs = 0;
+        let mut bitmap_size = 0;
+        for _ in 0..self.cycles {
+            let start = Instant::now();
+            if fuzzer.execute_input(state, executor, manager, &input)? != ExitKind::Ok {
+                break;
+            }
+            total_time += start.elapsed();
+            runs += 1;
+            bitmap_size = executor
+                .observers()
+                .get(&self.edges_handle)
+                .ok_or_else(|| Error::key_not_found("Edges observer not found"))?
+                .as_ref()
+                .count_bytes();
+        }
+
+        let psmeta = state.metadata_mut::<SchedulerMetadata>()?;
+        let (exec_time, bitmap_size) = if runs > 0 {
+            psmeta.set_exec_time(psmeta.exec_time() + total_time);
+            psmeta.set_cycles(psmeta.cycles() + runs as u64);
+            psmeta.set_bitmap_size(psmeta.bitmap_size() + bitmap_size);
+            psmeta.set_bitmap_size_log(psmeta.bitmap_size_log() + (bitmap_size as f64).log2());
+            psmeta.set_bitmap_entries(psmeta.bitmap_entries() + 1);
+            (total_time / runs as u32, bitmap_size)
+        } else {
+            let exec_time = u32::try_from(psmeta.cycles())
+                .ok()
+                .filter(|&cycles| cycles > 0)
+                .map_or(DEFAULT_EXEC_TIME, |cycles| psmeta.exec_time() / cycles);
+            let bitmap_size = psmeta
+                .bitmap_size()
+                .checked_div(psmeta.bitmap_entries())
+                .unwrap_or(1);
+            (exec_time, bitmap_size)
+        };
+        let handicap = psmeta.queue_cycles();
+
+        let mut testcase = state.corpus().get(id)?.borrow_mut();
+        testcase.set_exec_time(exec_time);
+        let metadata = testcase.metadata_mut::<SchedulerTestcaseMetadata>()?;
+        metadata.set_cycle_and_time((total_time, runs));
+        metadata.set_bitmap_size(bitmap_size);
+        metadata.set_handicap(handicap);
+        Ok(())
+    }
+}
+
+impl<C, S> Restartable<S> for QuickCalibrationStage<C> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
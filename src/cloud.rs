@@ -0,0 +1,131 @@
+use libafl::Error;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Interval between two uploads of the output directory
+const UPLOAD_INTERVAL: Duration = Duration::from_secs(300);
+
+// Directories of the output uploaded to the bucket, under the same names
+const UPLOADED: [&str; 2] = ["queue", "crashes"];
+
+// Object stores, driven through their command line tools, which handle the credentials
+#[derive(Debug, Clone, Copy)]
+enum Store {
+    // `aws s3 sync`
+    S3,
+    // `gsutil -m rsync`
+    Gcs,
+}
+
+impl Store {
+    fn from_url(url: &str) -> Result<Self, Error> {
+        if url.starts_with("s3://") {
+            Ok(Self::S3)
+        } else if url.starts_with("gs://") {
+            Ok(Self::Gcs)
+        } else {
+            Err(Error::illegal_argument(format!(
+                "Unsupported bucket {url}, expected s3://<bucket>/<prefix> or \
+                 gs://<bucket>/<prefix>"
+            )))
+        }
+    }
+
+    // Copy the files of `src` missing from `dst`, without deleting any: several workers share the
+    // bucket. The hidden metadata and lock files of the corpora are skipped, as well as the links
+    // of the crashes by kind.
+    fn sync(self, src: &str, dst: &str) -> Result<(), Error> {
+        let mut command = match self {
+            Self::S3 => {
+                let mut command = Command::new("aws");
+                command.args(["s3", "sync", "--only-show-errors", "--exclude", ".*"]);
+                command.args(["--exclude", "*/.*", "--no-follow-symlinks", src, dst]);
+                command
+            }
+            Self::Gcs => {
+                let mut command = Command::new("gsutil");
+                command.args(["-q", "-m", "rsync", "-r", "-e"]);
+                command.args(["-x", r"(^|.*/)\..*", src, dst]);
+                command
+            }
+        };
+        let status = command
+            .status()
+            .map_err(|e| Error::os_error(e, format!("Failed to run the sync of {src} to {dst}")))?;
+        if !status.success() {
+            return Err(Error::unknown(format!(
+                "Sync of {src} to {dst} failed with {status}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+// Uploads the queue and the crashes of a campaign to `<bucket>/queue` and `<bucket>/crashes`, so
+// that preemptible workers don't lose their progress
+pub(crate) struct CloudSync {
+    store: Store,
+    bucket: String,
+    output: PathBuf,
+    last: Instant,
+    // Uploads run in the background, a slow one delays the next
+    upload: Option<JoinHandle<()>>,
+}
+
+impl CloudSync {
+    pub(crate) fn new(bucket: &str, output: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            store: Store::from_url(bucket)?,
+            bucket: bucket.trim_end_matches('/').to_string(),
+            output: output.to_path_buf(),
+            last: Instant::now(),
+            upload: None,
+        })
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= UPLOAD_INTERVAL
+            && self.upload.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    // Start uploading in the background. Failures are only reported, the campaign goes on.
+    pub(crate) fn upload(&mut self) {
+        self.last = Instant::now();
+        let (store, bucket, output) = (self.store, self.bucket.clone(), self.output.clone());
+        self.upload = Some(thread::spawn(move || {
+            if let Err(err) = upload(store, &bucket, &output) {
+                eprintln!("Warning: failed to upload the campaign to {bucket}: {err}");
+            }
+        }));
+    }
+
+    // Upload once the campaign is over, waiting for it to complete
+    pub(crate) fn finish(&mut self) -> Result<(), Error> {
+        if let Some(upload) = self.upload.take() {
+            let _ = upload.join();
+        }
+        upload(self.store, &self.bucket, &self.output)
+    }
+}
+
+fn upload(store: Store, bucket: &str, output: &Path) -> Result<(), Error> {
+    for dir in UPLOADED {
+        let local = output.join(dir);
+        if local.is_dir() {
+            store.sync(&local.display().to_string(), &format!("{bucket}/{dir}"))?;
+        }
+    }
+    Ok(())
+}
+
+// Download the queue uploaded to `bucket` by previous workers into `dest`, to seed a campaign
+pub(crate) fn download_queue(bucket: &str, dest: &Path) -> Result<(), Error> {
+    let store = Store::from_url(bucket)?;
+    let queue = format!("{}/queue", bucket.trim_end_matches('/'));
+    std::fs::create_dir_all(dest)?;
+    store.sync(&queue, &dest.display().to_string())
+}
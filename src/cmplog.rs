@@ -0,0 +1,113 @@
+use libafl::{
+    executors::ExitKind,
+    observers::{cmp::CmpValues, CmpValuesMetadata, Observer},
+    Error, HasMetadata,
+};
+use libafl_bolts::{rands::Rand, Named};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+// Comparison tracing modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpLogMode {
+    // No tracing, for targets built without the comparison hooks
+    Off,
+    // Operands of the comparison instructions
+    Instructions,
+    // Operands of the comparison instructions, and arguments of the hooked comparison routines
+    // (strings and bytes comparisons)
+    Routines,
+}
+
+// How often the comparisons of the scheduled corpus entries are traced, like AFL++'s cmplog
+// scheduling: tracing doubles the executions spent on an entry for tracing-heavy targets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpLogRate {
+    // Every Nth scheduled entry
+    Every(u32),
+    // Each scheduled entry with this probability
+    Probability(f64),
+}
+
+impl CmpLogRate {
+    // Parse `N` for every Nth scheduled entry, or a probability, e.g. `0.25`
+    pub fn from_cmdline(rate: &str) -> Result<Self, String> {
+        if rate.contains('.') {
+            rate.parse()
+                .ok()
+                .filter(|probability| (0.0..=1.0).contains(probability))
+                .map(Self::Probability)
+                .ok_or_else(|| format!("Invalid probability {rate}, expected 0 to 1"))
+        } else {
+            rate.parse()
+                .ok()
+                .filter(|&every| every > 0)
+                .map(Self::Every)
+                .ok_or_else(|| format!("Invalid rate {rate}, expected N > 0 or a probability"))
+        }
+    }
+}
+
+// Decides whether the comparisons of the scheduled entry are traced. It is asked once per
+// scheduled entry, and has its own random generator so that the state's one isn't needed.
+pub(crate) struct CmpLogSchedule<R> {
+    rate: CmpLogRate,
+    scheduled: u64,
+    rand: R,
+}
+
+impl<R: Rand> CmpLogSchedule<R> {
+    pub(crate) fn new(rate: CmpLogRate, rand: R) -> Self {
+        Self {
+            rate,
+            scheduled: 0,
+            rand,
+        }
+    }
+
+    pub(crate) fn should_trace(&mut self) -> bool {
+        self.scheduled += 1;
+        match self.rate {
+            CmpLogRate::Every(every) => (self.scheduled - 1) % u64::from(every) == 0,
+            CmpLogRate::Probability(probability) => self.rand.coinflip(probability),
+        }
+    }
+}
+
+// Observer dropping the routine arguments logged by the CmpLogObserver preceding it, so that only
+// the operands of the comparison instructions are used by the input-to-state mutations
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct InstructionsOnlyObserver {
+    name: Cow<'static, str>,
+    enabled: bool,
+}
+
+impl InstructionsOnlyObserver {
+    // Filter the comparisons if `enabled`, otherwise the observer does nothing
+    pub(crate) fn new(name: &'static str, enabled: bool) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            enabled,
+        }
+    }
+}
+
+impl Named for InstructionsOnlyObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for InstructionsOnlyObserver
+where
+    S: HasMetadata,
+{
+    fn post_exec(&mut self, state: &mut S, _input: &I, _exit_kind: &ExitKind) -> Result<(), Error> {
+        if self.enabled {
+            if let Ok(meta) = state.metadata_mut::<CmpValuesMetadata>() {
+                meta.list.retain(|cmp| !matches!(cmp, CmpValues::Bytes(_)));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use libafl::{
+    corpus::{Corpus, CorpusId, InMemoryOnDiskCorpus, Testcase},
+    inputs::{BytesInput, HasTargetBytes, Input},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::VecDeque, fs, path::Path};
+
+// Extension of the zstd-compressed queue entries
+pub(crate) const COMPRESSED_EXTENSION: &str = "zst";
+// Compression level of the queue entries, zstd's default: large inputs are compressed several
+// times faster than at higher levels, for a close ratio
+const COMPRESSION_LEVEL: i32 = 3;
+
+// Read the content of a queue entry, decompressing the `.zst` ones. Other files are read with
+// `read_testcase`, which takes them as is.
+pub(crate) fn read_input_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let data = fs::read(path)?;
+    if path
+        .extension()
+        .is_some_and(|ext| ext == COMPRESSED_EXTENSION)
+    {
+        zstd::decode_all(&data[..]).map_err(|err| {
+            Error::illegal_argument(format!("Failed to decompress {}: {err}", path.display()))
+        })
+    } else {
+        Ok(data)
+    }
+}
+
+// Whether the file `name` holds a compressed input
+fn is_compressed(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext == COMPRESSED_EXTENSION)
+}
+
+// Queue keeping the last `cache_max_len` loaded entries in memory, like a `CachedOnDiskCorpus`,
+// which can write its entries compressed with zstd, as `<name>.zst` files, for targets with large
+// inputs whose queue would otherwise fill the disk. They are decompressed when loaded back, which
+// `CachedOnDiskCorpus` can't do as it reads its entries itself, hence the cache kept here.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct QueueCorpus {
+    inner: InMemoryOnDiskCorpus<BytesInput>,
+    cached_indexes: RefCell<VecDeque<CorpusId>>,
+    cache_max_len: usize,
+    compress: bool,
+}
+
+impl QueueCorpus {
+    pub(crate) fn new(dir: &Path, cache_max_len: usize, compress: bool) -> Result<Self, Error> {
+        Ok(Self {
+            inner: InMemoryOnDiskCorpus::new(dir)?,
+            cached_indexes: RefCell::new(VecDeque::new()),
+            cache_max_len,
+            compress,
+        })
+    }
+
+    // Name the new entries after their content, with the `.zst` extension when compressing. The
+    // input of the `.zst` entries is swapped for its compressed content, so that the inner corpus
+    // writes it once, compressed. Returns the input to restore once stored.
+    fn prepare(&self, testcase: &mut Testcase<BytesInput>) -> Result<Option<BytesInput>, Error> {
+        if self.compress && testcase.filename().is_none() {
+            if let Some(input) = testcase.input() {
+                let name = format!("{}.{COMPRESSED_EXTENSION}", input.generate_name(None));
+                *testcase.filename_mut() = Some(name);
+            }
+        }
+        if !testcase.filename().as_deref().is_some_and(is_compressed) {
+            return Ok(None);
+        }
+        let Some(input) = testcase.input_mut().take() else {
+            return Ok(None);
+        };
+        let compressed = zstd::encode_all(&*input.target_bytes(), COMPRESSION_LEVEL)?;
+        testcase.set_input(BytesInput::new(compressed));
+        Ok(Some(input))
+    }
+
+    fn stored(&self, id: CorpusId, input: Option<BytesInput>) -> Result<(), Error> {
+        let testcase = self.inner.get_from_all(id)?;
+        if let Some(input) = input {
+            testcase.borrow_mut().set_input(input);
+        }
+        if testcase.borrow().input().is_some() {
+            self.cache(id);
+        }
+        Ok(())
+    }
+
+    // Mark the entry `id` as loaded, unloading the least recently loaded ones beyond the cache size
+    fn cache(&self, id: CorpusId) {
+        let mut cached_indexes = self.cached_indexes.borrow_mut();
+        if let Some(position) = cached_indexes.iter().position(|&cached| cached == id) {
+            cached_indexes.remove(position);
+        }
+        while cached_indexes.len() >= self.cache_max_len {
+            let Some(oldest) = cached_indexes.pop_front() else {
+                break;
+            };
+            // Entries removed since they were loaded are already gone
+            if let Ok(testcase) = self.inner.get_from_all(oldest) {
+                if let Ok(mut testcase) = testcase.try_borrow_mut() {
+                    *testcase.input_mut() = None;
+                } else {
+                    // Still in use, unloaded later
+                    cached_indexes.push_back(oldest);
+                    break;
+                }
+            }
+        }
+        cached_indexes.push_back(id);
+    }
+
+    fn load(&self, testcase: &RefCell<Testcase<BytesInput>>, id: CorpusId) -> Result<(), Error> {
+        if testcase.borrow().input().is_none() {
+            self.load_input_into(&mut testcase.borrow_mut())?;
+            self.cache(id);
+        }
+        Ok(())
+    }
+}
+
+impl Corpus<BytesInput> for QueueCorpus {
+    fn count(&self) -> usize {
+        self.inner.count()
+    }
+
+    fn count_disabled(&self) -> usize {
+        self.inner.count_disabled()
+    }
+
+    fn count_all(&self) -> usize {
+        self.inner.count_all()
+    }
+
+    fn add(&mut self, mut testcase: Testcase<BytesInput>) -> Result<CorpusId, Error> {
+        let input = self.prepare(&mut testcase)?;
+        let id = self.inner.add(testcase)?;
+        self.stored(id, input)?;
+        Ok(id)
+    }
+
+    fn add_disabled(&mut self, mut testcase: Testcase<BytesInput>) -> Result<CorpusId, Error> {
+        let input = self.prepare(&mut testcase)?;
+        let id = self.inner.add_disabled(testcase)?;
+        self.stored(id, input)?;
+        Ok(id)
+    }
+
+    fn replace(
+        &mut self,
+        id: CorpusId,
+        mut testcase: Testcase<BytesInput>,
+    ) -> Result<Testcase<BytesInput>, Error> {
+        let input = self.prepare(&mut testcase)?;
+        let previous = self.inner.replace(id, testcase)?;
+        self.stored(id, input)?;
+        Ok(previous)
+    }
+
+    fn remove(&mut self, id: CorpusId) -> Result<Testcase<BytesInput>, Error> {
+        let testcase = self.inner.remove(id)?;
+        self.cached_indexes
+            .borrow_mut()
+            .retain(|&cached| cached != id);
+        Ok(testcase)
+    }
+
+    fn get(&self, id: CorpusId) -> Result<&RefCell<Testcase<BytesInput>>, Error> {
+        let testcase = self.inner.get(id)?;
+        self.load(testcase, id)?;
+        Ok(testcase)
+    }
+
+    fn get_from_all(&self, id: CorpusId) -> Result<&RefCell<Testcase<BytesInput>>, Error> {
+        let testcase = self.inner.get_from_all(id)?;
+        self.load(testcase, id)?;
+        Ok(testcase)
+    }
+
+    fn peek_free_id(&self) -> CorpusId {
+        self.inner.peek_free_id()
+    }
+
+    fn current(&self) -> &Option<CorpusId> {
+        self.inner.current()
+    }
+
+    fn current_mut(&mut self) -> &mut Option<CorpusId> {
+        self.inner.current_mut()
+    }
+
+    fn next(&self, id: CorpusId) -> Option<CorpusId> {
+        self.inner.next(id)
+    }
+
+    fn prev(&self, id: CorpusId) -> Option<CorpusId> {
+        self.inner.prev(id)
+    }
+
+    fn first(&self) -> Option<CorpusId> {
+        self.inner.first()
+    }
+
+    fn last(&self) -> Option<CorpusId> {
+        self.inner.last()
+    }
+
+    fn nth_from_all(&self, nth: usize) -> CorpusId {
+        self.inner.nth_from_all(nth)
+    }
+
+    fn load_input_into(&self, testcase: &mut Testcase<BytesInput>) -> Result<(), Error> {
+        if testcase.input().is_none() {
+            let Some(path) = testcase.file_path() else {
+                return Err(Error::illegal_argument(
+                    "No file path set for the testcase, unable to load its input",
+                ));
+            };
+            let input = BytesInput::new(read_input_file(path)?);
+            testcase.set_input(input);
+        }
+        Ok(())
+    }
+
+    fn store_input_from(&self, testcase: &Testcase<BytesInput>) -> Result<(), Error> {
+        if !testcase.filename().as_deref().is_some_and(is_compressed) {
+            return self.inner.store_input_from(testcase);
+        }
+        let (Some(path), Some(input)) = (testcase.file_path(), testcase.input()) else {
+            return Ok(());
+        };
+        fs::write(
+            path,
+            zstd::encode_all(&*input.target_bytes(), COMPRESSION_LEVEL)?,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::remove_dir_all, process};
+
+    #[test]
+    fn compressed_files_are_read_back() {
+        let dir = std::env::temp_dir().join(format!("golibafl-compress-{}", process::id()));
+        let _ = remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let data = b"input ".repeat(1000);
+        let compressed = dir.join(format!("input.{COMPRESSED_EXTENSION}"));
+        fs::write(
+            &compressed,
+            zstd::encode_all(&data[..], COMPRESSION_LEVEL).unwrap(),
+        )
+        .unwrap();
+        let raw = dir.join("input");
+        fs::write(&raw, &data).unwrap();
+
+        assert_eq!(read_input_file(&compressed).unwrap(), data);
+        assert_eq!(read_input_file(&raw).unwrap(), data);
+        fs::write(&compressed, &data).unwrap();
+        assert!(read_input_file(&compressed).is_err());
+        let _ = remove_dir_all(&dir);
+    }
+}
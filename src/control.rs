@@ -0,0 +1,86 @@
+use libafl::Error;
+use std::{
+    ptr::null_mut,
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, Ordering},
+    thread::sleep,
+    time::Duration,
+};
+
+// Interval between two checks of a paused client for the end of the pause
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Requests made to all the clients of a campaign at once
+struct Requests {
+    stop: AtomicBool,
+    pause: AtomicBool,
+    // Number of state dumps requested since the campaign started
+    dumps: AtomicU32,
+}
+
+// In memory shared with the clients forked after `share_requests`
+static REQUESTS: AtomicPtr<Requests> = AtomicPtr::new(null_mut());
+
+// Map the requests in memory shared with the clients, before they are launched. The mapping is
+// zeroed, which makes no request.
+pub(crate) fn share_requests() -> Result<(), Error> {
+    let requests = unsafe {
+        libc::mmap(
+            null_mut(),
+            size_of::<Requests>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if requests == libc::MAP_FAILED {
+        return Err(Error::last_os_error("Failed to map the clients' requests"));
+    }
+    REQUESTS.store(requests.cast(), Ordering::SeqCst);
+    Ok(())
+}
+
+fn requests() -> Option<&'static Requests> {
+    unsafe { REQUESTS.load(Ordering::SeqCst).as_ref() }
+}
+
+// Make all the clients leave, as on SIGTERM
+pub(crate) fn request_stop() {
+    if let Some(requests) = requests() {
+        requests.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn stop_requested() -> bool {
+    requests().is_some_and(|requests| requests.stop.load(Ordering::SeqCst))
+}
+
+// Pause or resume all the clients
+pub(crate) fn set_paused(paused: bool) {
+    if let Some(requests) = requests() {
+        requests.pause.store(paused, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn paused() -> bool {
+    requests().is_some_and(|requests| requests.pause.load(Ordering::SeqCst))
+}
+
+// Make all the clients dump their state
+pub(crate) fn request_dump() {
+    if let Some(requests) = requests() {
+        requests.dumps.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn dump_requests() -> u32 {
+    requests().map_or(0, |requests| requests.dumps.load(Ordering::SeqCst))
+}
+
+// Block a client between two fuzzing iterations while the campaign is paused, keeping its state
+// in memory. A stop request ends the pause.
+pub(crate) fn wait_while_paused(stop_requested: impl Fn() -> bool) {
+    while paused() && !stop_requested() {
+        sleep(PAUSE_POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+// Directory of the harness linked into this binary, recorded by build.rs
+const HARNESS_DIR: &str = env!("GOLIBAFL_HARNESS");
+
+// Report formats of `cov`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CovFormat {
+    // Files and functions reached, for humans
+    Text,
+    // LCOV tracefile, for genhtml and CI coverage services
+    Lcov,
+}
+
+// Basic block of a Go cover profile, executed `count` times
+struct Block {
+    file: String,
+    start_line: usize,
+    end_line: usize,
+    count: u64,
+}
+
+// Run `go` in the harness directory, exiting on failure
+fn go(args: &[&str], corpus: Option<&Path>) -> String {
+    let mut cmd = Command::new("go");
+    cmd.args(args).current_dir(HARNESS_DIR);
+    if let Some(corpus) = corpus {
+        cmd.env("GOLIBAFL_CORPUS", corpus);
+    }
+    let output = cmd
+        .output()
+        .unwrap_or_else(|err| panic!("Failed to execute go: {err}"));
+    if !output.status.success() {
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        eprintln!("`go {}` failed in {HARNESS_DIR}", args.join(" "));
+        process::exit(1);
+    }
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// Non-standard packages the harness depends on, i.e. the fuzzed ones
+fn default_coverpkg() -> String {
+    go(
+        &[
+            "list",
+            "-deps",
+            "-test",
+            "-f",
+            "{{if not .Standard}}{{.ImportPath}}{{end}}",
+        ],
+        None,
+    )
+    .lines()
+    .filter(|pkg| !pkg.is_empty())
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+// Replay the corpus through the `FuzzMe` Go test of the harness, built with `-cover`, and return
+// the path of the resulting cover profile.
+//
+// Go registers placeholder addresses in its sancov PC table, so the edges of the counters maps
+// can't be symbolized: Go's own coverage tooling attributes the executions to the source instead.
+fn cover_profile(input: &Path, coverpkg: Option<&str>) -> PathBuf {
+    let input = input
+        .canonicalize()
+        .unwrap_or_else(|_| panic!("Unable to access {}", input.display()));
+    let profile = env::temp_dir().join(format!("golibafl-cover-{}.out", process::id()));
+    let coverpkg = coverpkg.map_or_else(default_coverpkg, String::from);
+    go(
+        &[
+            "test",
+            "-tags=gocov",
+            "-run=FuzzMe",
+            "-covermode=count",
+            &format!("-coverpkg={coverpkg}"),
+            &format!("-coverprofile={}", profile.display()),
+        ],
+        Some(&input),
+    );
+    profile
+}
+
+// Blocks of a cover profile, in `file:start.col,end.col statements count` format
+fn parse_profile(profile: &Path) -> Vec<Block> {
+    let content = std::fs::read_to_string(profile)
+        .unwrap_or_else(|_| panic!("Unable to read file {}", profile.display()));
+    content
+        .lines()
+        .filter(|line| !line.starts_with("mode:"))
+        .filter_map(|line| {
+            let (file, rest) = line.rsplit_once(':')?;
+            let mut fields = rest.split_whitespace();
+            let (start, end) = fields.next()?.split_once(',')?;
+            let count = fields.nth(1)?.parse().ok()?;
+            Some(Block {
+                file: file.to_string(),
+                start_line: start.split_once('.')?.0.parse().ok()?,
+                end_line: end.split_once('.')?.0.parse().ok()?,
+                count,
+            })
+        })
+        .collect()
+}
+
+// Print the coverage of the corpus in `input`, in the given format
+pub(crate) fn cov(input: &Path, coverpkg: Option<&str>, format: CovFormat) {
+    let profile = cover_profile(input, coverpkg);
+    let blocks = parse_profile(&profile);
+    match format {
+        CovFormat::Text => print_text(&blocks, &profile),
+        CovFormat::Lcov => print_lcov(&blocks),
+    }
+    let _ = std::fs::remove_file(profile);
+}
+
+// Directories of the given Go packages
+fn package_dirs<'a>(packages: impl Iterator<Item = &'a str>) -> HashMap<String, String> {
+    let mut args = vec!["list", "-f", "{{.ImportPath}}\t{{.Dir}}"];
+    args.extend(packages);
+    go(&args, None)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(pkg, dir)| (pkg.to_string(), dir.to_string()))
+        .collect()
+}
+
+// LCOV tracefile with the execution count of every line. Go reports files by import path, which
+// are resolved to their location on disk for genhtml.
+fn print_lcov(blocks: &[Block]) {
+    // file -> line -> count
+    let mut lines: BTreeMap<&str, BTreeMap<usize, u64>> = BTreeMap::new();
+    for block in blocks {
+        let file = lines.entry(&block.file).or_default();
+        for line in block.start_line..=block.end_line {
+            let count = file.entry(line).or_default();
+            *count = (*count).max(block.count);
+        }
+    }
+    let packages: BTreeSet<&str> = lines
+        .keys()
+        .filter_map(|file| Some(file.rsplit_once('/')?.0))
+        .collect();
+    let dirs = package_dirs(packages.into_iter());
+
+    for (file, counts) in &lines {
+        let path = file
+            .rsplit_once('/')
+            .and_then(|(pkg, name)| Some(format!("{}/{name}", dirs.get(pkg)?)))
+            .unwrap_or_else(|| file.to_string());
+        println!("TN:");
+        println!("SF:{path}");
+        for (line, count) in counts {
+            println!("DA:{line},{count}");
+        }
+        println!("LF:{}", counts.len());
+        println!("LH:{}", counts.values().filter(|&&count| count > 0).count());
+        println!("end_of_record");
+    }
+}
+
+// Files and functions reached, with their execution counts
+fn print_text(blocks: &[Block], profile: &Path) {
+    // file -> (reached blocks, blocks, executions)
+    let mut files: BTreeMap<&str, (usize, usize, u64)> = BTreeMap::new();
+    for block in blocks {
+        let entry = files.entry(&block.file).or_default();
+        entry.0 += usize::from(block.count > 0);
+        entry.1 += 1;
+        entry.2 += block.count;
+    }
+    println!("Files:");
+    for (file, (reached, total, hits)) in files.iter().filter(|(_, stats)| stats.0 > 0) {
+        println!("  {file}: {reached}/{total} blocks reached, {hits} block executions");
+    }
+    println!(
+        "{} out of {} files reached",
+        files.values().filter(|stats| stats.0 > 0).count(),
+        files.len()
+    );
+
+    println!("Functions:");
+    let profile_arg = format!("-func={}", profile.display());
+    for line in go(&["tool", "cover", &profile_arg], None).lines() {
+        if !line.ends_with(" 0.0%") {
+            println!("  {line}");
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use libafl::{
+    corpus::{Corpus, CorpusId, HasCurrentCorpusId, IsFavoredMetadata},
+    fuzzer::HasScheduler,
+    inputs::BytesInput,
+    schedulers::RemovableScheduler,
+    state::HasCorpus,
+    Error, HasMetadata,
+};
+use std::{
+    fs::metadata,
+    time::{Duration, Instant},
+};
+
+// Interval between two checks of the corpus size
+const CULL_INTERVAL: Duration = Duration::from_secs(60);
+
+// Periodically drops redundant queue entries once the corpus of a client exceeds its maximum
+// number of entries or size. Redundant entries are the ones the minimizer scheduler doesn't favor:
+// each of their edges is also reached by a favored entry, at least as small and fast.
+pub(crate) struct CorpusCuller {
+    max_entries: Option<usize>,
+    max_bytes: Option<u64>,
+    last: Option<Instant>,
+}
+
+impl CorpusCuller {
+    pub(crate) fn new(max_entries: Option<usize>, max_bytes: Option<u64>) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            last: None,
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        (self.max_entries.is_some() || self.max_bytes.is_some())
+            && self.last.is_none_or(|last| last.elapsed() >= CULL_INTERVAL)
+    }
+
+    // Remove the oldest redundant entries until the corpus fits its limits, or none is left.
+    // Favored entries are always kept, so that culling never loses coverage. Returns the number
+    // of removed entries.
+    pub(crate) fn cull<S, Z>(&mut self, fuzzer: &mut Z, state: &mut S) -> Result<usize, Error>
+    where
+        S: HasCorpus<BytesInput> + HasCurrentCorpusId,
+        Z: HasScheduler<BytesInput, S>,
+        Z::Scheduler: RemovableScheduler<BytesInput, S>,
+    {
+        self.last = Some(Instant::now());
+        let current = state.current_corpus_id()?;
+
+        let mut entries = state.corpus().count();
+        let mut bytes = 0;
+        let mut favored = 0;
+        // Entries which can be removed, with their size on disk
+        let mut redundant: Vec<(CorpusId, u64)> = Vec::new();
+        for id in state.corpus().ids() {
+            let testcase = state.corpus().get(id)?.borrow();
+            let size = testcase
+                .file_path()
+                .as_ref()
+                .and_then(|path| metadata(path).ok())
+                .map_or(0, |metadata| metadata.len());
+            bytes += size;
+            if testcase.has_metadata::<IsFavoredMetadata>() {
+                favored += 1;
+            } else if Some(id) != current {
+                redundant.push((id, size));
+            }
+        }
+        // Without the minimizer, no entry is favored, and none is known to be redundant
+        if favored == 0 {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for (id, size) in redundant {
+            if self.max_entries.is_none_or(|max| entries <= max)
+                && self.max_bytes.is_none_or(|max| bytes <= max)
+            {
+                break;
+            }
+            let testcase = state.corpus_mut().remove(id)?;
+            fuzzer
+                .scheduler_mut()
+                .on_remove(state, id, &Some(testcase))?;
+            entries -= 1;
+            bytes -= size;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,62 @@
+use libafl::mutators::Tokens;
+use std::{
+    fmt::Write as _,
+    fs::{rename, write},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// Name of the dictionary of learned tokens, in the output directory
+pub(crate) const LEARNED_DICT: &str = "learned.dict";
+
+// Periodically dumps the tokens of a client (extracted from the target, loaded from the
+// dictionaries, or learned) in AFL dictionary format, so that they survive restarts
+pub(crate) struct DictWriter {
+    path: PathBuf,
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl DictWriter {
+    // Dump every minute to `path`
+    pub(crate) fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            interval: Duration::from_secs(60),
+            last: None,
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.is_none_or(|last| last.elapsed() >= self.interval)
+    }
+
+    // Written to a temporary file first, so that clients dumping at the same time, or reading
+    // the dictionary while restarting, never see a partial one
+    pub(crate) fn write(&mut self, tokens: &Tokens) -> io::Result<()> {
+        let mut dict = String::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let _ = writeln!(dict, "token_{i}=\"{}\"", escape(token));
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("dict.{}", std::process::id()));
+        write(&tmp, dict)?;
+        rename(&tmp, &self.path)?;
+        self.last = Some(Instant::now());
+        Ok(())
+    }
+}
+
+// Escape a token for an AFL dictionary: printable ASCII is kept, except quotes and backslashes
+fn escape(token: &[u8]) -> String {
+    token
+        .iter()
+        .map(|&b| match b {
+            b'"' | b'\\' => format!("\\x{b:02x}"),
+            0x20..=0x7e => char::from(b).to_string(),
+            _ => format!("\\x{b:02x}"),
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+use libafl::Error;
+use libafl_targets::libfuzzer::libfuzzer_test_one_input;
+use libloading::Library;
+use std::{ffi::CString, path::Path, process, sync::OnceLock};
+
+// Entry point of the reference implementation, unless another symbol is given
+pub(crate) const DEFAULT_REFERENCE_SYMBOL: &str = "LLVMFuzzerTestOneInput";
+
+// Printed before aborting on a divergence, which tells it apart from the other crashes
+pub(crate) const DIVERGENCE_MESSAGE: &str = "golibafl: divergence:";
+
+// Function the reference library may export to compare both implementations itself, in C:
+//   int golibafl_compare(const uint8_t *data, size_t size, int harness_result,
+//                        int reference_result);
+// It returns non-zero when they diverged, e.g. after comparing the output each one stored. Without
+// it, the values both entry points returned are compared.
+const COMPARE_SYMBOL: &[u8] = b"golibafl_compare\0";
+
+type TestOneInputFn = unsafe extern "C" fn(*const u8, usize) -> libc::c_int;
+type CompareFn = unsafe extern "C" fn(*const u8, usize, libc::c_int, libc::c_int) -> libc::c_int;
+
+// Second implementation of the target, run on each input after the harness
+struct Reference {
+    // Kept loaded as long as its functions may be called
+    _library: Library,
+    test_one_input: TestOneInputFn,
+    compare: Option<CompareFn>,
+}
+
+static REFERENCE: OnceLock<Reference> = OnceLock::new();
+
+// Load the reference implementation from the dynamic library `path`, through its `symbol` entry
+// point with the signature of `LLVMFuzzerTestOneInput`. Loaded once per process: the forked
+// executions and the restarted clients keep it.
+pub(crate) fn load_reference(path: &Path, symbol: &str) -> Result<(), Error> {
+    if REFERENCE.get().is_some() {
+        return Ok(());
+    }
+    let invalid = |err: libloading::Error| {
+        Error::illegal_argument(format!(
+            "Failed to load the reference implementation {}: {err}",
+            path.display()
+        ))
+    };
+    let symbol = CString::new(symbol)
+        .map_err(|_| Error::illegal_argument(format!("Invalid symbol name {symbol}")))?;
+    // Loading runs the library's initializers, which the user trusts by passing it
+    let library = unsafe { Library::new(path) }.map_err(invalid)?;
+    let test_one_input =
+        *unsafe { library.get::<TestOneInputFn>(symbol.as_bytes_with_nul()) }.map_err(invalid)?;
+    let compare = unsafe { library.get::<CompareFn>(COMPARE_SYMBOL) }
+        .ok()
+        .map(|compare| *compare);
+    let _ = REFERENCE.set(Reference {
+        _library: library,
+        test_one_input,
+        compare,
+    });
+    Ok(())
+}
+
+// Run the harness on `buf`, then the reference implementation if one is loaded. Divergent
+// implementations abort the process with a message, so that the input is saved as a crash, like
+// any other crash of the harness, in every mode and executor.
+pub(crate) fn test_one_input(buf: &[u8]) -> i32 {
+    let result = unsafe { libfuzzer_test_one_input(buf) };
+    let Some(reference) = REFERENCE.get() else {
+        return result;
+    };
+    let expected = unsafe { (reference.test_one_input)(buf.as_ptr(), buf.len()) };
+    let diverged = match reference.compare {
+        Some(compare) => unsafe { compare(buf.as_ptr(), buf.len(), result, expected) != 0 },
+        None => result != expected,
+    };
+    if diverged {
+        eprintln!(
+            "{DIVERGENCE_MESSAGE} the harness returned {result}, the reference implementation \
+             {expected}"
+        );
+        process::abort();
+    }
+    result
+}
@@ -0,0 +1,145 @@
+use crate::{elf::elf_symbols, run::initialize};
+use libafl::observers::cmp::CmpMap;
+use libafl_targets::{
+    extra_counters, libfuzzer::libfuzzer_test_one_input, CMPLOG_ENABLED, CMPLOG_MAP, CMPLOG_MAP_W,
+    COUNTERS_MAPS,
+};
+use std::{env, fs, process};
+
+// Hint printed when the target lacks the libFuzzer instrumentation
+const REBUILD_HINT: &str = "rebuild the harness with `go build -buildmode=c-archive \
+                            -tags=libfuzzer,gofuzz -gcflags=all=-d=libfuzzer`, as build.rs does";
+
+// Inputs executed to check that the instrumentation is reached
+const PROBES: [&[u8]; 3] = [b"", b"golibafl", b"{\"a\": [1, 2.5, \"b\"]}\n"];
+
+// Outcome of a preflight check
+enum Check {
+    Ok(String),
+    Warning(String),
+    Error(String),
+}
+
+impl Check {
+    fn print(&self) -> bool {
+        match self {
+            Check::Ok(msg) => println!("[ok]      {msg}"),
+            Check::Warning(msg) => println!("[warning] {msg}"),
+            Check::Error(msg) => println!("[error]   {msg}"),
+        }
+        !matches!(self, Check::Error(_))
+    }
+}
+
+fn check_symbols() -> Vec<Check> {
+    let symbols = env::current_exe()
+        .and_then(fs::read)
+        .ok()
+        .and_then(|exe| elf_symbols(&exe));
+    let Some(symbols) = symbols else {
+        return vec![Check::Warning(String::from(
+            "Unable to read the symbol table of the executable (stripped?), skipping symbol checks",
+        ))];
+    };
+    let has = |name: &str| symbols.iter().any(|s| s.name == name);
+    let mut checks = Vec::new();
+    checks.push(if has("LLVMFuzzerTestOneInput") {
+        Check::Ok(String::from(
+            "LLVMFuzzerTestOneInput is exported by the harness",
+        ))
+    } else {
+        Check::Error(String::from(
+            "LLVMFuzzerTestOneInput not found: export it from the harness with \
+             `//export LLVMFuzzerTestOneInput`",
+        ))
+    });
+    checks.push(if has("LLVMFuzzerInitialize") {
+        Check::Ok(String::from(
+            "LLVMFuzzerInitialize is exported by the harness",
+        ))
+    } else {
+        Check::Warning(String::from(
+            "LLVMFuzzerInitialize not found: the Go garbage collector won't be tuned, see \
+             harness_template/harness_fuzz.go",
+        ))
+    });
+    checks
+}
+
+#[allow(static_mut_refs)]
+fn check_instrumentation() -> Vec<Check> {
+    let maps = unsafe { extra_counters() };
+    if maps.is_empty() {
+        return vec![Check::Error(format!(
+            "No sancov counters map registered by the target: {REBUILD_HINT}"
+        ))];
+    }
+    let mut checks = vec![Check::Ok(format!(
+        "{} sancov counters map(s) registered, {} edges in total",
+        maps.len(),
+        maps.iter().map(|map| map.len()).sum::<usize>()
+    ))];
+
+    let mut edges = 0;
+    let mut cmps = 0;
+    for probe in PROBES {
+        unsafe {
+            for map in COUNTERS_MAPS.iter_mut() {
+                map.fill(0);
+            }
+            CMPLOG_MAP.reset().expect("Failed to reset the cmplog map");
+            CMPLOG_ENABLED = 1;
+            libfuzzer_test_one_input(probe);
+            CMPLOG_ENABLED = 0;
+            edges = edges.max(
+                COUNTERS_MAPS
+                    .iter()
+                    .map(|map| map.iter().filter(|&&c| c != 0).count())
+                    .sum::<usize>(),
+            );
+            cmps = cmps.max(
+                (0..CMPLOG_MAP_W)
+                    .filter(|&i| CMPLOG_MAP.executions_for(i) > 0)
+                    .count(),
+            );
+        }
+    }
+    checks.push(if edges > 0 {
+        Check::Ok(format!("Executing the harness hits up to {edges} edges"))
+    } else {
+        Check::Error(format!(
+            "Executing the harness hits no edge: make sure it calls the fuzzed package, and \
+             that this package is instrumented ({REBUILD_HINT})"
+        ))
+    });
+    // RedQueen builds log the comparisons to AFL++'s extended map instead
+    if cfg!(feature = "redqueen") {
+        return checks;
+    }
+    checks.push(if cmps > 0 {
+        Check::Ok(format!(
+            "Comparison tracing hooks are called ({cmps} comparisons logged)"
+        ))
+    } else {
+        Check::Warning(format!(
+            "No comparison logged: the cmplog stages will be useless, {REBUILD_HINT}"
+        ))
+    });
+    checks
+}
+
+// Preflight checks of the linked Go target, exiting with 1 if one of them failed
+pub(crate) fn doctor() {
+    let mut checks = check_symbols();
+    initialize();
+    checks.extend(check_instrumentation());
+
+    let mut healthy = true;
+    for check in &checks {
+        healthy &= check.print();
+    }
+    if !healthy {
+        process::exit(1);
+    }
+    println!("The target is ready to be fuzzed");
+}
@@ -0,0 +1,176 @@
+use crate::{control::dump_requests, stats::ClientStats};
+use libafl::{
+    corpus::{Corpus, CorpusId, SchedulerTestcaseMetadata},
+    inputs::{BytesInput, HasTargetBytes},
+    mutators::Tokens,
+    schedulers::{
+        powersched::SchedulerMetadata,
+        testcase_score::{CorpusWeightTestcaseScore, TestcaseScore},
+    },
+    state::{HasCorpus, HasSolutions},
+    Error, HasMetadata,
+};
+use std::{
+    fmt::Write,
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+// Directory, in the output one, of the clients' state dumps
+const DUMPS_DIR: &str = "dumps";
+// Number of corpus entries with the highest scheduling weights in a dump
+const TOP_ENTRIES: usize = 10;
+// Number of the latest objectives in a dump
+const LATEST_OBJECTIVES: usize = 10;
+
+// Writes a human-readable snapshot of a client to `<output>/dumps/<client>` when a dump is
+// requested, to find out why its coverage stopped growing without restarting it
+pub(crate) struct StateDumps {
+    path: PathBuf,
+    client: usize,
+    // Requests already answered, those made before the client started included
+    answered: u32,
+}
+
+impl StateDumps {
+    pub(crate) fn new(output: &Path, client: usize) -> Result<Self, Error> {
+        let dir = output.join(DUMPS_DIR);
+        create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(client.to_string()),
+            client,
+            answered: dump_requests(),
+        })
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        dump_requests() != self.answered
+    }
+
+    pub(crate) fn write<S>(&mut self, state: &S, stats: &ClientStats) -> Result<(), Error>
+    where
+        S: HasCorpus<BytesInput> + HasSolutions<BytesInput> + HasMetadata,
+    {
+        self.answered = dump_requests();
+        let mut dump = String::new();
+        // Writing to a String can't fail
+        let _ = self.describe(&mut dump, state, stats);
+        write(&self.path, dump)?;
+        eprintln!(
+            "Client {} dumped its state to {}",
+            self.client,
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    fn describe<S>(&self, dump: &mut String, state: &S, stats: &ClientStats) -> std::fmt::Result
+    where
+        S: HasCorpus<BytesInput> + HasSolutions<BytesInput> + HasMetadata,
+    {
+        writeln!(dump, "Client {}", self.client)?;
+        writeln!(dump, "Executions: {}", stats.executions)?;
+        writeln!(
+            dump,
+            "Corpus: {} entries, {} of {} edges covered",
+            stats.corpus, stats.edges, stats.edges_total
+        )?;
+        match stats.stability {
+            Some(stability) => writeln!(dump, "Stability: {stability:.2}%")?,
+            None => writeln!(dump, "Stability: unknown")?,
+        }
+        let tokens = state.metadata_map().get::<Tokens>().map_or(0, Tokens::len);
+        writeln!(dump, "Tokens: {tokens}")?;
+        if let Ok(psmeta) = state.metadata::<SchedulerMetadata>() {
+            let average = u32::try_from(psmeta.cycles())
+                .ok()
+                .filter(|&cycles| cycles > 0)
+                .map_or_else(
+                    || "unknown".to_string(),
+                    |cycles| format!("{:?}", psmeta.exec_time() / cycles),
+                );
+            writeln!(
+                dump,
+                "Power schedule: {} queue cycles, {} calibrated entries, average execution time \
+                 {average}",
+                psmeta.queue_cycles(),
+                psmeta.bitmap_entries()
+            )?;
+        }
+
+        writeln!(dump, "\nCurrent entry:")?;
+        match state.corpus().current() {
+            Some(id) => writeln!(dump, "  {}", describe_entry(state, *id))?,
+            None => writeln!(dump, "  none")?,
+        }
+
+        // Only the entries already calibrated have a weight
+        let mut weights = state
+            .corpus()
+            .ids()
+            .filter_map(|id| {
+                let mut testcase = state.corpus().get(id).ok()?.borrow_mut();
+                let weight = CorpusWeightTestcaseScore::compute(state, &mut testcase).ok()?;
+                Some((weight, id))
+            })
+            .collect::<Vec<_>>();
+        weights.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        writeln!(dump, "\nHighest scheduling weights:")?;
+        for (weight, id) in weights.into_iter().take(TOP_ENTRIES) {
+            writeln!(dump, "  {weight:.4}  {}", describe_entry(state, id))?;
+        }
+
+        // Most productive first
+        let mut mutations = stats.mutations.iter().collect::<Vec<_>>();
+        mutations.sort_by(|(_, a), (_, b)| b.corpus.cmp(&a.corpus));
+        writeln!(
+            dump,
+            "\nMutators (mutants, corpus entries, hit rate, objectives):"
+        )?;
+        for (name, hits) in mutations {
+            writeln!(
+                dump,
+                "  {name:<32} {:>10} {:>8} {:>8.4}% {:>6}",
+                hits.mutants,
+                hits.corpus,
+                hits.hit_rate(),
+                hits.objectives
+            )?;
+        }
+
+        let objectives = state.solutions().ids().collect::<Vec<_>>();
+        writeln!(dump, "\nLatest objectives:")?;
+        for id in objectives.into_iter().rev().take(LATEST_OBJECTIVES) {
+            let name = state
+                .solutions()
+                .get(id)
+                .ok()
+                .and_then(|testcase| testcase.borrow().filename().clone());
+            writeln!(dump, "  {}", name.unwrap_or_else(|| format!("#{id}")))?;
+        }
+        Ok(())
+    }
+}
+
+// Id, size, depth, times fuzzed and execution time of a corpus entry
+fn describe_entry<S: HasCorpus<BytesInput>>(state: &S, id: CorpusId) -> String {
+    let Ok(testcase) = state.corpus().get(id) else {
+        return format!("#{id}");
+    };
+    let size = state
+        .corpus()
+        .cloned_input_for_id(id)
+        .map_or(0, |input| input.target_bytes().len());
+    let testcase = testcase.borrow();
+    let depth = testcase
+        .metadata::<SchedulerTestcaseMetadata>()
+        .map_or(0, SchedulerTestcaseMetadata::depth);
+    let exec_time = testcase.exec_time().map_or_else(
+        || "unknown".to_string(),
+        |exec_time| format!("{exec_time:?}"),
+    );
+    format!(
+        "#{id}: {size} bytes, depth {depth}, fuzzed {} times, execution time {exec_time}",
+        testcase.scheduled_count()
+    )
+}
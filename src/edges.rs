@@ -0,0 +1,174 @@
+use libafl::observers::{MapObserver, StdMapObserver};
+use libafl_targets::{
+    extra_counters, libfuzzer::libfuzzer_test_one_input, CMP_MAP, CMP_MAP_SIZE, COUNTERS_MAPS,
+    EDGES_MAP, EDGES_MAP_DEFAULT_SIZE,
+};
+use std::ptr::addr_of_mut;
+
+// AFL-style hitcount bucket, so that loop iteration counts don't make every input unique
+pub(crate) fn count_class(count: u8) -> u8 {
+    match count {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 4,
+        4..=7 => 8,
+        8..=15 => 16,
+        16..=31 => 32,
+        32..=127 => 64,
+        128..=255 => 128,
+    }
+}
+
+// Contiguous mirror of the counters maps, only used when the target registers more than one, or
+// when the executions are forked
+static mut COMBINED_EDGES: &mut [u8] = &mut [];
+
+// Indexes of the edges map cleared after every execution, to ignore the edges they count
+static mut MASKED_EDGES: Vec<usize> = Vec::new();
+
+pub(crate) fn mask_edges(edges: Vec<usize>) {
+    unsafe {
+        MASKED_EDGES = edges;
+    }
+}
+
+// Go usually registers a single counters map, which is observed in place with a StdMapObserver.
+// Targets built from several instrumented archives register more: instead of switching the whole
+// pipeline to a MultiMapObserver, they are mirrored into one contiguous map by `sync_edges`.
+#[allow(static_mut_refs)]
+pub(crate) fn edges_observer() -> StdMapObserver<'static, u8, false> {
+    let mut edges = unsafe { extra_counters() };
+    if edges.len() == 1 {
+        return StdMapObserver::from_mut_slice("edges", edges.pop().unwrap());
+    }
+    assert!(
+        !edges.is_empty(),
+        "No counters map registered by the target, run `golibafl doctor` to diagnose the build"
+    );
+
+    println!("Combining {} counters maps into a single one", edges.len());
+    let len = edges.iter().map(|map| map.len()).sum();
+    combined_edges_observer(Vec::leak(vec![0; len]))
+}
+
+// Edges observer of forked executions: the counters maps of the child are mirrored by
+// `sync_edges` into a map in shared memory, which the parent observes
+#[allow(static_mut_refs)]
+pub(crate) fn shared_edges_observer() -> StdMapObserver<'static, u8, false> {
+    let len: usize = unsafe { COUNTERS_MAPS.iter().map(|map| map.len()).sum() };
+    assert!(
+        len > 0,
+        "No counters map registered by the target, run `golibafl doctor` to diagnose the build"
+    );
+    let map = unsafe {
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert!(
+            ptr != libc::MAP_FAILED,
+            "Failed to map the shared edges map"
+        );
+        std::slice::from_raw_parts_mut(ptr.cast::<u8>(), len)
+    };
+    combined_edges_observer(map)
+}
+
+#[allow(static_mut_refs)]
+fn combined_edges_observer(map: &'static mut [u8]) -> StdMapObserver<'static, u8, false> {
+    unsafe {
+        for map in COUNTERS_MAPS.iter_mut() {
+            map.fill(0);
+        }
+        COMBINED_EDGES = map;
+        StdMapObserver::from_mut_ptr("edges", COMBINED_EDGES.as_mut_ptr(), COMBINED_EDGES.len())
+    }
+}
+
+// Copy the counters maps into the combined map and reset them, then clear the masked edges. A
+// no-op for single map targets without masked edges. Must be called after every execution of the
+// target.
+#[allow(static_mut_refs)]
+pub(crate) fn sync_edges() {
+    unsafe {
+        if !COMBINED_EDGES.is_empty() {
+            let mut offset = 0;
+            for map in COUNTERS_MAPS.iter_mut() {
+                COMBINED_EDGES[offset..offset + map.len()].copy_from_slice(map);
+                offset += map.len();
+                map.fill(0);
+            }
+        }
+        if !MASKED_EDGES.is_empty() {
+            let edges: &mut [u8] = if COMBINED_EDGES.is_empty() {
+                &mut COUNTERS_MAPS[0]
+            } else {
+                &mut *COMBINED_EDGES
+            };
+            for &edge in MASKED_EDGES.iter() {
+                edges[edge] = 0;
+            }
+        }
+    }
+}
+
+// Value profile map: the comparison hooks of the target set an entry per comparison site and
+// number of equal bits of the operands, so that partial progress towards matching a comparison is
+// observed as new coverage. Unless `enabled`, the observer covers none of the map, so that the
+// executions don't pay for resetting and reading it, while keeping the observers' type.
+pub(crate) fn value_profile_observer(enabled: bool) -> StdMapObserver<'static, u8, false> {
+    let len = if enabled { CMP_MAP_SIZE } else { 0 };
+    unsafe { StdMapObserver::from_mut_ptr("value_profile", addr_of_mut!(CMP_MAP).cast(), len) }
+}
+
+// Coverage of the code instrumented with `-fsanitize-coverage=trace-pc-guard`, usually the C code
+// of cgo targets: the Go compiler only emits the inline counters of the edges map. Built with the
+// `ngram` or `ctx` feature, each entry stands for an edge along with the 4 previous ones, or with
+// its calling context, instead of a single edge. The whole map is observed, since these entries
+// are spread over all of it. Unless `enabled`, the observer covers none of the map, like the value
+// profile one.
+pub(crate) fn pcguard_observer(enabled: bool) -> StdMapObserver<'static, u8, false> {
+    let len = if enabled { EDGES_MAP_DEFAULT_SIZE } else { 0 };
+    unsafe { StdMapObserver::from_mut_ptr("pcguard_edges", addr_of_mut!(EDGES_MAP).cast(), len) }
+}
+
+// Number of edges hit since the map was last reset, for `run`. The fuzzer's feedbacks count them
+// on the edges observer instead, the harness having already moved the counters into its map.
+#[allow(static_mut_refs)]
+pub(crate) fn covered_edges() -> usize {
+    sync_edges();
+    unsafe {
+        if COMBINED_EDGES.is_empty() {
+            COUNTERS_MAPS
+                .iter()
+                .map(|map| map.iter().filter(|&&c| c != 0).count())
+                .sum()
+        } else {
+            COMBINED_EDGES.iter().filter(|&&c| c != 0).count()
+        }
+    }
+}
+
+// Execute a single input and return its bucketed edges map
+pub(crate) fn trace(
+    edges_observer: &mut StdMapObserver<'static, u8, false>,
+    inp: &[u8],
+) -> Vec<u8> {
+    edges_observer
+        .reset_map()
+        .expect("Failed to reset the edges map");
+    unsafe {
+        libfuzzer_test_one_input(inp);
+    }
+    sync_edges();
+    edges_observer
+        .to_vec()
+        .into_iter()
+        .map(count_class)
+        .collect()
+}
@@ -0,0 +1,43 @@
+// Symbol of an ELF symbol table
+pub(crate) struct Symbol {
+    pub(crate) name: String,
+    // Address of the symbol, relative to the load address of position-independent executables
+    pub(crate) value: usize,
+}
+
+// Symbols in the ELF symbol table of `exe`, or `None` if it has none (stripped or not a 64-bit
+// little-endian ELF)
+pub(crate) fn elf_symbols(exe: &[u8]) -> Option<Vec<Symbol>> {
+    let u16_at = |off: usize| Some(u16::from_le_bytes(exe.get(off..off + 2)?.try_into().ok()?));
+    let u32_at = |off: usize| Some(u32::from_le_bytes(exe.get(off..off + 4)?.try_into().ok()?));
+    let u64_at =
+        |off: usize| Some(u64::from_le_bytes(exe.get(off..off + 8)?.try_into().ok()?) as usize);
+    // ELFCLASS64, ELFDATA2LSB
+    if exe.get(..6)? != b"\x7fELF\x02\x01" {
+        return None;
+    }
+    let shoff = u64_at(0x28)?;
+    let shentsize = usize::from(u16_at(0x3a)?);
+    let shnum = usize::from(u16_at(0x3c)?);
+
+    let mut symbols = Vec::new();
+    for i in 0..shnum {
+        let sh = shoff + i * shentsize;
+        // SHT_SYMTAB
+        if u32_at(sh + 4)? != 2 {
+            continue;
+        }
+        let (offset, size, entsize) = (u64_at(sh + 0x18)?, u64_at(sh + 0x20)?, u64_at(sh + 0x38)?);
+        let strtab = shoff + u32_at(sh + 0x28)? as usize * shentsize;
+        let stroff = u64_at(strtab + 0x18)?;
+        for sym in (offset..offset + size).step_by(entsize.max(1)) {
+            let start = stroff + u32_at(sym)? as usize;
+            let len = exe.get(start..)?.iter().position(|&b| b == 0)?;
+            symbols.push(Symbol {
+                name: String::from_utf8_lossy(&exe[start..start + len]).into_owned(),
+                value: u64_at(sym + 8)?,
+            });
+        }
+    }
+    (!symbols.is_empty()).then_some(symbols)
+}
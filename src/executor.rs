@@ -0,0 +1,74 @@
+use libafl::{
+    executors::{Executor, ExitKind, HasObservers, HasTimeout},
+    Error,
+};
+use libafl_bolts::tuples::RefIndexable;
+use std::time::Duration;
+
+// One of two executors sharing the same observers, chosen at runtime, so that the stages built on
+// top of it keep a single type
+pub(crate) enum EitherExecutor<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A, B, EM, I, S, Z> Executor<EM, I, S, Z> for EitherExecutor<A, B>
+where
+    A: Executor<EM, I, S, Z>,
+    B: Executor<EM, I, S, Z>,
+{
+    fn run_target(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        mgr: &mut EM,
+        input: &I,
+    ) -> Result<ExitKind, Error> {
+        match self {
+            Self::Left(executor) => executor.run_target(fuzzer, state, mgr, input),
+            Self::Right(executor) => executor.run_target(fuzzer, state, mgr, input),
+        }
+    }
+}
+
+impl<A, B> HasObservers for EitherExecutor<A, B>
+where
+    A: HasObservers,
+    B: HasObservers<Observers = A::Observers>,
+{
+    type Observers = A::Observers;
+
+    fn observers(&self) -> RefIndexable<&Self::Observers, Self::Observers> {
+        match self {
+            Self::Left(executor) => executor.observers(),
+            Self::Right(executor) => executor.observers(),
+        }
+    }
+
+    fn observers_mut(&mut self) -> RefIndexable<&mut Self::Observers, Self::Observers> {
+        match self {
+            Self::Left(executor) => executor.observers_mut(),
+            Self::Right(executor) => executor.observers_mut(),
+        }
+    }
+}
+
+impl<A, B> HasTimeout for EitherExecutor<A, B>
+where
+    A: HasTimeout,
+    B: HasTimeout,
+{
+    fn timeout(&self) -> Duration {
+        match self {
+            Self::Left(executor) => executor.timeout(),
+            Self::Right(executor) => executor.timeout(),
+        }
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        match self {
+            Self::Left(executor) => executor.set_timeout(timeout),
+            Self::Right(executor) => executor.set_timeout(timeout),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{
+    compress::read_input_file, fuzz::stored_files, minimize::select_minimal, run::collect_inputs,
+};
+use std::{
+    collections::HashSet,
+    io::Write,
+    os::unix::ffi::OsStrExt,
+    path::{absolute, Path, PathBuf},
+    process::{self, Command, Stdio},
+};
+
+// Package the queue of the campaign in `output` into the `archive` file, for handing it off
+// between CI runs or machines. tar compresses it according to the extension of the archive, e.g.
+// `.tar.zst`, `.tar.gz` or `.tar.xz`. With `minimize`, only the entries `cmin` keeps are
+// packaged, and with `metadata`, the LibAFL metadata file of each entry is packaged along.
+pub(crate) fn export_queue(output: &Path, archive: &Path, minimize: bool, metadata: bool) {
+    let queue = output.join("queue");
+    // Each client has its own queue, in `<output>/queue/<client>`. Entries are named after their
+    // content, the ones several clients share are packaged once.
+    let mut seen = HashSet::new();
+    let files: Vec<PathBuf> = stored_files(&queue)
+        .filter(|client| client.path().is_dir())
+        .flat_map(|client| collect_inputs(&client.path()))
+        .filter(|file| seen.insert(file.file_name().unwrap().to_os_string()))
+        .collect();
+    let entries = if minimize {
+        select_minimal(&files, read_input_file).0
+    } else {
+        files.iter().collect()
+    };
+    if entries.is_empty() {
+        eprintln!("No queue entries to export in {}", queue.display());
+        process::exit(1);
+    }
+    let mut names = Vec::new();
+    for entry in &entries {
+        let name = entry.strip_prefix(&queue).unwrap().to_path_buf();
+        let file_name = name.file_name().unwrap().to_string_lossy();
+        let metadata_name = name.with_file_name(format!(".{file_name}.metadata"));
+        if metadata && queue.join(&metadata_name).is_file() {
+            names.push(metadata_name);
+        }
+        names.push(name);
+    }
+
+    // The names are read from stdin, as a large queue exceeds the limit of the arguments
+    let archive_path = absolute(archive).unwrap_or_else(|_| archive.to_path_buf());
+    let mut tar = Command::new("tar")
+        .arg("-caf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(&queue)
+        .args(["--null", "-T", "-"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Failed to run tar");
+    let mut stdin = tar.stdin.take().unwrap();
+    for name in &names {
+        stdin
+            .write_all(name.as_os_str().as_bytes())
+            .and_then(|()| stdin.write_all(b"\0"))
+            .expect("Failed to pass the entries to tar");
+    }
+    drop(stdin);
+    let status = tar.wait().expect("Failed to wait for tar");
+    if !status.success() {
+        eprintln!(
+            "Failed to write {}: tar exited with {status}",
+            archive.display()
+        );
+        process::exit(1);
+    }
+    println!(
+        "Exported {} queue entries{} to {}",
+        entries.len(),
+        if metadata { " with their metadata" } else { "" },
+        archive.display()
+    );
+}
@@ -0,0 +1,222 @@
+use libafl::{
+    corpus::{Corpus, OnDiskCorpus, Testcase},
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    observers::Observer,
+    Error,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::read_dir,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+// Interval between two counts of the open file descriptors of a client
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// Number of consecutive growing counts making a client warn about a leak
+const GROWING_CHECKS: usize = 5;
+// Inputs leaking descriptors stored at most, the first ones being enough to debug the leaks
+const MAX_STORED_LEAKS: usize = 100;
+
+// Number of file descriptors open in the process
+fn open_fds() -> Result<usize, Error> {
+    // The directory's own descriptor is counted too, the same for every count
+    Ok(read_dir("/proc/self/fd")?.count())
+}
+
+// Periodically counts the open file descriptors of a client, warning when they kept growing over
+// the last checks. Harnesses opening files without closing them eventually make every execution
+// fail with EMFILE, which otherwise looks like the target breaking for no reason.
+pub(crate) struct FdLeakCheck {
+    client: usize,
+    last: Instant,
+    counts: Vec<usize>,
+    // Whether the growth was already reported
+    warned: bool,
+}
+
+impl FdLeakCheck {
+    pub(crate) fn new(client: usize) -> Self {
+        Self {
+            client,
+            last: Instant::now(),
+            counts: Vec::new(),
+            warned: false,
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= CHECK_INTERVAL
+    }
+
+    pub(crate) fn check(&mut self, leaks_stored: bool) -> Result<(), Error> {
+        self.last = Instant::now();
+        let count = open_fds()?;
+        // Only the growing run of counts is kept
+        if self.counts.last().is_some_and(|&last| count <= last) {
+            self.counts.clear();
+            self.warned = false;
+        }
+        self.counts.push(count);
+        if self.counts.len() > GROWING_CHECKS && !self.warned {
+            let limit = unsafe {
+                let mut limit: libc::rlimit = std::mem::zeroed();
+                libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit);
+                limit.rlim_cur
+            };
+            eprintln!(
+                "\x1b[31mWARNING: client {} open file descriptors kept growing, from {} to \
+                 {count} of {limit} allowed. The harness likely opens files or connections \
+                 without closing them, and executions will fail with EMFILE once the limit is \
+                 reached.{}\x1b[0m",
+                self.client,
+                self.counts[0],
+                if leaks_stored {
+                    ""
+                } else {
+                    " Pass --fd-leaks to store the inputs leaking descriptors."
+                }
+            );
+            self.warned = true;
+        }
+        Ok(())
+    }
+}
+
+// Observer counting the file descriptors an execution left open. Listing them costs a few system
+// calls per execution, so it is only enabled on demand.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct FdObserver {
+    name: Cow<'static, str>,
+    enabled: bool,
+    #[serde(skip)]
+    fds_before: usize,
+    #[serde(skip)]
+    leaked: usize,
+}
+
+impl FdObserver {
+    // Count the descriptors if `enabled`, otherwise the observer does nothing
+    pub(crate) fn new(name: &'static str, enabled: bool) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            enabled,
+            fds_before: 0,
+            leaked: 0,
+        }
+    }
+
+    // Descriptors opened and not closed by the last execution
+    pub(crate) fn leaked(&self) -> usize {
+        self.leaked
+    }
+}
+
+impl Named for FdObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for FdObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        if self.enabled {
+            self.fds_before = open_fds()?;
+        }
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        if self.enabled {
+            self.leaked = open_fds()?.saturating_sub(self.fds_before);
+        }
+        Ok(())
+    }
+}
+
+// Objective feedback storing the inputs leaving file descriptors open in their own corpus, one per
+// number of leaked descriptors and up to `MAX_STORED_LEAKS`, as a harness leaking on every
+// execution would otherwise store every input. The solutions corpus is reserved to crashes.
+// Descriptors closed asynchronously, e.g. by a goroutine or a finalizer, may be reported too.
+pub(crate) struct FdLeakFeedback<I> {
+    observer_handle: Handle<FdObserver>,
+    leaks: Option<OnDiskCorpus<I>>,
+    // Numbers of leaked descriptors an input was stored for
+    seen: HashSet<usize>,
+    // Inputs in the leaks directory, including the ones of the other clients when starting
+    stored: usize,
+}
+
+impl<I> FdLeakFeedback<I>
+where
+    I: Input,
+{
+    // Inputs leaking descriptors are stored in `dir`. Without a directory, the feedback does
+    // nothing.
+    pub(crate) fn new(observer: &FdObserver, dir: Option<PathBuf>) -> Result<Self, Error> {
+        let stored = dir.as_deref().map_or(0, |dir| {
+            read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+                .count()
+        });
+        Ok(Self {
+            observer_handle: observer.handle(),
+            leaks: dir.map(OnDiskCorpus::new).transpose()?,
+            seen: HashSet::new(),
+            stored,
+        })
+    }
+}
+
+impl<I> Named for FdLeakFeedback<I> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("FdLeakFeedback");
+        &NAME
+    }
+}
+
+impl<I, S> StateInitializer<S> for FdLeakFeedback<I> {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for FdLeakFeedback<I>
+where
+    I: Input,
+    OT: MatchNameRef,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let Some(leaks) = self.leaks.as_mut() else {
+            return Ok(false);
+        };
+        let observer = observers
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::key_not_found("FdObserver not found"))?;
+        let leaked = observer.leaked();
+        if leaked > 0 && self.stored < MAX_STORED_LEAKS && self.seen.insert(leaked) {
+            leaks.add(Testcase::new(input.clone()))?;
+            self.stored += 1;
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,283 @@
+use crate::mutators::{below, decoded_mutator};
+use libafl::{
+    corpus::CorpusId,
+    inputs::BytesInput,
+    mutators::{MutationResult, Mutator, Tokens},
+    state::HasRand,
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    rands::Rand,
+    tuples::{tuple_list, tuple_list_type},
+    Named,
+};
+use std::{borrow::Cow, num::NonZeroUsize};
+
+// Length go-fuzz-headers reads a zero length prefix as
+const ZERO_LENGTH: usize = 30;
+
+// Inputs of go-118-fuzz-build harnesses, whose arguments are decoded by go-fuzz-headers: each
+// `[]byte` or `string` argument is a field prefixed by its big-endian `uint32` length. Like
+// go-fuzz-headers' `GetBytes`, a zero length stands for 30 bytes, and lengths other than the
+// number of bytes left are taken modulo it. The bytes left once no field can be read (fewer than
+// a length, or a length with nothing after it) are kept as an opaque tail.
+pub(crate) struct FramedInput {
+    pub fields: Vec<Vec<u8>>,
+    pub tail: Vec<u8>,
+}
+
+impl FramedInput {
+    pub(crate) fn parse(mut bytes: &[u8]) -> Self {
+        let mut fields = Vec::new();
+        while let Some((len, rest)) = bytes.split_first_chunk::<4>() {
+            if rest.is_empty() {
+                break;
+            }
+            let len = match u32::from_be_bytes(*len) as usize {
+                0 => ZERO_LENGTH,
+                len => len,
+            };
+            let len = if len == rest.len() {
+                len
+            } else {
+                len % rest.len()
+            };
+            fields.push(rest[..len].to_vec());
+            bytes = &rest[len..];
+        }
+        Self {
+            fields,
+            tail: bytes.to_vec(),
+        }
+    }
+
+    // Encode the fields so that go-fuzz-headers decodes them back. Empty fields are encoded as
+    // a multiple of the bytes left, which can't be done for a last one without a tail.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        // Bytes following the length of the current field
+        let mut left = self.tail.len() + self.fields.iter().map(|f| 4 + f.len()).sum::<usize>();
+        for field in &self.fields {
+            left -= 4;
+            let len = if field.is_empty() {
+                2 * left
+            } else {
+                field.len()
+            };
+            bytes.extend_from_slice(&(len as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+            left -= field.len();
+        }
+        bytes.extend_from_slice(&self.tail);
+        bytes
+    }
+}
+
+// Random field of `input`, or `None` if it has none
+fn field<'a, S: HasRand>(state: &mut S, input: &'a mut FramedInput) -> Option<&'a mut Vec<u8>> {
+    if input.fields.is_empty() {
+        return None;
+    }
+    let i = below(state, input.fields.len());
+    Some(&mut input.fields[i])
+}
+
+macro_rules! field_mutator {
+    ($name:ident, |$state:ident, $input:ident| $mutate:block) => {
+        decoded_mutator!(
+            $name,
+            FramedInput::parse,
+            |input: FramedInput| input.to_bytes(),
+            |$state, $input| $mutate
+        );
+    };
+}
+
+field_mutator!(
+    // Flip a bit, or replace a byte of a field
+    FieldByteMutator,
+    |state, input| {
+        let flip = state.rand_mut().coinflip(0.5);
+        let value = below(state, 256) as u8;
+        match field(state, &mut input) {
+            Some(field) if !field.is_empty() => {
+                let i = state
+                    .rand_mut()
+                    .below(NonZeroUsize::new(field.len()).unwrap());
+                if flip {
+                    field[i] ^= 1 << (value % 8);
+                } else {
+                    field[i] = value;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+);
+
+field_mutator!(
+    // Insert random bytes in a field
+    FieldInsertMutator,
+    |state, input| {
+        let count = 1 + below(state, 16);
+        let inserted: Vec<u8> = (0..count).map(|_| below(state, 256) as u8).collect();
+        match field(state, &mut input) {
+            Some(field) => {
+                let at = state
+                    .rand_mut()
+                    .below(NonZeroUsize::new(field.len() + 1).unwrap());
+                field.splice(at..at, inserted);
+                true
+            }
+            None => false,
+        }
+    }
+);
+
+field_mutator!(
+    // Delete a range of bytes of a field
+    FieldDeleteMutator,
+    |state, input| {
+        match field(state, &mut input) {
+            Some(field) if !field.is_empty() => {
+                let start = state
+                    .rand_mut()
+                    .below(NonZeroUsize::new(field.len()).unwrap());
+                let len = 1 + state
+                    .rand_mut()
+                    .below(NonZeroUsize::new(field.len() - start).unwrap());
+                field.drain(start..start + len);
+                true
+            }
+            _ => false,
+        }
+    }
+);
+
+field_mutator!(
+    // Replace a field by a token of the dictionaries, or insert the token in it
+    FieldTokenMutator,
+    |state, input| {
+        let tokens = state
+            .metadata_map()
+            .get::<Tokens>()
+            .map(|tokens| tokens.tokens().to_vec())
+            .unwrap_or_default();
+        if tokens.is_empty() {
+            false
+        } else {
+            let token = tokens[below(state, tokens.len())].clone();
+            let replace = state.rand_mut().coinflip(0.5);
+            match field(state, &mut input) {
+                Some(field) if replace => {
+                    *field = token;
+                    true
+                }
+                Some(field) => {
+                    let at = state
+                        .rand_mut()
+                        .below(NonZeroUsize::new(field.len() + 1).unwrap());
+                    field.splice(at..at, token);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+);
+
+field_mutator!(
+    // Copy a field over another one
+    FieldCopyMutator,
+    |state, input| {
+        if input.fields.len() < 2 {
+            false
+        } else {
+            let from = below(state, input.fields.len());
+            let to = below(state, input.fields.len());
+            input.fields[to] = input.fields[from].clone();
+            from != to
+        }
+    }
+);
+
+field_mutator!(
+    // Append a field of random bytes, or remove the last one, for harnesses with optional
+    // trailing arguments
+    FieldCountMutator,
+    |state, input| {
+        if input.fields.is_empty() || state.rand_mut().coinflip(0.5) {
+            let count = 1 + below(state, 16);
+            let field = (0..count).map(|_| below(state, 256) as u8).collect();
+            input.fields.push(field);
+        } else {
+            input.fields.pop();
+        }
+        true
+    }
+);
+
+// Type of the field-aligned mutations
+pub(crate) type FieldMutationsType = tuple_list_type!(
+    FieldByteMutator,
+    FieldInsertMutator,
+    FieldDeleteMutator,
+    FieldTokenMutator,
+    FieldCopyMutator,
+    FieldCountMutator
+);
+
+// Mutations of go-118-fuzz-build inputs, keeping the length prefixes of the fields consistent
+pub(crate) fn field_mutations() -> FieldMutationsType {
+    tuple_list!(
+        FieldByteMutator,
+        FieldInsertMutator,
+        FieldDeleteMutator,
+        FieldTokenMutator,
+        FieldCopyMutator,
+        FieldCountMutator
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framed(fields: &[&[u8]], tail: &[u8]) -> FramedInput {
+        FramedInput {
+            fields: fields.iter().map(|field| field.to_vec()).collect(),
+            tail: tail.to_vec(),
+        }
+    }
+
+    #[test]
+    fn lengths_are_decoded_like_go_fuzz_headers() {
+        // 5 % 3 bytes left
+        let input = FramedInput::parse(&[0, 0, 0, 5, b'a', b'b', b'c']);
+        assert_eq!(input.fields, [b"ab".to_vec()]);
+        assert_eq!(input.tail, b"c");
+        // Zero reads as 30, the bytes left
+        let mut bytes = vec![0, 0, 0, 0];
+        bytes.extend([b'x'; 30]);
+        assert_eq!(FramedInput::parse(&bytes).fields, [vec![b'x'; 30]]);
+        // A length with nothing after it
+        let input = FramedInput::parse(&[0, 0, 0, 1, b'a', 0, 0, 0, 1]);
+        assert_eq!(input.fields, [b"a".to_vec()]);
+        assert_eq!(input.tail, [0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn fields_are_decoded_back() {
+        for input in [
+            framed(&[b"abc", b"", b"de"], b""),
+            framed(&[b"", b"xyz"], b"t"),
+            framed(&[b"abc"], b"\x01"),
+            framed(&[b"abc", b""], b"\x01\x02"),
+        ] {
+            let parsed = FramedInput::parse(&input.to_bytes());
+            assert_eq!(parsed.fields, input.fields);
+            assert_eq!(parsed.tail, input.tail);
+        }
+    }
+}
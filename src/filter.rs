@@ -0,0 +1,107 @@
+use libafl::{
+    corpus::CorpusId,
+    inputs::{BytesInput, HasTargetBytes},
+    mutators::{MutationResult, Mutator},
+    Error,
+};
+use libafl_bolts::Named;
+use libloading::Library;
+use std::{borrow::Cow, path::Path, sync::OnceLock};
+
+// Function a filter plugin exports, in C:
+//   int golibafl_filter(const uint8_t *data, size_t size);
+// It returns non-zero to execute the input, or 0 to skip it.
+const FILTER_SYMBOL: &[u8] = b"golibafl_filter\0";
+
+type FilterFn = unsafe extern "C" fn(*const u8, usize) -> libc::c_int;
+
+// Constraints the inputs must meet to be executed, for targets with strict framing requirements
+// which reject most inputs early anyway
+struct InputFilter {
+    min_len: usize,
+    prefix: Vec<u8>,
+    // Kept loaded as long as the filter may be called
+    plugin: Option<(Library, FilterFn)>,
+}
+
+static FILTER: OnceLock<InputFilter> = OnceLock::new();
+
+// Skip the inputs shorter than `min_len`, not starting with `prefix`, or which the
+// `golibafl_filter` function of the dynamic library `plugin` rejects. Installed once per process.
+pub(crate) fn install_filter(
+    min_len: Option<usize>,
+    prefix: Option<&[u8]>,
+    plugin: Option<&Path>,
+) -> Result<(), Error> {
+    if FILTER.get().is_some() || (min_len.is_none() && prefix.is_none() && plugin.is_none()) {
+        return Ok(());
+    }
+    let plugin = plugin
+        .map(|path| {
+            let invalid = |err: libloading::Error| {
+                Error::illegal_argument(format!(
+                    "Failed to load the filter plugin {}: {err}",
+                    path.display()
+                ))
+            };
+            // Loading runs the library's initializers, which the user trusts by passing it
+            let library = unsafe { Library::new(path) }.map_err(invalid)?;
+            let filter = *unsafe { library.get::<FilterFn>(FILTER_SYMBOL) }.map_err(invalid)?;
+            Ok::<_, Error>((library, filter))
+        })
+        .transpose()?;
+    let _ = FILTER.set(InputFilter {
+        min_len: min_len.unwrap_or(0),
+        prefix: prefix.map(<[u8]>::to_vec).unwrap_or_default(),
+        plugin,
+    });
+    Ok(())
+}
+
+// Whether `buf` must be skipped instead of executed
+pub(crate) fn skips(buf: &[u8]) -> bool {
+    let Some(filter) = FILTER.get() else {
+        return false;
+    };
+    buf.len() < filter.min_len
+        || !buf.starts_with(&filter.prefix)
+        || filter
+            .plugin
+            .as_ref()
+            .is_some_and(|(_, accepts)| unsafe { accepts(buf.as_ptr(), buf.len()) == 0 })
+}
+
+// Mutator skipping the mutants the filter rejects, so that the mutational stages don't execute
+// them at all
+pub(crate) struct FilteredMutator<M> {
+    inner: M,
+}
+
+impl<M> FilteredMutator<M> {
+    pub(crate) fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<M: Named> Named for FilteredMutator<M> {
+    fn name(&self) -> &Cow<'static, str> {
+        self.inner.name()
+    }
+}
+
+impl<M, S> Mutator<BytesInput, S> for FilteredMutator<M>
+where
+    M: Mutator<BytesInput, S>,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let result = self.inner.mutate(state, input)?;
+        if result == MutationResult::Mutated && skips(&input.target_bytes()) {
+            return Ok(MutationResult::Skipped);
+        }
+        Ok(result)
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
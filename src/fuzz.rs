@@ -0,0 +1,1403 @@
+#[cfg(feature = "cloud")]
+use crate::cloud::{download_queue, CloudSync};
+#[cfg(not(feature = "redqueen"))]
+use crate::cmplog::InstructionsOnlyObserver;
+#[cfg(feature = "grammar")]
+use crate::grammar::{generate_initial_inputs, grammar_stage, load_grammar};
+#[cfg(target_os = "linux")]
+use crate::sandbox::sandbox;
+#[cfg(feature = "scripting")]
+use crate::script::ScriptFeedback;
+use crate::{
+    batch::{self, BatchStage, NotInBatchFeedback, NotRejectedFeedback},
+    calibration::QuickCalibrationStage,
+    cmplog::{CmpLogMode, CmpLogSchedule},
+    compress::{read_input_file, QueueCorpus},
+    control::{share_requests, wait_while_paused},
+    cull::CorpusCuller,
+    dict::{DictWriter, LEARNED_DICT},
+    diff::{load_reference, DEFAULT_REFERENCE_SYMBOL},
+    dump::StateDumps,
+    edges::{
+        edges_observer, mask_edges, pcguard_observer, shared_edges_observer, sync_edges,
+        value_profile_observer,
+    },
+    executor::EitherExecutor,
+    fds::{FdLeakCheck, FdLeakFeedback, FdObserver},
+    fields::field_mutations,
+    filter::{install_filter, skips, FilteredMutator},
+    gocorpus::read_testcase,
+    hangs::HangFeedback,
+    logs::ClientLog,
+    meta::{CrashMetaFeedback, UniqueCrashBudget},
+    mutation_stats::{mutation_hits, MutantScope, MutationHitsFeedback, TrackMutations},
+    mutators::{utf8_mutations, MutatorSet},
+    noise::{changed_packages, masked_edges},
+    notify::NotifyFeedback,
+    oom::{OomFeedback, RssObserver},
+    output::{CrashPatternFeedback, OutputFeedback, OutputObserver},
+    plugin::PluginMutator,
+    race::RaceFeedback,
+    rarity::{RareEdgeFeedback, RareEdgeScheduler},
+    remote::{fetch_remote_inputs, is_url},
+    repro::ReproFeedback,
+    restarts::{campaign_id, client_started, record_voluntary_restart, LastInputObserver},
+    run::initialize,
+    sanitizers::install_sanitizer_callback,
+    scheduler::{
+        EitherScheduler::{Left, Right},
+        PowerMetadata, SchedulerKind,
+    },
+    signals::{install_request_handlers, install_stop_handlers, stop_requested},
+    snapshot::{StateSnapshots, STATE_DIR},
+    stability::{stability, StabilityCheck},
+    stages::OptionalStage,
+    stats::{CampaignStatsMonitor, ClientStats, StatsWriter},
+    sync::{foreign_queues, AflSync, SYNC_INTERVAL},
+    timeout::AdaptiveTimeout,
+    GoLibAfl,
+};
+#[cfg(feature = "http")]
+use crate::{http, stats::SharedStats};
+#[cfg(feature = "prometheus")]
+use libafl::monitors::PrometheusMonitor;
+use libafl::{
+    corpus::{Corpus, OnDiskCorpus},
+    events::{Event, EventFirer, EventRestarter, SendExiting},
+    executors::{
+        inprocess::InProcessExecutor, inprocess_fork::InProcessForkExecutor, ExitKind, HasTimeout,
+    },
+    feedback_and_fast, feedback_or, feedback_or_fast,
+    feedbacks::{ConstFeedback, CrashFeedback, MapFeedbackMetadata, MaxMapFeedback},
+    fuzzer::{Evaluator, Fuzzer, StdFuzzer},
+    inputs::BytesInput,
+    mutators::scheduled::StdScheduledMutator,
+    nonzero,
+    prelude::{
+        havoc_mutations, havoc_mutations_no_crossover, tokens_mutations, AggregatorOps,
+        CalibrationStage, CanTrack, ClientDescription, EventConfig,
+        IndexesLenTimeMinimizerScheduler, Launcher, RandBytesGenerator, SimpleMonitor,
+        StdMOptMutator, StdWeightedScheduler, TimeFeedback, TimeObserver, Tokens, UserStats,
+        UserStatsValue,
+    },
+    schedulers::{powersched::PowerQueueScheduler, QueueScheduler, RandScheduler},
+    stages::{logics::IfStage, StdPowerMutationalStage, SyncFromDiskStage},
+    state::{HasCorpus, HasExecutions, HasMaxSize, HasSolutions, StdState},
+    Error, HasMetadata, HasNamedMetadata,
+};
+#[cfg(not(feature = "redqueen"))]
+use libafl::{
+    executors::ShadowExecutor,
+    prelude::I2SRandReplace,
+    stages::{mutational::StdMutationalStage, ShadowTracingStage},
+};
+#[cfg(feature = "redqueen")]
+use libafl::{
+    mutators::token_mutations::AFLppRedQueen,
+    stages::{ColorizationStage, MultiMutationalStage},
+};
+#[cfg(feature = "redqueen")]
+use libafl_bolts::ownedref::OwnedRefMut;
+use libafl_bolts::{
+    core_affinity::Cores,
+    prelude::StdShMemProvider,
+    rands::StdRand,
+    shmem::ShMemProvider,
+    tuples::{tuple_list, Handled, Map, Merge},
+};
+#[cfg(feature = "redqueen")]
+use libafl_targets::cmps::{
+    observers::AFLppCmpLogObserver, stages::AFLppCmplogTracingStage, CMPLOG_MAP_EXTENDED,
+};
+#[cfg(not(feature = "redqueen"))]
+use libafl_targets::CmpLogObserver;
+use libafl_targets::{
+    autotokens,
+    libfuzzer::{has_custom_crossover, has_custom_mutator, LLVMCustomMutator},
+};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "redqueen")]
+use std::ptr::addr_of_mut;
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    env,
+    ffi::OsString,
+    fs::{self, create_dir_all, read_dir, remove_dir_all, DirEntry},
+    io::ErrorKind,
+    marker::PhantomData,
+    net::TcpListener,
+    os::unix::fs::symlink,
+    path::{absolute, Path, PathBuf},
+    process,
+    time::Instant,
+};
+
+// Number of fuzzing iterations between two checks of the campaign budgets and stats records
+pub(crate) const FUZZ_LOOP_CHUNK: u64 = 100;
+
+// Fuzzing function, wrapping the exported libfuzzer functions from golang.
+// Returns the crash which ended the campaign, if `stop_on_crash` is set.
+#[allow(clippy::too_many_lines)]
+pub(crate) fn fuzz(config: &GoLibAfl) -> Option<PathBuf> {
+    let output = &config.output;
+    let crashes_dir = output.join("crashes");
+    let stale_crashes = stored_crashes(&crashes_dir);
+    // The queue of a previous campaign is either resumed, or cleared to start over
+    let previous = previous_queue(output);
+    if previous.is_some() && !config.resume {
+        if !config.force {
+            eprintln!(
+                "{} holds the queue of a previous campaign, pass --resume to continue it or \
+                 --force to start over",
+                output.display()
+            );
+            process::exit(1);
+        }
+        // The findings are kept
+        for dir in ["queue", STATE_DIR] {
+            let _ = remove_dir_all(output.join(dir));
+        }
+    }
+    // Resumed clients load the previous queue instead of the initial corpus
+    let inputs = if previous.is_some() && config.resume {
+        Vec::new()
+    } else {
+        fetch_remote_inputs(&config.inputs, output).unwrap_or_else(|err| {
+            eprintln!("Unable to fetch the initial corpus: {err}");
+            process::exit(1);
+        })
+    };
+    let initial = previous
+        .filter(|_| config.resume)
+        .or_else(|| merge_initial_corpora(&inputs, output))
+        .unwrap_or_else(|| inputs[0].clone());
+    #[allow(unused_mut)]
+    let mut initial_dirs = vec![initial];
+    // Along with the queue other workers uploaded to the bucket
+    #[cfg(feature = "cloud")]
+    if let Some(bucket) = config.cloud_bucket.as_deref().filter(|_| config.cloud_seed) {
+        let dest = output.join("cloud");
+        match download_queue(bucket, &dest) {
+            Ok(()) => initial_dirs.push(dest),
+            Err(err) => eprintln!("Warning: failed to download the queue of {bucket}: {err}"),
+        }
+    }
+    // Loaded once, the clients share it
+    #[cfg(feature = "grammar")]
+    let grammar = config.grammar.as_deref().map(load_grammar);
+    initialize();
+    warn_if_broker_port_taken(config.broker_port);
+    if config.forking {
+        println!(
+            "Forking each execution: comparison tracing, value profiling, the trace-pc-guard \
+             coverage and the RSS limit are disabled"
+        );
+    }
+    // Inherited by the clients
+    if install_sanitizer_callback() {
+        println!("Sanitizer runtime found, its reports are saved as crashes");
+    }
+    // Patch-oriented campaigns focus on the packages of the changed functions, coverage being
+    // attributed to packages
+    let mut focus = config.focus.clone();
+    if let Some(functions) = &config.focus_changed_packages {
+        let packages = changed_packages(functions);
+        println!("Focusing on the changed packages {}", packages.join(", "));
+        focus.extend(packages);
+    }
+    // Resolved once, the clients share the same executable. Fuzzing with all the edges instead
+    // of the focused ones would defeat the purpose of `--focus`.
+    let ignored_edges = (config.mask_runtime_edges || !focus.is_empty())
+        .then(|| {
+            masked_edges(config.mask_runtime_edges, &focus)
+                .inspect(|edges| println!("Ignoring {} edges", edges.len()))
+                .unwrap_or_else(|err| {
+                    if !focus.is_empty() {
+                        panic!("Unable to resolve the edges of the focused packages: {err}");
+                    }
+                    eprintln!("Warning: not ignoring the Go runtime edges: {err}");
+                    Vec::new()
+                })
+        })
+        .filter(|edges| !edges.is_empty());
+    let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+    // Also prints the user stats of the clients, such as their restarts
+    let monitor = SimpleMonitor::with_user_monitor(|s| println!("{s}"));
+    // Along with the clients' records, the broker writes the stats of the whole campaign
+    let stats_path = config.json_stats.then(|| output.join("stats.json"));
+    // Mapped before the clients are forked, so that they all see the requests
+    share_requests().expect("Failed to share the clients' requests");
+    install_request_handlers().expect("Failed to install the request handlers");
+    // Served by a thread of the broker, the clients inherit the listening socket only
+    #[cfg(feature = "http")]
+    let shared_stats = config.http.as_deref().map(|listener| {
+        let stats = SharedStats::default();
+        http::serve(listener, output, stats.clone())
+            .unwrap_or_else(|err| panic!("Failed to serve HTTP on {listener}: {err}"));
+        stats
+    });
+    #[cfg(not(feature = "http"))]
+    let shared_stats = None;
+    // Computed before spawning the clients, so that respawned ones share the same budget
+    let start = Instant::now();
+    let deadline = config.max_total_time.map(|budget| start + budget);
+    let campaign = campaign_id();
+
+    // An unbounded cache never evicts inputs, nor reads them back from disk: the queue is then
+    // only written, like with an `InMemoryOnDiskCorpus`, without changing the state's type
+    let cache_size = if config.in_memory_corpus {
+        usize::MAX
+    } else {
+        config.corpus_cache.get()
+    };
+
+    let mut run_client = |state: Option<_>,
+                          mut restarting_mgr,
+                          client_description: ClientDescription|
+     -> Result<(), Error> {
+        // First, so that the client's own messages are logged too
+        let mut client_log = config
+            .client_logs
+            .as_deref()
+            .map(|dir| ClientLog::open(dir, client_description.id()))
+            .transpose()?;
+        let (restarts, batched_input) = client_started(output, campaign, client_description.id())?;
+        if config.client_workdirs {
+            enter_workdir(output, client_description.id())?;
+        }
+        if let Some(reference) = &config.reference {
+            let symbol = config
+                .reference_symbol
+                .as_deref()
+                .unwrap_or(DEFAULT_REFERENCE_SYMBOL);
+            load_reference(reference, symbol)?;
+        }
+        install_filter(
+            config.min_len,
+            config.prefix.as_deref(),
+            config.filter_plugin.as_deref(),
+        )?;
+        let overrides = config
+            .client_overrides
+            .get(&client_description.id())
+            .cloned()
+            .unwrap_or_default();
+        let cmplog_mode = overrides.cmplog.unwrap_or(config.cmplog);
+        let edges_observer = if config.forking {
+            shared_edges_observer()
+        } else {
+            edges_observer()
+        }
+        .track_indices();
+        if let Some(edges) = &ignored_edges {
+            mask_edges(edges.clone());
+        }
+
+        // Observers
+        let last_input_observer =
+            LastInputObserver::new("last_input", output, client_description.id())?;
+        let time_observer = TimeObserver::new("time");
+        #[cfg(feature = "scripting")]
+        let objective_script = config.objective_script.as_deref();
+        #[cfg(not(feature = "scripting"))]
+        let objective_script: Option<&Path> = None;
+        // The races, the crash signatures and patterns, and the objective script's input are
+        // found in the output
+        let output_observer = OutputObserver::new(
+            "output",
+            config.capture_output
+                || config.races
+                || config.keep_going.is_some()
+                || config.notify_url.is_some()
+                || !config.crash_patterns.is_empty()
+                || objective_script.is_some(),
+        )?;
+        // The children's memory isn't measured
+        let rss_limit_mb = config.rss_limit_mb.filter(|_| !config.forking);
+        let rss_observer = RssObserver::new("rss", rss_limit_mb.is_some())?;
+        let fd_leaks = config.fd_leaks && !config.forking;
+        let fd_observer = FdObserver::new("fds", fd_leaks);
+        // The comparisons and the memory of the children aren't observed when forking
+        let value_profile = config.value_profile && !config.forking;
+        let pcguard_coverage = config.pcguard_coverage && !config.forking;
+        let value_profile_observer = value_profile_observer(value_profile);
+        let pcguard_observer = pcguard_observer(pcguard_coverage);
+        let map_feedback = MaxMapFeedback::new(&edges_observer);
+        // Either LibAFL's calibration, or a fixed number of runs without the stability tracking
+        let calibration_cycles = config.calibration_cycles;
+        let calibration = IfStage::new(
+            move |_, _, _, _| Ok(calibration_cycles.is_none()),
+            tuple_list!(CalibrationStage::new(&map_feedback)),
+        );
+        let quick_calibration = IfStage::new(
+            move |_, _, _, _| Ok(calibration_cycles.is_some()),
+            tuple_list!(QuickCalibrationStage::new(
+                edges_observer.handle(),
+                calibration_cycles.unwrap_or(0)
+            )),
+        );
+
+        let scheduler = overrides.scheduler.unwrap_or(config.scheduler);
+        let rare_edges = config.rare_edges && scheduler == SchedulerKind::Weighted;
+        // Inputs the harness rejected by returning -1 are never added to the corpus
+        let mut feedback = feedback_and_fast!(
+            NotRejectedFeedback,
+            feedback_or!(
+                feedback_or_fast!(
+                    // New maximization map feedback linked to the edges observer and the feedback
+                    // state
+                    map_feedback,
+                    // Time feedback, this one does not need a feedback state
+                    TimeFeedback::new(&time_observer)
+                ),
+                // Comparison progress, only when value profiling is enabled. Not short-circuited by
+                // the edges, so that its history stays up to date.
+                feedback_and_fast!(
+                    ConstFeedback::new(value_profile),
+                    MaxMapFeedback::new(&value_profile_observer)
+                ),
+                // New n-gram or context-sensitive coverage of the cgo code, only when enabled
+                feedback_and_fast!(
+                    ConstFeedback::new(pcguard_coverage),
+                    MaxMapFeedback::new(&pcguard_observer)
+                ),
+                // Counts the corpus entries hitting each edge, for the rare edges boost
+                RareEdgeFeedback::new(rare_edges),
+                // Credits the mutators of the mutants added to the corpus
+                MutationHitsFeedback::corpus()
+            )
+        );
+
+        // Solutions of the objective script, which never reports inputs without the feature
+        #[cfg(feature = "scripting")]
+        let script_feedback = ScriptFeedback::new(
+            objective_script,
+            edges_observer.handle(),
+            &time_observer,
+            &output_observer,
+        )?;
+        #[cfg(not(feature = "scripting"))]
+        let script_feedback = ConstFeedback::new(false);
+
+        // A feedback to choose if an input is a solution or not
+        let hangs_dir = config.hangs.then(|| output.join("hangs"));
+        // Objectives met in a batch are found again by executing their input on its own
+        let mut objective = feedback_and_fast!(
+            NotInBatchFeedback,
+            feedback_or_fast!(
+                // First, so that it sees every mutant
+                MutationHitsFeedback::objective(),
+                CrashFeedback::new(),
+                HangFeedback::new(hangs_dir.clone())?,
+                OomFeedback::new(&rss_observer, rss_limit_mb, output.join("oom"))?,
+                FdLeakFeedback::new(&fd_observer, fd_leaks.then(|| output.join("fd-leaks")))?,
+                RaceFeedback::new(
+                    &output_observer,
+                    client_description.id(),
+                    config.races.then(|| output.join("races"))
+                )?,
+                // Last of the objectives, so that they only run on the executions which didn't
+                // crash, and before the feedbacks storing the solutions' metadata
+                CrashPatternFeedback::new(&output_observer, config.crash_patterns.clone()),
+                script_feedback,
+                OutputFeedback::new(&output_observer, crashes_dir.clone()),
+                CrashMetaFeedback::new(
+                    edges_observer.handle(),
+                    &time_observer,
+                    &output_observer,
+                    client_description.id(),
+                    crashes_dir.clone(),
+                    reference_args(config)
+                ),
+                ReproFeedback::new(
+                    client_description.id(),
+                    crashes_dir.clone(),
+                    config.env.clone(),
+                    config.timeout,
+                    reference_args(config)
+                ),
+                NotifyFeedback::new(
+                    config.notify_url.clone(),
+                    &output_observer,
+                    client_description.id(),
+                    crashes_dir.clone(),
+                    output.join("notified")
+                )
+            )
+        );
+
+        // Restored from the previous campaign only when starting, restarts keep the state
+        let fresh = state.is_none();
+        // create a State from scratch
+        let mut state = state.unwrap_or_else(|| {
+            StdState::new(
+                // Clients derive distinct seeds from the campaign's one, so that they don't
+                // mutate in lockstep
+                config.seed.map_or_else(StdRand::new, |seed| {
+                    StdRand::with_seed(seed.wrapping_add(client_description.id() as u64))
+                }),
+                // Corpus that will be evolved
+                QueueCorpus::new(
+                    &output
+                        .join("queue")
+                        .join(client_description.id().to_string()),
+                    cache_size,
+                    config.compress_corpus,
+                )
+                .unwrap(),
+                // Corpus in which we store solutions
+                OnDiskCorpus::new(&crashes_dir).unwrap(),
+                &mut feedback,
+                &mut objective,
+            )
+            .unwrap()
+        });
+        // Havoc, splicing and token mutators won't grow inputs beyond this size
+        if let Some(max_len) = config.max_len {
+            state.set_max_size(max_len.get());
+        }
+
+        // Grammar-based fuzzing replaces the byte-level mutators, the Go and plugin ones included
+        #[cfg(feature = "grammar")]
+        let bytes = grammar.is_none();
+        #[cfg(not(feature = "grammar"))]
+        let bytes = true;
+        #[cfg(feature = "grammar")]
+        let nautilus = OptionalStage::build(!bytes, || {
+            grammar_stage(
+                grammar.as_ref().unwrap(),
+                config.seed.map_or_else(StdRand::new, |seed| {
+                    StdRand::with_seed(seed.wrapping_add(client_description.id() as u64))
+                }),
+                &output
+                    .join("grammar")
+                    .join(client_description.id().to_string()),
+            )
+        })?;
+        #[cfg(not(feature = "grammar"))]
+        let nautilus = IfStage::new(|_, _, _, _| Ok(false), ());
+
+        // One power mutational stage per mutator set, only the selected one is built, unless the
+        // mutants are executed in batches. The mutants the input filter rejects aren't executed.
+        let mutators = overrides.mutators.unwrap_or(config.mutators);
+        let batching = config.batch_size.is_some() && bytes;
+        let selected = |set| mutators == set && !batching && bytes;
+        let batches = OptionalStage::build(batching, || {
+            Ok(BatchStage::new(
+                edges_observer.handle(),
+                StdScheduledMutator::new(havoc_mutations().merge(tokens_mutations())),
+                config.batch_size.unwrap_or(1),
+            ))
+        })?;
+        let mopt: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(selected(MutatorSet::MOpt), || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    MutantScope::new(StdMOptMutator::new(
+                        &mut state,
+                        havoc_mutations()
+                            .merge(tokens_mutations())
+                            .map(TrackMutations),
+                        7,
+                        5,
+                    )?),
+                )))
+            })?;
+        let havoc: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(selected(MutatorSet::Havoc), || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    MutantScope::new(StdScheduledMutator::new(
+                        havoc_mutations().map(TrackMutations),
+                    )),
+                )))
+            })?;
+        let havoc_tokens: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(selected(MutatorSet::HavocTokens), || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    MutantScope::new(StdScheduledMutator::new(
+                        havoc_mutations()
+                            .merge(tokens_mutations())
+                            .map(TrackMutations),
+                    )),
+                )))
+            })?;
+        let unicode: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(selected(MutatorSet::Unicode), || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    MutantScope::new(StdScheduledMutator::new(
+                        utf8_mutations().map(TrackMutations),
+                    )),
+                )))
+            })?;
+        let fields: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(selected(MutatorSet::Fields), || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    MutantScope::new(StdScheduledMutator::new(
+                        field_mutations().map(TrackMutations),
+                    )),
+                )))
+            })?;
+
+        // Mutators written in Go, if the harness exports `LLVMFuzzerCustomMutator` or
+        // `LLVMFuzzerCustomCrossOver`. They run after the selected set, and can call back into
+        // havoc mutations with `LLVMFuzzerMutate`.
+        let custom: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(has_custom_mutator() && bytes, || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(unsafe {
+                    LLVMCustomMutator::mutate_unchecked(StdScheduledMutator::new(
+                        havoc_mutations_no_crossover(),
+                    ))
+                })))
+            })?;
+        let crossover: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(has_custom_crossover() && bytes, || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(unsafe {
+                    LLVMCustomMutator::crossover_unchecked(StdScheduledMutator::new(
+                        havoc_mutations_no_crossover(),
+                    ))
+                })))
+            })?;
+        // Mutator of a dynamic library, with `--mutator-plugin`, after the Go ones
+        let plugin: OptionalStage<StdPowerMutationalStage<_, _, BytesInput, _, _, _>> =
+            OptionalStage::build(config.mutator_plugin.is_some() && bytes, || {
+                Ok(StdPowerMutationalStage::new(FilteredMutator::new(
+                    PluginMutator::load(config.mutator_plugin.as_deref())?,
+                )))
+            })?;
+
+        let power_schedule = overrides.power_schedule.unwrap_or(config.power_schedule);
+        let scheduler = match scheduler {
+            SchedulerKind::Weighted if rare_edges => Left(Left(Left(
+                RareEdgeScheduler::with_schedule(&mut state, &edges_observer, Some(power_schedule)),
+            ))),
+            SchedulerKind::Weighted => Left(Left(Right(StdWeightedScheduler::with_schedule(
+                &mut state,
+                &edges_observer,
+                Some(power_schedule),
+            )))),
+            SchedulerKind::PowerQueue => Left(Right(PowerQueueScheduler::new(
+                &mut state,
+                &edges_observer,
+                power_schedule,
+            ))),
+            SchedulerKind::Queue => {
+                Right(Left(PowerMetadata::new(&mut state, QueueScheduler::new())))
+            }
+            SchedulerKind::Rand => {
+                Right(Right(PowerMetadata::new(&mut state, RandScheduler::new())))
+            }
+        };
+        // The minimizer favors the smallest and fastest entry of each edge, skipping the others
+        // most of the time
+        let scheduler = if config.minimizer {
+            Left(IndexesLenTimeMinimizerScheduler::new(
+                &edges_observer,
+                scheduler,
+            ))
+        } else {
+            Right(scheduler)
+        };
+
+        // A fuzzer with feedbacks and a corpus scheduler
+        let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective);
+
+        // The closure that we want to fuzz
+        let mut harness = |input: &BytesInput| {
+            batch::execute(input);
+            sync_edges();
+            ExitKind::Ok
+        };
+        // It captures nothing, the comparisons tracer gets its own copy
+        #[cfg(feature = "redqueen")]
+        let mut tracer_harness = harness;
+
+        #[cfg(feature = "redqueen")]
+        let colorization = ColorizationStage::new(&edges_observer);
+
+        let observers = tuple_list!(
+            edges_observer,
+            time_observer,
+            output_observer,
+            rss_observer,
+            fd_observer,
+            value_profile_observer,
+            pcguard_observer,
+            last_input_observer
+        );
+        let executor = if config.forking {
+            EitherExecutor::Right(InProcessForkExecutor::new(
+                &mut harness,
+                observers,
+                &mut fuzzer,
+                &mut state,
+                &mut restarting_mgr,
+                config.timeout,
+                StdShMemProvider::new()?,
+            )?)
+        } else {
+            EitherExecutor::Left(InProcessExecutor::with_timeout(
+                &mut harness,
+                observers,
+                &mut fuzzer,
+                &mut state,
+                &mut restarting_mgr,
+                config.timeout,
+            )?)
+        };
+        // The comparisons of the children aren't shared with the parent
+        let cmplog_enabled = cmplog_mode != CmpLogMode::Off && !config.forking;
+        let mut cmplog_schedule = overrides.cmplog_rate.or(config.cmplog_rate).map(|rate| {
+            CmpLogSchedule::new(
+                rate,
+                config.seed.map_or_else(StdRand::new, |seed| {
+                    StdRand::with_seed(seed.wrapping_add(client_description.id() as u64))
+                }),
+            )
+        });
+        // Asked once per scheduled entry, by the stage deciding whether to trace it
+        let mut should_trace = move || {
+            cmplog_enabled
+                && cmplog_schedule
+                    .as_mut()
+                    .is_none_or(|schedule| schedule.should_trace())
+        };
+
+        #[cfg(not(feature = "redqueen"))]
+        let (mut executor, cmplog) = {
+            // Tracing runs go through the inner executor, and thus share its timeout
+            let cmplog_observer = CmpLogObserver::new("cmplog", true);
+            let executor = ShadowExecutor::new(
+                executor,
+                tuple_list!(
+                    cmplog_observer,
+                    InstructionsOnlyObserver::new(
+                        "cmplog_instructions",
+                        cmplog_mode == CmpLogMode::Instructions
+                    )
+                ),
+            );
+
+            // Setup a tracing stage in which we log comparisons, followed by a randomic
+            // Input2State stage using them. Both are skipped when comparison tracing is off.
+            let tracing = ShadowTracingStage::new();
+            let i2s = StdMutationalStage::new(MutantScope::new(StdScheduledMutator::new(
+                tuple_list!(I2SRandReplace::new()).map(TrackMutations),
+            )));
+            let cmplog = IfStage::new(
+                move |_, _, _, _| Ok(should_trace()),
+                tuple_list!(tracing, i2s),
+            );
+            (executor, cmplog)
+        };
+
+        // AFL++-style RedQueen: the input is colorized, then the comparisons of the original and
+        // colorized inputs are traced by a dedicated executor, sharing the same timeout. RedQueen
+        // mutations are derived from the operands changing with the colorized bytes.
+        #[cfg(feature = "redqueen")]
+        let (mut executor, cmplog) = {
+            let cmplog_observer = AFLppCmpLogObserver::new(
+                "cmplog",
+                OwnedRefMut::Ref(unsafe { &mut *addr_of_mut!(CMPLOG_MAP_EXTENDED) }),
+                true,
+            );
+            let cmplog_handle = cmplog_observer.handle();
+            let tracer = InProcessExecutor::with_timeout(
+                &mut tracer_harness,
+                tuple_list!(cmplog_observer),
+                &mut fuzzer,
+                &mut state,
+                &mut restarting_mgr,
+                config.timeout,
+            )?;
+            let tracing = AFLppCmplogTracingStage::new(tracer, cmplog_handle);
+            let redqueen = MultiMutationalStage::new(AFLppRedQueen::with_cmplog_options(
+                true,
+                cmplog_mode == CmpLogMode::Routines,
+            ));
+            let cmplog = IfStage::new(
+                move |_, _, _, _| Ok(should_trace()),
+                tuple_list!(colorization, tracing, redqueen),
+            );
+            (executor, cmplog)
+        };
+
+        // Periodically import the queues of the AFL++ instances sharing the sync directory, and
+        // the seeds added to the watched directories. The list of queues is taken once, when the
+        // client starts.
+        let mut sync_dirs = config
+            .sync_dir
+            .as_deref()
+            .map_or_else(Vec::new, |dir| foreign_queues(dir, &config.sync_id));
+        sync_dirs.extend(config.sync_in.iter().cloned());
+        if config.watch_input {
+            sync_dirs.extend(config.inputs.iter().filter(|input| !is_url(input)).cloned());
+        }
+        let sync = SyncFromDiskStage::new(
+            sync_dirs,
+            |_, _, path: &Path| read_testcase(path).map(BytesInput::new),
+            SYNC_INTERVAL,
+            "sync",
+        );
+
+        let mut stages = tuple_list!(
+            calibration,
+            quick_calibration,
+            cmplog,
+            nautilus,
+            mopt,
+            havoc,
+            havoc_tokens,
+            unicode,
+            fields,
+            batches,
+            custom,
+            crossover,
+            plugin,
+            sync
+        );
+
+        if state.metadata_map().get::<Tokens>().is_none() {
+            let mut toks = Tokens::default();
+            toks += autotokens()?;
+            for dict in &config.dicts {
+                toks.add_from_file(dict)?;
+            }
+            // Tokens learned by a previous run of the campaign
+            let learned = output.join(LEARNED_DICT);
+            if learned.exists() {
+                toks.add_from_file(&learned)?;
+            }
+
+            if !toks.is_empty() {
+                state.add_metadata(toks);
+            }
+        }
+
+        let mut snapshots = StateSnapshots::new(output, client_description.id())?;
+        if fresh && config.resume && snapshots.restore(&mut state)? {
+            println!(
+                "Restored the state of client {} after {} executions",
+                client_description.id(),
+                state.executions()
+            );
+        }
+
+        // Load corpus from input folder
+        // In case the corpus is empty (on first run), reset
+        if state.must_load_initial_inputs() {
+            let empty = read_dir(&initial_dirs[0]).iter().len() == 0;
+            if empty && bytes {
+                // Generator of printable bytearrays of max size 32, or max_len if smaller
+                let max_size = config.max_len.map_or(nonzero!(32), |l| l.min(nonzero!(32)));
+                let mut generator = RandBytesGenerator::new(max_size);
+
+                // Generate 8 initial inputs
+                state
+                    .generate_initial_inputs(
+                        &mut fuzzer,
+                        &mut executor,
+                        &mut generator,
+                        &mut restarting_mgr,
+                        8,
+                    )
+                    .expect("Failed to generate the initial corpus");
+                println!(
+                    "We imported {} inputs from the generator.",
+                    state.corpus().count()
+                );
+            } else if !empty {
+                // Initial inputs the input filter rejects
+                let mut filtered = 0;
+                // Each client executes its own shard of the initial corpus, the broker sends the
+                // interesting entries to the others. A client whose shard added nothing loads the
+                // whole corpus, as it can't fuzz an empty one.
+                let clients = config.cores.ids.len();
+                if config.corpus_shards && clients > 1 {
+                    let shard = initial_files(&initial_dirs)
+                        .into_iter()
+                        .skip(client_description.id())
+                        .step_by(clients)
+                        .collect::<Vec<_>>();
+                    println!(
+                        "Loading {} inputs, shard {} of {}, from {:?}",
+                        shard.len(),
+                        client_description.id(),
+                        clients,
+                        initial_dirs
+                    );
+                    for path in &shard {
+                        match read_initial_input(path, output) {
+                            Ok(input) if skips(&input) => filtered += 1,
+                            Ok(input) => {
+                                fuzzer.evaluate_input(
+                                    &mut state,
+                                    &mut executor,
+                                    &mut restarting_mgr,
+                                    &BytesInput::new(input),
+                                )?;
+                            }
+                            Err(err) => eprintln!("Skipping {}: {err}", path.display()),
+                        }
+                    }
+                }
+                if state.corpus().count() == 0 {
+                    println!("Loading from {:?}", initial_dirs);
+                    // Load from disk
+                    state
+                        .load_initial_inputs_custom(
+                            &mut fuzzer,
+                            &mut executor,
+                            &mut restarting_mgr,
+                            &initial_dirs,
+                            // Go native corpus files are decoded, other files are loaded as is.
+                            // The filtered ones are rejected when executed.
+                            &mut |_, _, path| {
+                                let input = read_initial_input(path, output)?;
+                                filtered += usize::from(skips(&input));
+                                Ok(BytesInput::new(input))
+                            },
+                        )
+                        .unwrap_or_else(|_| {
+                            panic!("Failed to load initial corpus at {:?}", initial_dirs);
+                        });
+                }
+                println!("We imported {} inputs from disk.", state.corpus().count());
+                if filtered > 0 {
+                    println!(
+                        "Dropped {filtered} initial inputs rejected by --min-len, --prefix or \
+                         --filter-plugin"
+                    );
+                }
+            }
+            // Along with the initial corpus, whose entries are mutated from random trees
+            #[cfg(feature = "grammar")]
+            if let Some(context) = &grammar {
+                let loaded = state.corpus().count();
+                generate_initial_inputs(
+                    context,
+                    &mut fuzzer,
+                    &mut executor,
+                    &mut state,
+                    &mut restarting_mgr,
+                    8,
+                )
+                .expect("Failed to generate the initial corpus");
+                println!(
+                    "We imported {} inputs from the grammar.",
+                    state.corpus().count() - loaded
+                );
+            }
+        }
+
+        // The client died executing this input in a batch, which left its objective unsaved
+        if let Some(input) = batched_input {
+            fuzzer.evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut restarting_mgr,
+                &BytesInput::new(input),
+            )?;
+        }
+
+        let mut stats_writers = Vec::new();
+        if config.json_stats {
+            let path = output.join("stats.ndjson");
+            stats_writers.push(StatsWriter::json(&path, client_description.id(), start)?);
+        }
+        if config.plot_data {
+            let dir = output
+                .join("plot")
+                .join(client_description.id().to_string());
+            create_dir_all(&dir)?;
+            stats_writers.push(StatsWriter::plot_data(&dir.join("plot_data"), start)?);
+        }
+
+        let mut dict_writer = DictWriter::new(&output.join(LEARNED_DICT));
+
+        let mut dumps = StateDumps::new(output, client_description.id())?;
+
+        let mut afl_sync = config
+            .sync_dir
+            .as_deref()
+            .map(|dir| {
+                let fuzzer_id = format!("{}-{}", config.sync_id, client_description.id());
+                AflSync::new(dir, &fuzzer_id)
+            })
+            .transpose()?;
+
+        // Surfaced in the monitor, summed over the clients
+        restarting_mgr.fire(
+            &mut state,
+            Event::UpdateUserStats {
+                name: Cow::Borrowed("restarts"),
+                value: UserStats::new(UserStatsValue::Number(restarts), AggregatorOps::Sum),
+                phantom: PhantomData,
+            },
+        )?;
+
+        let mut stability_check =
+            StabilityCheck::new(client_description.id(), config.stability_threshold);
+        let mut fd_leak_check = FdLeakCheck::new(client_description.id());
+
+        // The first client uploads the campaign, the clients sharing the output directory
+        #[cfg(feature = "cloud")]
+        let mut cloud_sync = config
+            .cloud_bucket
+            .as_deref()
+            .filter(|_| client_description.id() == 0)
+            .map(|bucket| CloudSync::new(bucket, output))
+            .transpose()?;
+
+        let mut culler = CorpusCuller::new(config.max_corpus_entries, config.max_corpus_bytes);
+
+        // The RedQueen tracer keeps the fixed timeout
+        let mut adaptive_timeout = config
+            .auto_timeout
+            .map(|auto| AdaptiveTimeout::new(auto, config.timeout));
+
+        // Counted from the start of this run of the client, the executions being restored
+        let restart_at = config
+            .restart_interval_execs
+            .map(|interval| *state.executions() + interval);
+
+        let mut crash_budget = config
+            .keep_going
+            .map(|budget| UniqueCrashBudget::new(crashes_dir.clone(), budget, &stale_crashes));
+
+        // Installed last, so that they override the handlers of the event manager
+        install_stop_handlers()?;
+        install_request_handlers()?;
+        // Last, the client no longer opens sockets nor writes outside of these directories
+        if config.sandbox {
+            #[cfg(target_os = "linux")]
+            sandbox(
+                &[
+                    Some(output.as_path()),
+                    config.sync_dir.as_deref(),
+                    config.client_logs.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+            )?;
+            #[cfg(not(target_os = "linux"))]
+            return Err(Error::unsupported("--sandbox is only supported on Linux"));
+        }
+
+        while deadline.is_none_or(|deadline| Instant::now() < deadline)
+            && config
+                .max_execs
+                .is_none_or(|max_execs| *state.executions() < max_execs)
+            && !(config.stop_on_crash && first_crash(&crashes_dir, &stale_crashes).is_some())
+            && !crash_budget
+                .as_mut()
+                .is_some_and(UniqueCrashBudget::is_exhausted)
+            && !stop_requested()
+        {
+            fuzzer.fuzz_loop_for(
+                &mut stages,
+                &mut executor,
+                &mut state,
+                &mut restarting_mgr,
+                FUZZ_LOOP_CHUNK,
+            )?;
+
+            // The captured output of the target must not be redirected to the log again
+            if let Some(log) = client_log
+                .as_mut()
+                .filter(|log| !config.capture_output && log.is_due())
+            {
+                log.check()?;
+            }
+
+            if fd_leak_check.is_due() {
+                fd_leak_check.check(fd_leaks)?;
+            }
+
+            if stability_check.is_due() {
+                if let Some((unstable, filled)) = stability_check.check(&state) {
+                    // Same stat as the calibration's, which only updates it when it finds new
+                    // unstable edges
+                    restarting_mgr.fire(
+                        &mut state,
+                        Event::UpdateUserStats {
+                            name: Cow::Borrowed("stability"),
+                            value: UserStats::new(
+                                UserStatsValue::Ratio((filled - unstable) as u64, filled as u64),
+                                AggregatorOps::Avg,
+                            ),
+                            phantom: PhantomData,
+                        },
+                    )?;
+                }
+            }
+
+            if let Some(adaptive_timeout) = adaptive_timeout.as_mut().filter(|t| t.is_due()) {
+                if let Some(timeout) = adaptive_timeout.update(&state) {
+                    executor.set_timeout(timeout);
+                    restarting_mgr.fire(
+                        &mut state,
+                        Event::UpdateUserStats {
+                            name: Cow::Borrowed("timeout_ms"),
+                            value: UserStats::new(
+                                UserStatsValue::Number(timeout.as_millis() as u64),
+                                AggregatorOps::Max,
+                            ),
+                            phantom: PhantomData,
+                        },
+                    )?;
+                }
+            }
+
+            #[cfg(feature = "cloud")]
+            if let Some(cloud_sync) = cloud_sync.as_mut().filter(|s| s.is_due()) {
+                cloud_sync.upload();
+            }
+
+            if culler.is_due() {
+                culler.cull(&mut fuzzer, &mut state)?;
+            }
+
+            if let Some(afl_sync) = afl_sync.as_mut().filter(|s| s.is_due()) {
+                afl_sync.export(state.corpus())?;
+            }
+
+            if snapshots.is_due() {
+                snapshots.save(&state)?;
+            }
+
+            if dict_writer.is_due() {
+                if let Some(tokens) = state.metadata_map().get::<Tokens>() {
+                    dict_writer.write(tokens)?;
+                }
+            }
+
+            if dumps.is_due() {
+                dumps.write(&state, &client_stats(&state, hangs_dir.as_deref()))?;
+            }
+
+            if stats_writers.iter().any(StatsWriter::is_due) {
+                let stats = client_stats(&state, hangs_dir.as_deref());
+                for writer in stats_writers.iter_mut().filter(|w| w.is_due()) {
+                    writer.write(&stats)?;
+                }
+            }
+
+            // The state is handed over to the relaunched client, like on a crash
+            if restart_at.is_some_and(|restart_at| *state.executions() >= restart_at) {
+                record_voluntary_restart(output, client_description.id())?;
+                restarting_mgr.on_restart(&mut state)?;
+                process::exit(0);
+            }
+
+            wait_while_paused(stop_requested);
+        }
+
+        // Record the final state of the client before leaving
+        if let Some(afl_sync) = afl_sync.as_mut() {
+            afl_sync.export(state.corpus())?;
+        }
+        if let Some(tokens) = state.metadata_map().get::<Tokens>() {
+            dict_writer.write(tokens)?;
+        }
+        snapshots.save(&state)?;
+        let stats = client_stats(&state, hangs_dir.as_deref());
+        for writer in &mut stats_writers {
+            writer.write(&stats)?;
+        }
+        // The corpus is written to disk as it grows, we only have to tell the broker we are
+        // leaving for good. It shuts down once all the clients did.
+        restarting_mgr.send_exiting()?;
+        // Don't return to the launcher, only the broker does once the campaign is over
+        process::exit(0);
+    };
+    // A broker-only node only coordinates the clients of the other machines
+    let cores = if config.broker_only {
+        Cores::from(Vec::new())
+    } else {
+        config.cores.clone()
+    };
+    // The launcher is generic over the monitor, which depends on the enabled outputs
+    macro_rules! launch {
+        ($monitor:expr) => {
+            Launcher::builder()
+                .shmem_provider(shmem_provider)
+                .configuration(event_config(config, "default"))
+                .monitor($monitor)
+                .run_client(&mut run_client)
+                .cores(&cores)
+                .broker_port(config.broker_port)
+                .remote_broker_addr(config.remote_broker)
+                // Clients are silenced, unless running in the foreground
+                .stdout_file((!config.foreground).then_some("/dev/null"))
+                .build()
+                .launch()
+        };
+    }
+    #[cfg(feature = "prometheus")]
+    let launched = match &config.prometheus {
+        Some(listener) => {
+            let monitor = PrometheusMonitor::new(listener.clone(), |s| println!("{s}"));
+            launch!(CampaignStatsMonitor::new(
+                monitor,
+                stats_path,
+                shared_stats,
+                start
+            ))
+        }
+        None => launch!(CampaignStatsMonitor::new(
+            monitor,
+            stats_path,
+            shared_stats,
+            start
+        )),
+    };
+    #[cfg(not(feature = "prometheus"))]
+    let launched = launch!(CampaignStatsMonitor::new(
+        monitor,
+        stats_path,
+        shared_stats,
+        start
+    ));
+
+    let crash = match launched {
+        Ok(()) if config.stop_on_crash => first_crash(&crashes_dir, &stale_crashes),
+        Ok(()) => None,
+        Err(Error::ShuttingDown) => {
+            println!("Fuzzing stopped by user. Good bye.");
+            None
+        }
+        Err(err) => panic!("Failed to run launcher: {err:?}"),
+    };
+    // The last findings of the clients, which left without waiting for their uploads
+    #[cfg(feature = "cloud")]
+    if let Some(bucket) = &config.cloud_bucket {
+        if let Err(err) = CloudSync::new(bucket, output).and_then(|mut sync| sync.finish()) {
+            eprintln!("Warning: failed to upload the campaign to {bucket}: {err}");
+        }
+    }
+    if let Some(budget) = config.keep_going {
+        let mut crashes = UniqueCrashBudget::new(crashes_dir.clone(), budget, &stale_crashes);
+        if crashes.is_exhausted() {
+            println!(
+                "Stopped after {} crashes with distinct signatures",
+                crashes.unique()
+            );
+        }
+    }
+    print_summary(output, start);
+    for report in &config.reports {
+        report.write(&crashes_dir, start.elapsed(), config.timeout);
+    }
+    crash
+}
+
+// Snapshot of a client's progress, for the stats writers
+fn client_stats<S>(state: &S, hangs_dir: Option<&Path>) -> ClientStats
+where
+    S: HasCorpus<BytesInput>
+        + HasSolutions<BytesInput>
+        + HasExecutions
+        + HasMetadata
+        + HasNamedMetadata,
+{
+    let edges = state
+        .named_metadata_map()
+        .get::<MapFeedbackMetadata<u8>>("edges");
+    ClientStats {
+        executions: *state.executions(),
+        current: state.corpus().current().map_or(0, |id| id.0),
+        corpus: state.corpus().count(),
+        objectives: state.solutions().count(),
+        hangs: hangs_dir.map_or(0, |dir| stored_files(dir).count()),
+        edges: edges.map_or(0, |m| m.num_covered_map_indexes),
+        edges_total: edges.map_or(0, |m| m.history_map.len()),
+        stability: stability(state),
+        mutations: mutation_hits(state),
+    }
+}
+
+// Campaign summary, from what the clients stored in the output directory
+pub(crate) fn print_summary(output: &Path, start: Instant) {
+    let queue = read_dir(output.join("queue"))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|client| stored_files(&client.path()).count())
+        .sum::<usize>();
+    let count = |dir: &str| stored_files(&output.join(dir)).filter(is_file).count();
+    println!(
+        "Fuzzed for {}s: {} queue entries, {} crashes, {} hangs, {} OOMs, {} races in {}",
+        start.elapsed().as_secs(),
+        queue,
+        count("crashes"),
+        count("hangs"),
+        count("oom"),
+        count("races"),
+        output.display()
+    );
+}
+
+// Configuration of the events of `mode`: a client only trusts the observers of the testcases sent
+// by clients with the same configuration, and executes the other testcases again. Named after a
+// hash of the golibafl executable, which embeds the target, unless `--config-name` is given.
+pub(crate) fn event_config(config: &GoLibAfl, mode: &str) -> EventConfig {
+    let name = config.config_name.clone().unwrap_or_else(|| {
+        let exe = env::current_exe().expect("Unable to locate the golibafl executable");
+        let binary = fs::read(&exe)
+            .unwrap_or_else(|_| panic!("Failed to read the executable {}", exe.display()));
+        Sha256::digest(binary)
+            .iter()
+            .take(8)
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    });
+    EventConfig::from_name(&format!("{name}-{mode}"))
+}
+
+// LLMP brokers only listen on TCP ports: when the port of the broker is already bound, e.g. by
+// the broker of another campaign, the clients join that campaign instead of starting their own.
+// Unix domain sockets would avoid the collisions, but LibAFL's launcher doesn't support them.
+pub(crate) fn warn_if_broker_port_taken(port: u16) {
+    if TcpListener::bind(("127.0.0.1", port)).is_err() {
+        eprintln!(
+            "Warning: the broker port {port} is already in use, the clients will join the \
+             campaign of its broker. Pick a free port with -p to start a separate campaign"
+        );
+    }
+}
+
+// Arguments of `run` running the reference implementation of the campaign, if any
+fn reference_args(config: &GoLibAfl) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(reference) = &config.reference {
+        args.extend(["--reference".into(), reference.display().to_string()]);
+    }
+    if let Some(symbol) = &config.reference_symbol {
+        args.extend(["--reference-symbol".into(), symbol.clone()]);
+    }
+    args
+}
+
+// Make a client work from its own directory, emptied of the files left by its previous run
+pub(crate) fn enter_workdir(output: &Path, client: usize) -> Result<(), Error> {
+    let dir = output.join("tmp").join(client.to_string());
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir)?;
+    env::set_current_dir(&dir)?;
+    Ok(())
+}
+
+// Queue directory of a previous campaign in `output`, if any client stored an entry in it
+fn previous_queue(output: &Path) -> Option<PathBuf> {
+    let queue = output.join("queue");
+    stored_files(&queue)
+        .any(|client| stored_files(&client.path()).next().is_some())
+        .then_some(queue)
+}
+
+// Testcases stored in a corpus directory, skipping its hidden metadata and lock files
+pub(crate) fn stored_files(dir: &Path) -> impl Iterator<Item = DirEntry> {
+    read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+}
+
+// Read an initial input: the entries of a resumed or downloaded queue are decompressed, the seeds
+// are read as testcases
+fn read_initial_input(path: &Path, output: &Path) -> Result<Vec<u8>, Error> {
+    if ["queue", "cloud"]
+        .iter()
+        .any(|dir| path.starts_with(output.join(dir)))
+    {
+        read_input_file(path)
+    } else {
+        read_testcase(path)
+    }
+}
+
+// Files of the initial corpus directories and their subdirectories, in the same order for every
+// client
+fn initial_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        for entry in stored_files(&dir) {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                pending.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+// Directory of the merged initial corpora, in the output directory
+const INITIAL_DIR: &str = "initial";
+
+// Merge the initial corpora `inputs`, along with their subdirectories, into a directory of links
+// to their files, each distinct content once, so that the seed collections organized by format
+// variant or gathered from several sources are executed without duplicates. Returns `None` when
+// they hold no files.
+fn merge_initial_corpora(inputs: &[PathBuf], output: &Path) -> Option<PathBuf> {
+    let files = initial_files(inputs);
+    if files.is_empty() {
+        return None;
+    }
+    let dir = output.join(INITIAL_DIR);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir).unwrap_or_else(|_| panic!("Unable to create dir {}", dir.display()));
+    let (mut merged, mut duplicates) = (0, 0);
+    for file in &files {
+        let Ok(content) = fs::read(file) else {
+            eprintln!("Skipping {}: unable to read it", file.display());
+            continue;
+        };
+        let digest = Sha256::digest(&content);
+        let name: String = digest[..8].iter().map(|b| format!("{b:02x}")).collect();
+        let target = absolute(file).unwrap_or_else(|_| file.clone());
+        match symlink(target, dir.join(name)) {
+            Ok(()) => merged += 1,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => duplicates += 1,
+            Err(err) => panic!(
+                "Unable to link {} in {}: {err}",
+                file.display(),
+                dir.display()
+            ),
+        }
+    }
+    if duplicates > 0 || inputs.len() > 1 {
+        println!(
+            "Merged {merged} initial inputs from {inputs:?}, skipping {duplicates} duplicates"
+        );
+    }
+    Some(dir)
+}
+
+// Skips the subdirectories of the crashes, linking them by kind
+pub(crate) fn is_file(entry: &DirEntry) -> bool {
+    entry.file_type().is_ok_and(|file_type| file_type.is_file())
+}
+
+// Names of the crashes stored in `crashes_dir` before the campaign started, e.g. kept by `--force`
+// in a reused output directory, which neither stop it nor count towards its budget
+pub(crate) fn stored_crashes(crashes_dir: &Path) -> HashSet<OsString> {
+    stored_files(crashes_dir)
+        .filter(is_file)
+        .map(|entry| entry.file_name())
+        .collect()
+}
+
+// Oldest crash stored in `crashes_dir`, except the `stale` ones
+pub(crate) fn first_crash(crashes_dir: &Path, stale: &HashSet<OsString>) -> Option<PathBuf> {
+    stored_files(crashes_dir)
+        .filter(is_file)
+        .filter(|entry| !stale.contains(&entry.file_name()))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .min()
+        .map(|(_, path)| path)
+}
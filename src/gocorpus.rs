@@ -0,0 +1,246 @@
+use crate::run::collect_inputs;
+use libafl::Error;
+use std::{fmt::Write, fs, path::Path};
+
+// First line of the corpus files of Go's native fuzzing
+pub const GO_CORPUS_HEADER: &str = "go test fuzz v1";
+
+// Read a testcase: Go native corpus files are decoded, any other file is taken as raw bytes, even
+// named `.zst`, as targets may take compressed data
+pub fn read_testcase(path: &Path) -> Result<Vec<u8>, Error> {
+    let data = fs::read(path)?;
+    if data.starts_with(GO_CORPUS_HEADER.as_bytes()) {
+        let text = String::from_utf8(data).map_err(|_| {
+            Error::illegal_argument(format!(
+                "Invalid UTF-8 in Go corpus file {}",
+                path.display()
+            ))
+        })?;
+        decode(&text)
+    } else {
+        Ok(data)
+    }
+}
+
+// Decode a "go test fuzz v1" file holding a single `[]byte` or `string` argument
+pub fn decode(text: &str) -> Result<Vec<u8>, Error> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    if lines.next() != Some(GO_CORPUS_HEADER) {
+        return Err(Error::illegal_argument("Missing Go corpus header"));
+    }
+    let (Some(value), None) = (lines.next(), lines.next()) else {
+        return Err(Error::illegal_argument(
+            "Only Go corpus files with a single argument are supported",
+        ));
+    };
+    let literal = value
+        .strip_prefix("[]byte(")
+        .or_else(|| value.strip_prefix("string("))
+        .and_then(|v| v.strip_suffix(')'))
+        .ok_or_else(|| Error::illegal_argument(format!("Unsupported Go corpus value: {value}")))?;
+    unquote(literal)
+}
+
+// Encode an input as a "go test fuzz v1" file for a harness taking a single `[]byte`
+pub fn encode(data: &[u8]) -> String {
+    format!("{GO_CORPUS_HEADER}\n[]byte({})\n", quote(data))
+}
+
+// Go interpreted string literal, escaping everything but printable ASCII
+fn quote(data: &[u8]) -> String {
+    let mut quoted = String::with_capacity(data.len() + 2);
+    quoted.push('"');
+    for &b in data {
+        match b {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            0x20..=0x7e => quoted.push(b as char),
+            _ => write!(quoted, "\\x{b:02x}").unwrap(),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Bytes of a string with the escapes of Go string literals, e.g. `\x00` or `\n`, as given on the
+// command line
+pub fn unescape(s: &str) -> Result<Vec<u8>, Error> {
+    unquote(&format!("\"{s}\""))
+}
+
+// Parse a Go string literal, either interpreted ("...") or raw (`...`)
+fn unquote(literal: &str) -> Result<Vec<u8>, Error> {
+    if let Some(raw) = literal.strip_prefix('`').and_then(|l| l.strip_suffix('`')) {
+        // Carriage returns are discarded from raw string literals
+        return Ok(raw.bytes().filter(|&b| b != b'\r').collect());
+    }
+    let body = literal
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .ok_or_else(|| Error::illegal_argument(format!("Invalid Go string literal: {literal}")))?;
+    let invalid_escape = || Error::illegal_argument(format!("Invalid escape in {literal}"));
+
+    let mut out = Vec::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let escape = chars.next().ok_or_else(invalid_escape)?;
+        match escape {
+            'a' => out.push(0x07),
+            'b' => out.push(0x08),
+            'f' => out.push(0x0c),
+            'n' => out.push(b'\n'),
+            'r' => out.push(b'\r'),
+            't' => out.push(b'\t'),
+            'v' => out.push(0x0b),
+            '\\' | '\'' | '"' => out.push(escape as u8),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                out.push(u8::from_str_radix(&hex, 16).map_err(|_| invalid_escape())?);
+            }
+            '0'..='7' => {
+                let octal: String = std::iter::once(escape)
+                    .chain(chars.by_ref().take(2))
+                    .collect();
+                out.push(u8::from_str_radix(&octal, 8).map_err(|_| invalid_escape())?);
+            }
+            'u' | 'U' => {
+                let len = if escape == 'u' { 4 } else { 8 };
+                let hex: String = chars.by_ref().take(len).collect();
+                let c = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(invalid_escape)?;
+                let mut buf = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            _ => return Err(invalid_escape()),
+        }
+    }
+    Ok(out)
+}
+
+// Export the crashes of a campaign to `<package>/testdata/fuzz/<fuzz_target>`, where `go test`
+// picks them up as regression inputs
+pub(crate) fn export_crashes(output: &Path, package: &Path, fuzz_target: &str) {
+    let crashes = collect_inputs(&output.join("crashes"));
+    let dest = package.join("testdata").join("fuzz").join(fuzz_target);
+    fs::create_dir_all(&dest).unwrap_or_else(|_| panic!("Unable to create dir {}", dest.display()));
+    for crash in &crashes {
+        let data =
+            fs::read(crash).unwrap_or_else(|_| panic!("Unable to read file {}", crash.display()));
+        let path = dest.join(crash.file_name().unwrap());
+        fs::write(&path, encode(&data))
+            .unwrap_or_else(|_| panic!("Unable to write file {}", path.display()));
+    }
+    println!("Exported {} crashes to {}", crashes.len(), dest.display());
+}
+
+// Name of the generated regression test file
+const TESTS_FILE: &str = "golibafl_crashes_test.go";
+
+// Name of the Go package in `dir`, from the package clause of its first non-test source file
+fn package_name(dir: &Path) -> Option<String> {
+    let mut sources: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension().is_some_and(|ext| ext == "go")
+                && !p.to_string_lossy().ends_with("_test.go")
+        })
+        .collect();
+    sources.sort();
+    sources.iter().find_map(|source| {
+        fs::read_to_string(source).ok()?.lines().find_map(|line| {
+            line.trim()
+                .strip_prefix("package ")
+                .map(|name| name.trim().to_string())
+        })
+    })
+}
+
+// Generate `<package>/golibafl_crashes_test.go`, a Go test calling `function` with each crash of
+// the campaign in a subtest, so that the crashes become regression tests of the package
+pub(crate) fn export_tests(output: &Path, package: &Path, function: &str) {
+    let mut crashes = collect_inputs(&output.join("crashes"));
+    crashes.sort();
+    let name = package_name(package).unwrap_or_else(|| {
+        panic!(
+            "Unable to find the package clause of the Go sources in {}",
+            package.display()
+        )
+    });
+    let mut test = format!(
+        "// Code generated by golibafl export-go-tests. DO NOT EDIT.\n\n\
+         package {name}\n\n\
+         import \"testing\"\n\n\
+         var golibaflCrashes = []struct {{\n\
+         \tname string\n\
+         \tdata []byte\n\
+         }}{{\n"
+    );
+    for crash in &crashes {
+        let data = read_testcase(crash)
+            .unwrap_or_else(|_| panic!("Unable to read file {}", crash.display()));
+        let file_name = crash.file_name().unwrap().to_string_lossy();
+        writeln!(
+            test,
+            "\t{{{}, []byte({})}},",
+            quote(file_name.as_bytes()),
+            quote(&data)
+        )
+        .unwrap();
+    }
+    write!(
+        test,
+        "}}\n\n\
+         func TestGolibaflCrashes(t *testing.T) {{\n\
+         \tfor _, crash := range golibaflCrashes {{\n\
+         \t\tt.Run(crash.name, func(t *testing.T) {{\n\
+         \t\t\t{function}(crash.data)\n\
+         \t\t}})\n\
+         \t}}\n\
+         }}\n"
+    )
+    .unwrap();
+    let path = package.join(TESTS_FILE);
+    fs::write(&path, test).unwrap_or_else(|_| panic!("Unable to write file {}", path.display()));
+    println!("Exported {} crashes to {}", crashes.len(), path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn go_escapes_are_unescaped() {
+        assert_eq!(unescape(r"\x89PNG\r\n").unwrap(), b"\x89PNG\r\n");
+        assert_eq!(
+            unescape(r#"\a\b\f\t\v\\\'\""#).unwrap(),
+            b"\x07\x08\x0c\t\x0b\\'\""
+        );
+        assert_eq!(unescape(r"\000\177").unwrap(), b"\x00\x7f");
+        assert_eq!(unescape(r"é\U0001F600").unwrap(), "é😀".as_bytes());
+        assert!(unescape(r"\q").is_err());
+        assert!(unescape(r"\xzz").is_err());
+        assert!(unescape("\\").is_err());
+    }
+
+    #[test]
+    fn corpus_files_are_decoded_back() {
+        let data = b"\x00\xff\"quoted\"\\\n\ttext".to_vec();
+        assert_eq!(decode(&encode(&data)).unwrap(), data);
+        let raw = format!("{GO_CORPUS_HEADER}\nstring(`raw\rtext`)\n");
+        assert_eq!(decode(&raw).unwrap(), b"rawtext");
+        assert!(decode("[]byte(\"no header\")").is_err());
+        assert!(decode(&format!("{GO_CORPUS_HEADER}\nint(1)\n")).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::filter::skips;
+use libafl::{
+    corpus::{CorpusId, InMemoryCorpus},
+    feedbacks::{ConstFeedback, NautilusChunksMetadata},
+    fuzzer::Evaluator,
+    generators::{Generator, NautilusContext, NautilusGenerator},
+    inputs::{BytesInput, HasTargetBytes, NautilusInput},
+    mutators::{
+        MutationResult, Mutator, NautilusRandomMutator, NautilusRecursionMutator,
+        NautilusSpliceMutator, StdScheduledMutator,
+    },
+    nonzero,
+    stages::{Restartable, Stage},
+    state::{HasCurrentCorpusId, HasRand, StdState},
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    rands::{Rand, StdRand},
+    tuples::tuple_list,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+// Maximum depth of the generated derivation trees
+const TREE_DEPTH: usize = 15;
+
+// Load the Nautilus grammar (JSON) at `path`
+pub(crate) fn load_grammar(path: &Path) -> NautilusContext {
+    NautilusContext::from_file(TREE_DEPTH, path)
+        .unwrap_or_else(|err| panic!("Failed to load the grammar {}: {err:?}", path.display()))
+}
+
+// Derivation trees of the queue entries, by corpus id, which are unparsed to bytes for the Go
+// harness. Kept in the state, so that restarted clients keep them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct GrammarTreesMetadata {
+    trees: HashMap<CorpusId, NautilusInput>,
+}
+
+libafl_bolts::impl_serdeany!(GrammarTreesMetadata);
+
+fn unparse(context: &NautilusContext, tree: &NautilusInput) -> BytesInput {
+    let mut bytes = Vec::new();
+    tree.unparse(context, &mut bytes);
+    BytesInput::new(bytes)
+}
+
+// Generate `count` derivation trees from the grammar, and add them to the queue whatever their
+// coverage, as the initial corpus
+pub(crate) fn generate_initial_inputs<E, EM, S, Z>(
+    context: &NautilusContext,
+    fuzzer: &mut Z,
+    executor: &mut E,
+    state: &mut S,
+    manager: &mut EM,
+    count: usize,
+) -> Result<(), Error>
+where
+    S: HasRand + HasMetadata,
+    Z: Evaluator<E, EM, BytesInput, S>,
+{
+    let mut generator = NautilusGenerator::new(context);
+    for _ in 0..count {
+        let tree = generator.generate(state)?;
+        let id = fuzzer.add_input(state, executor, manager, unparse(context, &tree))?;
+        state
+            .metadata_or_insert_with(GrammarTreesMetadata::default)
+            .trees
+            .insert(id, tree);
+    }
+    Ok(())
+}
+
+// State the Nautilus mutators run on, apart from the fuzzer's one whose inputs are bytes
+type TreeState =
+    StdState<InMemoryCorpus<NautilusInput>, NautilusInput, StdRand, InMemoryCorpus<NautilusInput>>;
+
+// Mutational stage of grammar-based fuzzing, replacing the byte-level mutators: the derivation
+// tree of the scheduled entry is mutated with the Nautilus mutators, and unparsed to bytes which
+// go through the same executor, feedbacks and objectives as any other input. Entries without a
+// tree, e.g. received from the other clients, are replaced by a random tree of the client.
+pub(crate) struct GrammarStage<'a, M> {
+    context: &'a NautilusContext,
+    mutator: M,
+    trees: TreeState,
+    // Entries whose tree was added to the chunks the splicing mutator picks from
+    chunked: HashSet<CorpusId>,
+}
+
+// Build the stage for the grammar of `context`, mutating with its own `rand`. The chunks of the
+// splicing mutator are stored in `chunks_dir`.
+pub(crate) fn grammar_stage<'a>(
+    context: &'a NautilusContext,
+    rand: StdRand,
+    chunks_dir: &Path,
+) -> Result<GrammarStage<'a, impl Mutator<NautilusInput, TreeState> + 'a>, Error> {
+    let mut trees = StdState::new(
+        rand,
+        InMemoryCorpus::new(),
+        InMemoryCorpus::new(),
+        &mut ConstFeedback::new(false),
+        &mut ConstFeedback::new(false),
+    )?;
+    trees.add_metadata(NautilusChunksMetadata::new(
+        chunks_dir.display().to_string(),
+    ));
+    let mutator = StdScheduledMutator::with_max_stack_pow(
+        tuple_list!(
+            NautilusRandomMutator::new(context),
+            NautilusRandomMutator::new(context),
+            NautilusRecursionMutator::new(context),
+            NautilusSpliceMutator::new(context),
+            NautilusSpliceMutator::new(context),
+        ),
+        2,
+    );
+    Ok(GrammarStage {
+        context,
+        mutator,
+        trees,
+        chunked: HashSet::new(),
+    })
+}
+
+impl<E, EM, M, S, Z> Stage<E, EM, S, Z> for GrammarStage<'_, M>
+where
+    M: Mutator<NautilusInput, TreeState>,
+    S: HasCurrentCorpusId + HasMetadata,
+    Z: Evaluator<E, EM, BytesInput, S>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let current = state.current_corpus_id()?;
+        let Some(metadata) = state.metadata_map().get::<GrammarTreesMetadata>() else {
+            return Ok(());
+        };
+        if metadata.trees.is_empty() {
+            return Ok(());
+        }
+        // The trees generated or added since the last run, and the ones the state was restored
+        // with, can be spliced
+        if metadata.trees.len() != self.chunked.len() {
+            let chunks = self
+                .trees
+                .metadata_map_mut()
+                .get_mut::<NautilusChunksMetadata>()
+                .unwrap();
+            for (id, tree) in &metadata.trees {
+                if self.chunked.insert(*id) {
+                    chunks.cks.add_tree(tree.tree.clone(), &self.context.ctx);
+                }
+            }
+        }
+        let base = match current.and_then(|id| metadata.trees.get(&id)) {
+            Some(tree) => tree.clone(),
+            None => {
+                let nth = self
+                    .trees
+                    .rand_mut()
+                    .below(metadata.trees.len().try_into()?);
+                metadata.trees.values().nth(nth).unwrap().clone()
+            }
+        };
+
+        let mutants = self.trees.rand_mut().below(nonzero!(128)) + 1;
+        for _ in 0..mutants {
+            let mut tree = base.clone();
+            if self.mutator.mutate(&mut self.trees, &mut tree)? == MutationResult::Skipped {
+                continue;
+            }
+            let input = unparse(self.context, &tree);
+            if skips(&input.target_bytes()) {
+                continue;
+            }
+            let (_, id) = fuzzer.evaluate_input(state, executor, manager, &input)?;
+            self.mutator.post_exec(&mut self.trees, id)?;
+            if let Some(id) = id {
+                state
+                    .metadata_or_insert_with(GrammarTreesMetadata::default)
+                    .trees
+                    .insert(id, tree);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<M, S> Restartable<S> for GrammarStage<'_, M> {
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+}
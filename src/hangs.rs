@@ -0,0 +1,55 @@
+use libafl::{
+    corpus::{Corpus, OnDiskCorpus, Testcase},
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    Error,
+};
+use libafl_bolts::Named;
+use std::{borrow::Cow, path::PathBuf};
+
+// Objective feedback storing timed out inputs in their own corpus, so that hangs don't end up
+// mixed with crashes. Timeouts aren't solutions, so that `replay-crashes` only finds inputs
+// which crash in `crashes`.
+pub(crate) struct HangFeedback<I> {
+    hangs: Option<OnDiskCorpus<I>>,
+}
+
+impl<I> HangFeedback<I>
+where
+    I: Input,
+{
+    // Hangs are stored in `dir`, or discarded if it is `None`
+    pub(crate) fn new(dir: Option<PathBuf>) -> Result<Self, Error> {
+        let hangs = dir.map(OnDiskCorpus::new).transpose()?;
+        Ok(Self { hangs })
+    }
+}
+
+impl<I> Named for HangFeedback<I> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("HangFeedback");
+        &NAME
+    }
+}
+
+impl<I, S> StateInitializer<S> for HangFeedback<I> {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for HangFeedback<I>
+where
+    I: Input,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &I,
+        _observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        if let (ExitKind::Timeout, Some(hangs)) = (exit_kind, self.hangs.as_mut()) {
+            hangs.add(Testcase::new(input.clone()))?;
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    control::{request_stop, set_paused},
+    fuzz::{is_file, stored_files},
+    stats::SharedStats,
+};
+use libafl::Error;
+use serde_json::{json, Value};
+use std::{
+    fs::read_to_string,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    thread,
+    time::{Duration, UNIX_EPOCH},
+};
+
+// Time given to a controller to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Serve the status of the campaign and its controls over HTTP on `listener`, from a thread of
+// the broker. Requests are handled one at a time, which is plenty for a fleet controller:
+// - `GET /stats`: the stats of the campaign, as in `stats.json`
+// - `GET /crashes`: the crashes stored in the output's crashes directory
+// - `POST /stop`: make the clients leave, as on SIGTERM
+// - `POST /pause` and `POST /resume`: pause the clients, or resume them
+pub(crate) fn serve(listener: &str, output: &Path, stats: SharedStats) -> Result<(), Error> {
+    let listener = TcpListener::bind(listener)?;
+    let crashes_dir = output.join("crashes");
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            if let Err(err) = handle(stream, &crashes_dir, &stats) {
+                eprintln!("Failed to answer an HTTP request: {err}");
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, crashes_dir: &Path, stats: &SharedStats) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers, and the body if any, are ignored
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let mut fields = request.split_whitespace();
+    let (status, body) = match (fields.next().unwrap_or(""), fields.next().unwrap_or("")) {
+        ("GET", "/stats") => match stats.lock().unwrap().clone() {
+            Some(stats) => ("200 OK", stats),
+            // Until the first client reports
+            None => (
+                "503 Service Unavailable",
+                json!({ "error": "no stats yet" }),
+            ),
+        },
+        ("GET", "/crashes") => ("200 OK", crashes(crashes_dir)),
+        ("POST", "/stop") => {
+            request_stop();
+            ("200 OK", json!({ "stopping": true }))
+        }
+        ("POST", "/pause") => {
+            set_paused(true);
+            ("200 OK", json!({ "paused": true }))
+        }
+        ("POST", "/resume") => {
+            set_paused(false);
+            ("200 OK", json!({ "paused": false }))
+        }
+        (_, "/stats" | "/crashes" | "/stop" | "/pause" | "/resume") => (
+            "405 Method Not Allowed",
+            json!({ "error": "method not allowed" }),
+        ),
+        _ => ("404 Not Found", json!({ "error": "not found" })),
+    };
+    let body = format!("{body}\n");
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+// Name, size, time and kind of the stored crashes, oldest first
+fn crashes(crashes_dir: &Path) -> Value {
+    let mut crashes = stored_files(crashes_dir)
+        .filter(is_file)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Written by the crash metadata feedback, without a kind if the output isn't captured
+            let kind = read_to_string(crashes_dir.join(format!(".{name}.meta.json")))
+                .ok()
+                .and_then(|meta| serde_json::from_str::<Value>(&meta).ok())
+                .and_then(|meta| meta["kind"].as_str().map(str::to_owned));
+            let timestamp = metadata
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            Some(json!({
+                "name": name,
+                "size": metadata.len(),
+                "timestamp": timestamp,
+                "kind": kind,
+            }))
+        })
+        .collect::<Vec<_>>();
+    crashes.sort_by_key(|crash| crash["timestamp"].as_u64());
+    Value::Array(crashes)
+}
@@ -0,0 +1,981 @@
+// GoLibAFL: fuzzing Go targets exporting the libFuzzer interface with LibAFL.
+//
+// The `golibafl` binary is a thin command line wrapper around this crate. To embed the fuzzer in
+// another binary, configure it with `GoLibAfl::builder()` and call one of the modes:
+//
+//     GoLibAfl::builder()
+//         .input("./seeds")
+//         .output("./findings")
+//         .cores(Cores::from_cmdline("0-3").unwrap())
+//         .build()
+//         .fuzz();
+//
+// The Go harness must be linked into the final binary, which `build.rs` takes care of.
+
+mod batch;
+mod bench;
+mod calibration;
+#[cfg(feature = "cloud")]
+mod cloud;
+mod cmplog;
+mod compress;
+mod control;
+mod cov;
+mod cull;
+mod dict;
+mod diff;
+mod doctor;
+mod dump;
+mod edges;
+mod elf;
+mod executor;
+mod export;
+mod fds;
+mod fields;
+mod filter;
+mod fuzz;
+pub mod gocorpus;
+#[cfg(feature = "grammar")]
+mod grammar;
+mod hangs;
+#[cfg(feature = "http")]
+mod http;
+mod logs;
+mod meta;
+mod minimize;
+mod mutation_stats;
+mod mutators;
+mod noise;
+mod notify;
+mod oom;
+mod output;
+mod overrides;
+mod plugin;
+mod race;
+mod rarity;
+mod remote;
+mod report;
+mod repro;
+mod restarts;
+mod run;
+#[cfg(target_os = "linux")]
+mod sandbox;
+mod sanitizers;
+mod sarif;
+mod scheduler;
+#[cfg(feature = "scripting")]
+mod script;
+mod signals;
+mod snapshot;
+mod stability;
+mod stages;
+mod stats;
+mod sync;
+mod timeout;
+mod triage;
+
+pub use cmplog::{CmpLogMode, CmpLogRate};
+pub use cov::CovFormat;
+pub use libafl::schedulers::powersched::PowerSchedule;
+pub use libafl_bolts::core_affinity::Cores;
+pub use mutators::MutatorSet;
+pub use overrides::ClientOverrides;
+pub use report::CampaignReport;
+pub use run::RunOptions;
+pub use scheduler::SchedulerKind;
+pub use timeout::AutoTimeout;
+
+use regex::Regex;
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Configuration shared by all the fuzzer modes
+#[derive(Debug, Clone)]
+pub struct GoLibAfl {
+    cores: Cores,
+    broker_port: u16,
+    remote_broker: Option<SocketAddr>,
+    broker_only: bool,
+    config_name: Option<String>,
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    timeout: Duration,
+    auto_timeout: Option<AutoTimeout>,
+    dicts: Vec<PathBuf>,
+    hangs: bool,
+    corpus_shards: bool,
+    client_workdirs: bool,
+    max_len: Option<NonZeroUsize>,
+    min_len: Option<usize>,
+    prefix: Option<Vec<u8>>,
+    filter_plugin: Option<PathBuf>,
+    corpus_cache: NonZeroUsize,
+    in_memory_corpus: bool,
+    compress_corpus: bool,
+    resume: bool,
+    force: bool,
+    max_corpus_entries: Option<usize>,
+    max_corpus_bytes: Option<u64>,
+    max_total_time: Option<Duration>,
+    max_execs: Option<u64>,
+    restart_interval_execs: Option<u64>,
+    stop_on_crash: bool,
+    keep_going: Option<usize>,
+    json_stats: bool,
+    plot_data: bool,
+    run_options: RunOptions,
+    capture_output: bool,
+    races: bool,
+    crash_patterns: Vec<Regex>,
+    reference: Option<PathBuf>,
+    reference_symbol: Option<String>,
+    client_logs: Option<PathBuf>,
+    foreground: bool,
+    power_schedule: PowerSchedule,
+    scheduler: SchedulerKind,
+    minimizer: bool,
+    rare_edges: bool,
+    client_overrides: BTreeMap<usize, ClientOverrides>,
+    mutators: MutatorSet,
+    mutator_plugin: Option<PathBuf>,
+    seed: Option<u64>,
+    cmplog: CmpLogMode,
+    cmplog_rate: Option<CmpLogRate>,
+    calibration_cycles: Option<usize>,
+    rss_limit_mb: Option<u64>,
+    fd_leaks: bool,
+    stability_threshold: u8,
+    notify_url: Option<String>,
+    sandbox: bool,
+    reports: Vec<CampaignReport>,
+    env: Vec<(String, String)>,
+    value_profile: bool,
+    pcguard_coverage: bool,
+    forking: bool,
+    batch_size: Option<usize>,
+    mask_runtime_edges: bool,
+    focus: Vec<String>,
+    focus_changed_packages: Option<PathBuf>,
+    sync_dir: Option<PathBuf>,
+    sync_id: String,
+    sync_in: Vec<PathBuf>,
+    watch_input: bool,
+    #[cfg(feature = "prometheus")]
+    prometheus: Option<String>,
+    #[cfg(feature = "http")]
+    http: Option<String>,
+    #[cfg(feature = "cloud")]
+    cloud_bucket: Option<String>,
+    #[cfg(feature = "cloud")]
+    cloud_seed: bool,
+    #[cfg(feature = "grammar")]
+    grammar: Option<PathBuf>,
+    #[cfg(feature = "scripting")]
+    objective_script: Option<PathBuf>,
+}
+
+impl GoLibAfl {
+    pub fn builder() -> GoLibAflBuilder {
+        GoLibAflBuilder::default()
+    }
+
+    // Fuzz the target, using `inputs` as initial corpus and storing findings in `output`.
+    // Returns the crash which ended the campaign, if `stop_on_crash` is set.
+    pub fn fuzz(&self) -> Option<PathBuf> {
+        let config = &if self.client_workdirs {
+            self.absolute_paths()
+        } else {
+            self.clone()
+        };
+        fuzz::fuzz(config)
+    }
+
+    // Copy of the configuration with the paths the clients use made absolute, for them to work
+    // from their own directory
+    fn absolute_paths(&self) -> Self {
+        let absolute = |path: &PathBuf| std::path::absolute(path).unwrap_or_else(|_| path.clone());
+        let mut config = self.clone();
+        // Seed archives are downloaded by URL
+        config.inputs = self
+            .inputs
+            .iter()
+            .map(|input| {
+                if remote::is_url(input) {
+                    input.clone()
+                } else {
+                    absolute(input)
+                }
+            })
+            .collect();
+        config.output = absolute(&self.output);
+        config.dicts = self.dicts.iter().map(absolute).collect();
+        config.client_logs = self.client_logs.as_ref().map(absolute);
+        config.sync_dir = self.sync_dir.as_ref().map(absolute);
+        config.sync_in = self.sync_in.iter().map(absolute).collect();
+        config.mutator_plugin = self.mutator_plugin.as_ref().map(absolute);
+        config.filter_plugin = self.filter_plugin.as_ref().map(absolute);
+        config.reference = self.reference.as_ref().map(absolute);
+        #[cfg(feature = "scripting")]
+        {
+            config.objective_script = self.objective_script.as_ref().map(absolute);
+        }
+        config
+    }
+
+    // The initial corpus directory, or input file, of the modes taking a single one
+    fn input(&self) -> &Path {
+        &self.inputs[0]
+    }
+
+    // Run the inputs in `input` without fuzzing
+    pub fn run(&self) {
+        run::run(self.input(), &self.run_options);
+    }
+
+    // Replay the corpus in `input` with Go's coverage tooling, and print the files and functions
+    // it reaches, or an LCOV tracefile. `coverpkg` defaults to all the non-standard packages of
+    // the harness.
+    pub fn cov(&self, coverpkg: Option<&str>, format: CovFormat) {
+        cov::cov(self.input(), coverpkg, format);
+    }
+
+    // Run the inputs in `input` in a loop for `duration`, calling the harness directly and then
+    // through the fuzzer's executor and observers, and report the throughput of both
+    pub fn bench(&self, duration: Duration) {
+        bench::bench(self.input(), duration, self.timeout);
+    }
+
+    // Check that the linked Go target is correctly built and instrumented
+    pub fn doctor(&self) {
+        doctor::doctor();
+    }
+
+    // Minimize the corpus in `input` into the `output` directory
+    pub fn cmin(&self) {
+        minimize::cmin(self.input(), &self.output);
+    }
+
+    // Merge the corpora in `dirs` into `output`, only copying the inputs adding coverage
+    pub fn merge(&self, dirs: &[PathBuf]) {
+        minimize::merge(&self.output, dirs);
+    }
+
+    // Minimize the testcase file `input` into the `output` file
+    pub fn tmin(&self) {
+        minimize::tmin(self.input(), &self.output);
+    }
+
+    // Export the crashes of the campaign in `output` as Go native corpus files, to
+    // `<package>/testdata/fuzz/<fuzz_target>`, where `go test` replays them
+    pub fn export_go_corpus(&self, package: &Path, fuzz_target: &str) {
+        gocorpus::export_crashes(&self.output, package, fuzz_target);
+    }
+
+    // Export the crashes of the campaign in `output` as a Go test of the package in `package`,
+    // calling `function` with each of them, so that `go test` keeps them as regression tests
+    pub fn export_go_tests(&self, package: &Path, function: &str) {
+        gocorpus::export_tests(&self.output, package, function);
+    }
+
+    // Package the queue of the campaign in `output` into the compressed `archive`, only keeping
+    // the entries `cmin` keeps with `minimize`, and along with their metadata with `metadata`
+    pub fn export_queue(&self, archive: &Path, minimize: bool, metadata: bool) {
+        export::export_queue(&self.output, archive, minimize, metadata);
+    }
+
+    // Replay the crashes of a previous campaign in `output` `runs` times each, classify them and
+    // move the flaky ones aside. Replays go through the `run` subcommand of the current executable.
+    pub fn triage(&self, runs: usize) {
+        triage::triage(&self.output, runs, self.timeout);
+    }
+
+    // Replay the crashes in `crashes` once each, in their own process, report which still
+    // reproduce and group them by signature. The JSON report defaults to `replay.json`, next to
+    // the crashes directory.
+    pub fn replay_crashes(&self, crashes: &Path, report: Option<&Path>) {
+        let report =
+            report.map_or_else(|| triage::default_replay_report(crashes), Path::to_path_buf);
+        triage::replay_crashes(crashes, &report, self.timeout);
+    }
+
+    // Replay the crashes in `crashes` and write the ones which reproduce as a SARIF report, one
+    // alert per signature located at the Go source line it happened on. The report defaults to
+    // `crashes.sarif`, next to the crashes directory. Source paths under `source_root` are made
+    // relative to it, as code scanning expects paths relative to the repository.
+    pub fn export_sarif(&self, crashes: &Path, report: Option<&Path>, source_root: Option<&Path>) {
+        let report = report.map_or_else(|| sarif::default_sarif_report(crashes), Path::to_path_buf);
+        sarif::export_sarif(crashes, &report, source_root, self.timeout);
+    }
+}
+
+// Builder for `GoLibAfl`, defaulting to the same values as the command line. Like the command
+// line, it selects among the built-in schedulers and feedbacks, it doesn't take LibAFL ones.
+#[derive(Debug, Clone)]
+pub struct GoLibAflBuilder {
+    config: GoLibAfl,
+}
+
+impl Default for GoLibAflBuilder {
+    fn default() -> Self {
+        Self {
+            config: GoLibAfl {
+                cores: Cores::all().expect("Failed to list the available cores"),
+                broker_port: 1337,
+                remote_broker: None,
+                broker_only: false,
+                config_name: None,
+                inputs: vec![PathBuf::from("./input")],
+                output: PathBuf::from("./output"),
+                timeout: Duration::from_millis(1000),
+                auto_timeout: None,
+                dicts: Vec::new(),
+                hangs: true,
+                corpus_shards: true,
+                client_workdirs: true,
+                max_len: None,
+                min_len: None,
+                prefix: None,
+                filter_plugin: None,
+                corpus_cache: NonZeroUsize::new(4096).unwrap(),
+                in_memory_corpus: false,
+                compress_corpus: false,
+                resume: false,
+                force: false,
+                max_corpus_entries: None,
+                max_corpus_bytes: None,
+                max_total_time: None,
+                max_execs: None,
+                restart_interval_execs: None,
+                stop_on_crash: false,
+                keep_going: None,
+                json_stats: false,
+                plot_data: false,
+                run_options: RunOptions::default(),
+                capture_output: false,
+                crash_patterns: Vec::new(),
+                reference: None,
+                reference_symbol: None,
+                races: false,
+                client_logs: None,
+                foreground: false,
+                power_schedule: PowerSchedule::fast(),
+                scheduler: SchedulerKind::Weighted,
+                minimizer: true,
+                rare_edges: false,
+                client_overrides: BTreeMap::new(),
+                mutators: MutatorSet::MOpt,
+                mutator_plugin: None,
+                seed: None,
+                cmplog: CmpLogMode::Routines,
+                cmplog_rate: None,
+                calibration_cycles: None,
+                rss_limit_mb: None,
+                fd_leaks: false,
+                stability_threshold: 90,
+                notify_url: None,
+                sandbox: false,
+                reports: Vec::new(),
+                env: Vec::new(),
+                value_profile: false,
+                pcguard_coverage: false,
+                forking: false,
+                batch_size: None,
+                mask_runtime_edges: false,
+                focus: Vec::new(),
+                focus_changed_packages: None,
+                sync_dir: None,
+                sync_id: String::from("golibafl"),
+                sync_in: Vec::new(),
+                watch_input: false,
+                #[cfg(feature = "prometheus")]
+                prometheus: None,
+                #[cfg(feature = "http")]
+                http: None,
+                #[cfg(feature = "cloud")]
+                cloud_bucket: None,
+                #[cfg(feature = "cloud")]
+                cloud_seed: false,
+                #[cfg(feature = "grammar")]
+                grammar: None,
+                #[cfg(feature = "scripting")]
+                objective_script: None,
+            },
+        }
+    }
+}
+
+impl GoLibAflBuilder {
+    // Cores to spawn clients on, the broker runs on the first one
+    #[must_use]
+    pub fn cores(mut self, cores: Cores) -> Self {
+        self.config.cores = cores;
+        self
+    }
+
+    // TCP port of the broker
+    #[must_use]
+    pub fn broker_port(mut self, broker_port: u16) -> Self {
+        self.config.broker_port = broker_port;
+        self
+    }
+
+    // Broker of another machine to connect the local broker to, so that the clients of both
+    // machines share their corpus
+    #[must_use]
+    pub fn remote_broker(mut self, remote_broker: Option<SocketAddr>) -> Self {
+        self.config.remote_broker = remote_broker;
+        self
+    }
+
+    // Only run the broker, without spawning any client, for the other machines to connect to
+    #[must_use]
+    pub fn broker_only(mut self, broker_only: bool) -> Self {
+        self.config.broker_only = broker_only;
+        self
+    }
+
+    // Name of the events' configuration, for the clients to only trust the coverage reported by
+    // the clients of the same target. Defaults to a hash of the executable.
+    #[must_use]
+    pub fn config_name(mut self, config_name: Option<String>) -> Self {
+        self.config.config_name = config_name;
+        self
+    }
+
+    // Initial corpus directory (will only be read), or input file for `tmin`
+    #[must_use]
+    pub fn input(mut self, input: impl Into<PathBuf>) -> Self {
+        self.config.inputs = vec![input.into()];
+        self
+    }
+
+    // Initial corpus directories of `fuzz` (will only be read), merged along with their
+    // subdirectories, each distinct file once. HTTP(S) URLs of seed archives, e.g.
+    // `https://example.com/corpus.tar.gz`, are downloaded and unpacked in `output/remote`.
+    #[must_use]
+    pub fn inputs(mut self, inputs: Vec<PathBuf>) -> Self {
+        if !inputs.is_empty() {
+            self.config.inputs = inputs;
+        }
+        self
+    }
+
+    // Fuzzer's output directory, or output file for `tmin`
+    #[must_use]
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.config.output = output.into();
+        self
+    }
+
+    // Per-input execution timeout
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    // Derive the timeout from the calibrated average execution time, the `timeout` being the
+    // upper bound
+    #[must_use]
+    pub fn auto_timeout(mut self, auto_timeout: Option<AutoTimeout>) -> Self {
+        self.config.auto_timeout = auto_timeout;
+        self
+    }
+
+    // AFL/libFuzzer-style dictionary files, merged with the tokens extracted from the target
+    #[must_use]
+    pub fn dicts<P: Into<PathBuf>>(mut self, dicts: impl IntoIterator<Item = P>) -> Self {
+        self.config.dicts.extend(dicts.into_iter().map(Into::into));
+        self
+    }
+
+    // Store inputs exceeding the timeout in `output/hangs`
+    #[must_use]
+    pub fn hangs(mut self, hangs: bool) -> Self {
+        self.config.hangs = hangs;
+        self
+    }
+
+    // Split the initial corpus between the clients, each one executing its own shard
+    #[must_use]
+    pub fn corpus_shards(mut self, corpus_shards: bool) -> Self {
+        self.config.corpus_shards = corpus_shards;
+        self
+    }
+
+    // Run each client from its own directory, `<output>/tmp/<client>`, emptied when it starts, so
+    // that the scratch files the target writes to its working directory don't collide
+    #[must_use]
+    pub fn client_workdirs(mut self, client_workdirs: bool) -> Self {
+        self.config.client_workdirs = client_workdirs;
+        self
+    }
+
+    // Maximum size of the generated and mutated inputs, `None` to keep LibAFL's default
+    #[must_use]
+    pub fn max_len(mut self, max_len: Option<NonZeroUsize>) -> Self {
+        self.config.max_len = max_len;
+        self
+    }
+
+    // Skip, instead of executing, the inputs shorter than `min_len`, not starting with `prefix`,
+    // or which the `golibafl_filter` function of the `plugin` dynamic library returns 0 for. The
+    // harness never sees them, and they aren't added to the corpus.
+    #[must_use]
+    pub fn input_filter(
+        mut self,
+        min_len: Option<usize>,
+        prefix: Option<Vec<u8>>,
+        plugin: Option<PathBuf>,
+    ) -> Self {
+        self.config.min_len = min_len;
+        self.config.prefix = prefix;
+        self.config.filter_plugin = plugin;
+        self
+    }
+
+    // Number of queue entries each client keeps loaded in memory
+    #[must_use]
+    pub fn corpus_cache(mut self, corpus_cache: NonZeroUsize) -> Self {
+        self.config.corpus_cache = corpus_cache;
+        self
+    }
+
+    // Keep the whole queue in memory, it is still written to disk but never read back
+    #[must_use]
+    pub fn in_memory_corpus(mut self, in_memory_corpus: bool) -> Self {
+        self.config.in_memory_corpus = in_memory_corpus;
+        self
+    }
+
+    // Write the queue entries compressed with zstd, for targets with large inputs. They are
+    // decompressed when loaded back, and by the other modes reading the queue.
+    #[must_use]
+    pub fn compress_corpus(mut self, compress_corpus: bool) -> Self {
+        self.config.compress_corpus = compress_corpus;
+        self
+    }
+
+    // Continue the previous campaign in `output`: its queue is loaded instead of the initial
+    // corpus, and the state each client saved in `<output>/state` is restored, i.e. the power
+    // schedule metadata, the MOpt probabilities, the tokens and the executions
+    #[must_use]
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.config.resume = resume;
+        self
+    }
+
+    // Clear the queue and the state of a previous campaign in `output` instead of refusing to
+    // start, unless resuming it. Its crashes, hangs, OOMs and races are kept.
+    #[must_use]
+    pub fn force(mut self, force: bool) -> Self {
+        self.config.force = force;
+        self
+    }
+
+    // Number of queue entries above which each client drops its redundant ones
+    #[must_use]
+    pub fn max_corpus_entries(mut self, max_corpus_entries: Option<usize>) -> Self {
+        self.config.max_corpus_entries = max_corpus_entries;
+        self
+    }
+
+    // Size in bytes of the queue above which each client drops its redundant entries
+    #[must_use]
+    pub fn max_corpus_bytes(mut self, max_corpus_bytes: Option<u64>) -> Self {
+        self.config.max_corpus_bytes = max_corpus_bytes;
+        self
+    }
+
+    // Wall-clock budget of the campaign, after which all the clients exit cleanly
+    #[must_use]
+    pub fn max_total_time(mut self, max_total_time: Option<Duration>) -> Self {
+        self.config.max_total_time = max_total_time;
+        self
+    }
+
+    // Number of executions after which each client exits cleanly
+    #[must_use]
+    pub fn max_execs(mut self, max_execs: Option<u64>) -> Self {
+        self.config.max_execs = max_execs;
+        self
+    }
+
+    // Number of executions after which each client exits and is relaunched with its state, to
+    // reset the heap and runtime state the Go target accumulates
+    #[must_use]
+    pub fn restart_interval_execs(mut self, restart_interval_execs: Option<u64>) -> Self {
+        self.config.restart_interval_execs = restart_interval_execs;
+        self
+    }
+
+    // Stop all the clients as soon as one of them finds a crash
+    #[must_use]
+    pub fn stop_on_crash(mut self, stop_on_crash: bool) -> Self {
+        self.config.stop_on_crash = stop_on_crash;
+        self
+    }
+
+    // Stop the campaign once this many crashes with distinct signatures were found
+    #[must_use]
+    pub fn keep_going(mut self, keep_going: Option<usize>) -> Self {
+        self.config.keep_going = keep_going;
+        self
+    }
+
+    // Periodically append the clients' stats to `output/stats.ndjson`, and write the campaign's
+    // to `output/stats.json`
+    #[must_use]
+    pub fn json_stats(mut self, json_stats: bool) -> Self {
+        self.config.json_stats = json_stats;
+        self
+    }
+
+    // Write AFL++-compatible `plot_data` files to `output/plot/<client>`, for `afl-plot`
+    #[must_use]
+    pub fn plot_data(mut self, plot_data: bool) -> Self {
+        self.config.plot_data = plot_data;
+        self
+    }
+
+    // Corpus scheduler picking the next entry to fuzz
+    #[must_use]
+    pub fn scheduler(mut self, scheduler: SchedulerKind) -> Self {
+        self.config.scheduler = scheduler;
+        self
+    }
+
+    // Favor the smallest and fastest corpus entry reaching each edge
+    #[must_use]
+    pub fn minimizer(mut self, minimizer: bool) -> Self {
+        self.config.minimizer = minimizer;
+        self
+    }
+
+    // Boost the weight of the corpus entries hitting rarely seen edges, with the weighted
+    // scheduler
+    #[must_use]
+    pub fn rare_edges(mut self, rare_edges: bool) -> Self {
+        self.config.rare_edges = rare_edges;
+        self
+    }
+
+    // Settings of some clients, by id, overriding the campaign's ones
+    #[must_use]
+    pub fn client_overrides(mut self, client_overrides: BTreeMap<usize, ClientOverrides>) -> Self {
+        self.config.client_overrides = client_overrides;
+        self
+    }
+
+    // Power schedule of the weighted corpus scheduler
+    #[must_use]
+    pub fn power_schedule(mut self, power_schedule: PowerSchedule) -> Self {
+        self.config.power_schedule = power_schedule;
+        self
+    }
+
+    // Mutations applied by the power mutational stage
+    #[must_use]
+    pub fn mutators(mut self, mutators: MutatorSet) -> Self {
+        self.config.mutators = mutators;
+        self
+    }
+
+    // Dynamic library exporting `golibafl_mutate`, whose mutations run after the selected set
+    #[must_use]
+    pub fn mutator_plugin(mut self, mutator_plugin: Option<PathBuf>) -> Self {
+        self.config.mutator_plugin = mutator_plugin;
+        self
+    }
+
+    // Seed of the clients' random number generators, instead of a random one, to reproduce a
+    // single-core campaign
+    #[must_use]
+    pub fn seed(mut self, seed: Option<u64>) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    // Comparisons traced for the input-to-state mutations
+    #[must_use]
+    pub fn cmplog(mut self, cmplog: CmpLogMode) -> Self {
+        self.config.cmplog = cmplog;
+        self
+    }
+
+    // Only trace the comparisons of some of the scheduled corpus entries, instead of all of them
+    #[must_use]
+    pub fn cmplog_rate(mut self, cmplog_rate: Option<CmpLogRate>) -> Self {
+        self.config.cmplog_rate = cmplog_rate;
+        self
+    }
+
+    // Calibrate the new corpus entries with this many runs, 0 to skip their calibration, instead
+    // of LibAFL's calibration and its tracking of the unstable edges
+    #[must_use]
+    pub fn calibration_cycles(mut self, calibration_cycles: Option<usize>) -> Self {
+        self.config.calibration_cycles = calibration_cycles;
+        self
+    }
+
+    // Store inputs growing the resident memory by more than this many MiB in `output/oom`
+    #[must_use]
+    pub fn rss_limit_mb(mut self, rss_limit_mb: Option<u64>) -> Self {
+        self.config.rss_limit_mb = rss_limit_mb;
+        self
+    }
+
+    // Store inputs leaving file descriptors open in `output/fd-leaks`
+    #[must_use]
+    pub fn fd_leaks(mut self, fd_leaks: bool) -> Self {
+        self.config.fd_leaks = fd_leaks;
+        self
+    }
+
+    // Warn when the share of a client's edges reached reliably by their inputs drops below this
+    // percentage, 0 to never warn
+    #[must_use]
+    pub fn stability_threshold(mut self, stability_threshold: u8) -> Self {
+        self.config.stability_threshold = stability_threshold;
+        self
+    }
+
+    // Webhook POSTed a JSON payload, through curl, whenever a crash with a new signature is
+    // saved. Signatures rely on the captured output of the target.
+    #[must_use]
+    pub fn notify_url(mut self, notify_url: Option<String>) -> Self {
+        self.config.notify_url = notify_url;
+        self
+    }
+
+    // Once initialized, forbid the clients to reach the network and to write outside of the
+    // output, sync and client logs directories. Linux only.
+    #[must_use]
+    pub fn sandbox(mut self, sandbox: bool) -> Self {
+        self.config.sandbox = sandbox;
+        self
+    }
+
+    // Reports written once the campaign is over, from a replay of its crashes
+    #[must_use]
+    pub fn reports(mut self, reports: Vec<CampaignReport>) -> Self {
+        self.config.reports = reports;
+        self
+    }
+
+    // Environment variables set for the target, repeated in the reproducer script written next to
+    // each crash. They must already be set in the process, the Go runtime reads them at load.
+    #[must_use]
+    pub fn env(mut self, env: Vec<(String, String)>) -> Self {
+        self.config.env = env;
+        self
+    }
+
+    // Reward inputs matching more bits of the target's comparisons, like libFuzzer's
+    // `-use_value_profile=1`
+    #[must_use]
+    pub fn value_profile(mut self, value_profile: bool) -> Self {
+        self.config.value_profile = value_profile;
+        self
+    }
+
+    // Reward inputs reaching new coverage of the code instrumented with trace-pc-guard, n-gram or
+    // context-sensitive with the `ngram` or `ctx` feature
+    #[must_use]
+    pub fn pcguard_coverage(mut self, pcguard_coverage: bool) -> Self {
+        self.config.pcguard_coverage = pcguard_coverage;
+        self
+    }
+
+    // Run each input in a forked child of the client, so that inputs corrupting the global state
+    // of the target or deadlocking its runtime don't affect the following executions. Slower, and
+    // disables comparison tracing, value profiling, the trace-pc-guard coverage and the RSS limit.
+    #[must_use]
+    pub fn forking(mut self, forking: bool) -> Self {
+        self.config.forking = forking;
+        self
+    }
+
+    // Execute the mutants in batches of this size, amortizing the cost of an execution on very
+    // fast targets. Batches use havoc and token mutations, whatever the mutator set.
+    #[must_use]
+    pub fn batch_size(mut self, batch_size: Option<usize>) -> Self {
+        self.config.batch_size = batch_size;
+        self
+    }
+
+    // Ignore the edges of the Go runtime packages, which vary with garbage collection, scheduling
+    // and map iteration order. Requires an executable with its symbol table.
+    #[must_use]
+    pub fn mask_runtime_edges(mut self, mask_runtime_edges: bool) -> Self {
+        self.config.mask_runtime_edges = mask_runtime_edges;
+        self
+    }
+
+    // Only reward the edges of these Go packages, by import path, with `<path>/...` matching the
+    // subpackages too. Requires an executable with its symbol table.
+    #[must_use]
+    pub fn focus(mut self, focus: Vec<String>) -> Self {
+        self.config.focus = focus;
+        self
+    }
+
+    // File listing the Go functions changed by a patch, one symbol name per line such as
+    // `github.com/me/lib.(*Parser).Parse`. The packages defining them are focused on, along
+    // with the `focus` ones: the edges can't be attributed to functions.
+    #[must_use]
+    pub fn focus_changed_packages(mut self, focus_changed_packages: Option<PathBuf>) -> Self {
+        self.config.focus_changed_packages = focus_changed_packages;
+        self
+    }
+
+    // AFL++-style sync directory: the queues of the other fuzzers are imported from
+    // `<sync_dir>/*/queue`, and each client exports its own to
+    // `<sync_dir>/<sync_id>-<client>/queue`
+    #[must_use]
+    pub fn sync_dir(mut self, sync_dir: Option<PathBuf>) -> Self {
+        self.config.sync_dir = sync_dir;
+        self
+    }
+
+    // Prefix of the clients' fuzzer ids in the sync directory
+    #[must_use]
+    pub fn sync_id(mut self, sync_id: impl Into<String>) -> Self {
+        self.config.sync_id = sync_id.into();
+        self
+    }
+
+    // Directories polled every minute for new seeds, e.g. written by another tool while the
+    // campaign runs. The ones adding coverage are imported.
+    #[must_use]
+    pub fn sync_in(mut self, sync_in: Vec<PathBuf>) -> Self {
+        self.config.sync_in = sync_in;
+        self
+    }
+
+    // Also poll `inputs` for new seeds during the campaign, like the `sync_in` directories
+    #[must_use]
+    pub fn watch_input(mut self, watch_input: bool) -> Self {
+        self.config.watch_input = watch_input;
+        self
+    }
+
+    // Capture the target's stdout and stderr during each execution, and store them next to the
+    // crashes as `.<name>.output`
+    #[must_use]
+    pub fn capture_output(mut self, capture_output: bool) -> Self {
+        self.config.capture_output = capture_output;
+        self
+    }
+
+    // Save the inputs whose execution prints output matching one of `crash_patterns` as crashes
+    // of kind `invariant`, for targets reporting broken invariants without panicking. The
+    // target's output is captured, as with `capture_output`.
+    #[must_use]
+    pub fn crash_patterns(mut self, crash_patterns: Vec<Regex>) -> Self {
+        self.config.crash_patterns = crash_patterns;
+        self
+    }
+
+    // Dynamic library of a second implementation of the target, whose `symbol` entry point, by
+    // default `LLVMFuzzerTestOneInput`, runs each input after the harness. Inputs on which both
+    // return different values, or the library's `golibafl_compare` reports a divergence for, are
+    // saved as crashes.
+    #[must_use]
+    pub fn reference(mut self, reference: Option<PathBuf>, symbol: Option<String>) -> Self {
+        self.config.reference = reference;
+        self.config.reference_symbol = symbol;
+        self
+    }
+
+    // Store the inputs making the race detector of a target built with `-race` report a data
+    // race in `output/races`, with the report next to them as `.<name>.race`. The target's output
+    // is captured, as with `capture_output`.
+    #[must_use]
+    pub fn races(mut self, races: bool) -> Self {
+        self.config.races = races;
+        self
+    }
+
+    // Write the stdout and stderr of each client to `<dir>/client-<id>.log`, rotated once it
+    // grows past 16 MiB
+    #[must_use]
+    pub fn client_logs(mut self, client_logs: Option<PathBuf>) -> Self {
+        self.config.client_logs = client_logs;
+        self
+    }
+
+    // Keep the clients' stdout on the console instead of discarding it
+    #[must_use]
+    pub fn foreground(mut self, foreground: bool) -> Self {
+        self.config.foreground = foreground;
+        self
+    }
+
+    // libFuzzer-like options of the `run` mode
+    #[must_use]
+    pub fn run_options(mut self, run_options: RunOptions) -> Self {
+        self.config.run_options = run_options;
+        self
+    }
+
+    // Address the Prometheus metrics endpoint listens on, such as `0.0.0.0:8080`
+    #[cfg(feature = "prometheus")]
+    #[must_use]
+    pub fn prometheus(mut self, listener: Option<String>) -> Self {
+        self.config.prometheus = listener;
+        self
+    }
+
+    // Address the HTTP status and control endpoint listens on, such as `127.0.0.1:8081`
+    #[cfg(feature = "http")]
+    #[must_use]
+    pub fn http(mut self, listener: Option<String>) -> Self {
+        self.config.http = listener;
+        self
+    }
+
+    // Object store URL, `s3://<bucket>/<prefix>` or `gs://<bucket>/<prefix>`, the queue and the
+    // crashes are uploaded to every 5 minutes and when the campaign ends. Uploads go through the
+    // `aws` or `gsutil` command line tools.
+    #[cfg(feature = "cloud")]
+    #[must_use]
+    pub fn cloud_bucket(mut self, cloud_bucket: Option<String>) -> Self {
+        self.config.cloud_bucket = cloud_bucket;
+        self
+    }
+
+    // Also load the queue previous workers uploaded to the cloud bucket, to `output/cloud`
+    #[cfg(feature = "cloud")]
+    #[must_use]
+    pub fn cloud_seed(mut self, cloud_seed: bool) -> Self {
+        self.config.cloud_seed = cloud_seed;
+        self
+    }
+
+    // Nautilus grammar (JSON) to generate and mutate the inputs with, instead of bytes
+    #[cfg(feature = "grammar")]
+    #[must_use]
+    pub fn grammar(mut self, grammar: Option<PathBuf>) -> Self {
+        self.config.grammar = grammar;
+        self
+    }
+
+    // Rhai script whose `is_objective` function is called after each execution which didn't
+    // crash, to declare its input a solution from the target's output, execution time and coverage
+    #[cfg(feature = "scripting")]
+    #[must_use]
+    pub fn objective_script(mut self, objective_script: Option<PathBuf>) -> Self {
+        self.config.objective_script = objective_script;
+        self
+    }
+
+    pub fn build(self) -> GoLibAfl {
+        self.config
+    }
+}
@@ -0,0 +1,80 @@
+use libafl::Error;
+use std::{
+    fs::{create_dir_all, rename, File},
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// Size above which a log is rotated
+const MAX_LOG_SIZE: u64 = 16 << 20;
+// Number of rotated logs kept per client, as `client-<id>.log.<n>`
+const KEEP: usize = 3;
+// Interval between two checks of the log size
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Log file of a client, in `<dir>/client-<id>.log`, receiving its stdout and stderr. Rotated once
+// it grows past 16 MiB, keeping the 3 previous ones.
+pub(crate) struct ClientLog {
+    path: PathBuf,
+    last: Instant,
+}
+
+impl ClientLog {
+    // Redirect the stdout and stderr of the client to its log. Must be called before the
+    // target's output is captured, which redirects them again.
+    pub(crate) fn open(dir: &Path, client: usize) -> Result<Self, Error> {
+        create_dir_all(dir)?;
+        let log = Self {
+            path: dir.join(format!("client-{client}.log")),
+            last: Instant::now(),
+        };
+        if log.size() > MAX_LOG_SIZE {
+            log.rotate()?;
+        }
+        log.redirect()?;
+        Ok(log)
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= CHECK_INTERVAL
+    }
+
+    // Rotate the log if it grew too large
+    pub(crate) fn check(&mut self) -> Result<(), Error> {
+        self.last = Instant::now();
+        if self.size() > MAX_LOG_SIZE {
+            self.rotate()?;
+            self.redirect()?;
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> u64 {
+        self.path.metadata().map_or(0, |metadata| metadata.len())
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        self.path.with_extension(format!("log.{n}"))
+    }
+
+    fn rotate(&self) -> Result<(), Error> {
+        for n in (1..KEEP).rev() {
+            let _ = rename(self.rotated(n), self.rotated(n + 1));
+        }
+        rename(&self.path, self.rotated(1))?;
+        Ok(())
+    }
+
+    fn redirect(&self) -> Result<(), Error> {
+        let file = File::options().create(true).append(true).open(&self.path)?;
+        for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            if unsafe { libc::dup2(file.as_raw_fd(), fd) } == -1 {
+                return Err(Error::last_os_error(
+                    "Failed to redirect the client's output",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
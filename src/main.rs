@@ -1,88 +1,1090 @@
-use clap::{Parser, Subcommand};
-use libafl::{
-    corpus::{CachedOnDiskCorpus, Corpus, OnDiskCorpus},
-    executors::{inprocess::InProcessExecutor, ExitKind, ShadowExecutor},
-    feedback_or_fast,
-    feedbacks::{CrashFeedback, MaxMapFeedback},
-    fuzzer::{Fuzzer, StdFuzzer},
-    inputs::{BytesInput, HasTargetBytes},
-    mutators::scheduled::StdScheduledMutator,
-    nonzero,
-    prelude::{
-        havoc_mutations, powersched::PowerSchedule, tokens_mutations, CalibrationStage, CanTrack,
-        ClientDescription, EventConfig, I2SRandReplace, IndexesLenTimeMinimizerScheduler, Launcher,
-        RandBytesGenerator, SimpleMonitor, StdMOptMutator, StdMapObserver, StdWeightedScheduler,
-        TimeFeedback, TimeObserver, Tokens,
-    },
-    stages::{mutational::StdMutationalStage, ShadowTracingStage, StdPowerMutationalStage},
-    state::{HasCorpus, StdState},
-    Error, HasMetadata,
-};
-use libafl_bolts::{
-    prelude::{Cores, StdShMemProvider},
-    rands::StdRand,
-    shmem::ShMemProvider,
-    tuples::{tuple_list, Merge},
-};
-use libafl_targets::{
-    autotokens, extra_counters, libfuzzer::libfuzzer_test_one_input, libfuzzer_initialize,
-    CmpLogObserver, COUNTERS_MAPS,
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use golibafl::{
+    gocorpus, AutoTimeout, CampaignReport, ClientOverrides, CmpLogRate, Cores, CovFormat, GoLibAfl,
+    PowerSchedule, RunOptions, SchedulerKind,
 };
 use mimalloc::MiMalloc;
+use regex::Regex;
 use std::{
-    env,
-    fs::read_dir,
+    collections::BTreeMap,
+    env, fs,
+    net::{SocketAddr, ToSocketAddrs},
+    num::NonZeroUsize,
+    os::unix::process::CommandExt,
     path::{Path, PathBuf},
+    process::{self, Command},
     time::Duration,
 };
 
-#[global_allocator]
-static GLOBAL: MiMalloc = MiMalloc;
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+// Exit code when `--stop-on-crash` found a crash, same as libFuzzer's default
+const CRASH_EXIT_CODE: i32 = 77;
+
+// Parse a `KEY=VALUE` environment variable, as given to `--env`
+fn parse_env_var(var: &str) -> Result<(String, String), String> {
+    var.split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("Expected KEY=VALUE, got {var}"))
+}
+
+// Set the environment variables, re-executing golibafl if any of them changes: the Go runtime
+// reads GOMAXPROCS, GOGC or GODEBUG when the harness is loaded, before `main` runs. The clients
+// and the target then inherit them.
+fn apply_env(vars: &[(String, String)]) {
+    if vars
+        .iter()
+        .all(|(key, value)| env::var(key).is_ok_and(|current| current == *value))
+    {
+        return;
+    }
+    let exe = env::current_exe().expect("Unable to locate the golibafl executable");
+    let err = Command::new(exe)
+        .args(env::args_os().skip(1))
+        .envs(vars.iter().cloned())
+        .exec();
+    eprintln!("Failed to re-execute golibafl with the new environment: {err}");
+    process::exit(1);
+}
+
+// Bytes given on the command line, with the escapes of Go string literals
+#[derive(Debug, Clone)]
+struct EscapedBytes(Vec<u8>);
+
+fn parse_escaped_bytes(bytes: &str) -> Result<EscapedBytes, String> {
+    gocorpus::unescape(bytes)
+        .map(EscapedBytes)
+        .map_err(|err| err.to_string())
+}
+
+// Resolve a `host:port` address, as given to `--connect-to`
+fn parse_broker_addr(addr: &str) -> Result<SocketAddr, String> {
+    addr.to_socket_addrs()
+        .map_err(|err| format!("Unable to resolve {addr}: {err}"))?
+        .next()
+        .ok_or_else(|| format!("No address found for {addr}"))
+}
+
+// Mutator sets of the power mutational stage
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Mutators {
+    Mopt,
+    Havoc,
+    #[value(name = "havoc+tokens")]
+    HavocTokens,
+    Unicode,
+    Fields,
+}
+
+impl From<Mutators> for MutatorSet {
+    fn from(mutators: Mutators) -> Self {
+        match mutators {
+            Mutators::Mopt => MutatorSet::MOpt,
+            Mutators::Havoc => MutatorSet::Havoc,
+            Mutators::HavocTokens => MutatorSet::HavocTokens,
+            Mutators::Unicode => MutatorSet::Unicode,
+            Mutators::Fields => MutatorSet::Fields,
+        }
+    }
+}
+
+// Comparison tracing modes
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum CmpLog {
+    Off,
+    Ins,
+    Routines,
+}
+
+impl From<CmpLog> for CmpLogMode {
+    fn from(cmplog: CmpLog) -> Self {
+        match cmplog {
+            CmpLog::Off => CmpLogMode::Off,
+            CmpLog::Ins => CmpLogMode::Instructions,
+            CmpLog::Routines => CmpLogMode::Routines,
+        }
+    }
+}
+
+// Report formats of the `cov` mode
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Format {
+    Text,
+    Lcov,
+}
+
+impl From<Format> for CovFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Text => CovFormat::Text,
+            Format::Lcov => CovFormat::Lcov,
+        }
+    }
+}
+
+// Corpus schedulers
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Scheduler {
+    Weighted,
+    Queue,
+    Rand,
+    Powerqueue,
+}
+
+impl From<Scheduler> for SchedulerKind {
+    fn from(scheduler: Scheduler) -> Self {
+        match scheduler {
+            Scheduler::Weighted => SchedulerKind::Weighted,
+            Scheduler::Queue => SchedulerKind::Queue,
+            Scheduler::Rand => SchedulerKind::Rand,
+            Scheduler::Powerqueue => SchedulerKind::PowerQueue,
+        }
+    }
+}
+
+// Power schedules of the weighted corpus scheduler
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum Schedule {
+    Fast,
+    Coe,
+    Lin,
+    Quad,
+    Exploit,
+    Explore,
+}
+
+impl From<Schedule> for PowerSchedule {
+    fn from(schedule: Schedule) -> Self {
+        match schedule {
+            Schedule::Fast => PowerSchedule::fast(),
+            Schedule::Coe => PowerSchedule::coe(),
+            Schedule::Lin => PowerSchedule::lin(),
+            Schedule::Quad => PowerSchedule::quad(),
+            Schedule::Exploit => PowerSchedule::exploit(),
+            Schedule::Explore => PowerSchedule::explore(),
+        }
+    }
+}
+
+// Command line arguments with clap
+#[derive(Subcommand, Debug, Clone)]
+enum Mode {
+    Run {
+        #[clap(short, long, value_name = "DIR", default_value = "./input")]
+        input: PathBuf,
+
+        #[clap(
+            long = "timeout-ms",
+            value_name = "MS",
+            help = "Stop an execution and report the input as a timeout after MS milliseconds, \
+                    overriding -timeout="
+        )]
+        timeout: Option<u64>,
+
+        #[clap(
+            long,
+            help = "Print the new edges of each input and the total, running all the inputs in \
+                    the same process"
+        )]
+        coverage: bool,
+
+        #[clap(
+            long,
+            value_name = "KEY=VALUE",
+            value_parser = parse_env_var,
+            help = "Set an environment variable of the target, e.g. GODEBUG=madvdontneed=1"
+        )]
+        env: Vec<(String, String)>,
+
+        #[clap(
+            long,
+            value_name = "LIB",
+            help = "Also run each input through the entry point of this dynamic library, a \
+                    reference implementation, and abort if both diverge"
+        )]
+        reference: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "SYMBOL",
+            requires = "reference",
+            help = "Entry point of the reference implementation, LLVMFuzzerTestOneInput by default"
+        )]
+        reference_symbol: Option<String>,
+
+        #[clap(
+            value_name = "ARGS",
+            allow_hyphen_values = true,
+            help = "libFuzzer-style flags (-runs=, -max_len=, -timeout=, -exact_artifact_path=) \
+                    and input path, overriding --input. Other flags are passed to \
+                    LLVMFuzzerInitialize"
+        )]
+        libfuzzer_args: Vec<String>,
+    },
+    Cov {
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./output/queue",
+            help = "Corpus directory to replay (will only be read)"
+        )]
+        input: PathBuf,
+
+        #[clap(
+            long,
+            value_name = "PKGS",
+            help = "Comma-separated Go packages to report, defaults to the harness' dependencies"
+        )]
+        coverpkg: Option<String>,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "text",
+            help = "Report format, lcov prints a tracefile for genhtml and CI coverage services"
+        )]
+        format: Format,
+    },
+    // Check that the linked target exports the libFuzzer interface and is instrumented
+    Doctor,
+    // Measure the throughput of the harness alone, and through the fuzzer's pipeline
+    Bench {
+        #[clap(
+            short,
+            long,
+            value_name = "PATH",
+            default_value = "./input",
+            help = "Input file or small corpus directory run in a loop"
+        )]
+        input: PathBuf,
+
+        #[clap(
+            long,
+            value_name = "N",
+            default_value = "10",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Seconds spent running the inputs in each mode"
+        )]
+        seconds: u64,
+
+        #[clap(
+            long = "timeout-ms",
+            value_name = "MS",
+            default_value = "1000",
+            help = "Execution timeout in milliseconds through the fuzzer's executor"
+        )]
+        timeout: u64,
+    },
+    Cmin {
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./input",
+            help = "Corpus directory to minimize (will only be read)"
+        )]
+        input: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            help = "Directory receiving the minimized corpus"
+        )]
+        output: PathBuf,
+    },
+    Merge {
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "Destination corpus, its inputs are kept and define the initial coverage"
+        )]
+        into: PathBuf,
+
+        #[clap(
+            value_name = "DIRS",
+            required = true,
+            help = "Corpus directories to merge (will only be read)"
+        )]
+        dirs: Vec<PathBuf>,
+    },
+    Tmin {
+        #[clap(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Testcase to minimize (will only be read)"
+        )]
+        input: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Path receiving the minimized testcase"
+        )]
+        output: PathBuf,
+    },
+    Triage {
+        #[clap(
+            short,
+            long,
+            value_name = "OUTPUT",
+            default_value = "./output",
+            help = "Output directory of the fuzzing campaign to triage"
+        )]
+        output: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "N",
+            default_value = "3",
+            help = "Number of times each crash is replayed"
+        )]
+        runs: usize,
+
+        #[clap(
+            short,
+            long = "timeout-ms",
+            value_name = "MS",
+            default_value = "1000",
+            help = "Replay timeout in milliseconds"
+        )]
+        timeout: u64,
+    },
+    ReplayCrashes {
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./output/crashes",
+            help = "Directory of the crashes to replay"
+        )]
+        crashes: PathBuf,
+
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "Where to write the JSON report [default: replay.json next to the crashes dir]"
+        )]
+        report: Option<PathBuf>,
+
+        #[clap(
+            short,
+            long = "timeout-ms",
+            value_name = "MS",
+            default_value = "1000",
+            help = "Replay timeout in milliseconds"
+        )]
+        timeout: u64,
+    },
+    Sarif {
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./output/crashes",
+            help = "Directory of the crashes to report"
+        )]
+        crashes: PathBuf,
+
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "Where to write the SARIF report [default: crashes.sarif next to the crashes \
+                    dir]"
+        )]
+        report: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "Root of the Go sources, e.g. the repository checkout, to report paths relative \
+                    to"
+        )]
+        source_root: Option<PathBuf>,
+
+        #[clap(
+            short,
+            long = "timeout-ms",
+            value_name = "MS",
+            default_value = "1000",
+            help = "Replay timeout in milliseconds"
+        )]
+        timeout: u64,
+    },
+    GoExport {
+        #[clap(
+            short,
+            long,
+            value_name = "OUTPUT",
+            default_value = "./output",
+            help = "Output directory of the fuzzing campaign whose crashes are exported"
+        )]
+        output: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = ".",
+            help = "Go package directory, crashes are written to its testdata/fuzz/<FUZZ_TARGET>"
+        )]
+        package: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "FUZZ_TARGET",
+            help = "Name of the Go fuzz test replaying the crashes, e.g. FuzzParse"
+        )]
+        fuzz_target: String,
+    },
+    ExportGoTests {
+        #[clap(
+            short,
+            long,
+            value_name = "OUTPUT",
+            default_value = "./output",
+            help = "Output directory of the fuzzing campaign whose crashes are exported"
+        )]
+        output: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = ".",
+            help = "Go package directory, the test is written to its golibafl_crashes_test.go"
+        )]
+        package: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "FUNCTION",
+            help = "Function of the package called with each crash as a []byte, e.g. the one the \
+                    harness calls"
+        )]
+        function: String,
+    },
+    Export {
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./output",
+            help = "Output directory of the fuzzing campaign whose queue is exported"
+        )]
+        input: PathBuf,
+
+        #[clap(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Archive to write, compressed according to its extension, e.g. corpus.tar.zst"
+        )]
+        output: PathBuf,
+
+        #[clap(long, help = "Only export the queue entries cmin keeps")]
+        minimize: bool,
+
+        #[clap(
+            long,
+            help = "Also export the LibAFL metadata file of each queue entry"
+        )]
+        metadata: bool,
+    },
+    // Flags given in a campaign file come first, the command line ones override them
+    #[command(args_override_self = true)]
+    Fuzz {
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "TOML campaign file, whose settings are overridden by the command line flags"
+        )]
+        config: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "KEY=VALUE",
+            value_parser = parse_env_var,
+            help = "Set an environment variable of the clients and the target, e.g. GOMAXPROCS=1"
+        )]
+        env: Vec<(String, String)>,
+
+        #[clap(
+            long,
+            help = "Let the Go runtime of each client use all the CPUs, instead of setting \
+                    GOMAXPROCS=1 for clients bound to a core"
+        )]
+        no_gomaxprocs: bool,
+
+        #[clap(
+            short = 'j',
+            long,
+            value_parser = Cores::from_cmdline,
+            help = "Spawn clients in each of the provided cores. Broker runs in the 0th core. 'all' to select all available cores. 'none' to run a client without binding to any core. eg: '1,2-4,6' selects the cores 1,2,3,4,6.",
+            name = "CORES",
+            default_value = "all",
+            )]
+        cores: Cores,
+
+        #[clap(
+            short = 'p',
+            long,
+            help = "Choose the broker TCP port, default is 1337",
+            name = "PORT",
+            default_value = "1337"
+        )]
+        broker_port: u16,
+
+        #[clap(
+            long,
+            value_name = "HOST:PORT",
+            value_parser = parse_broker_addr,
+            help = "Connect the local broker to the broker of another machine, to share the corpus"
+        )]
+        connect_to: Option<SocketAddr>,
+
+        #[clap(
+            long,
+            help = "Only run the broker, for the clients of other machines to connect to"
+        )]
+        broker_only: bool,
+
+        #[clap(
+            long,
+            value_name = "NAME",
+            help = "Name of the events' configuration, for the clients to only trust the coverage \
+                    of the testcases sent by clients of the same target. Defaults to a hash of \
+                    the executable"
+        )]
+        config_name: Option<String>,
+
+        #[clap(
+            short,
+            long,
+            value_name = "DIR",
+            default_value = "./input",
+            help = "Initial corpus directory (will only be read), with its subdirectories. Can be \
+                    repeated, each distinct input being loaded once"
+        )]
+        input: Vec<PathBuf>,
+
+        #[clap(
+            short,
+            long,
+            value_name = "OUTPUT",
+            default_value = "./output",
+            help = "Fuzzer's output directory"
+        )]
+        output: PathBuf,
+
+        #[clap(
+            short,
+            long = "timeout-ms",
+            value_name = "MS",
+            default_value = "1000",
+            help = "Per-input execution timeout in milliseconds"
+        )]
+        timeout: u64,
+
+        #[clap(
+            long = "timeout",
+            value_name = "auto[,xN]",
+            value_parser = AutoTimeout::from_cmdline,
+            help = "Derive the timeout from N times (5 by default) the calibrated average \
+                    execution time, bounded by --timeout-ms"
+        )]
+        auto_timeout: Option<AutoTimeout>,
+
+        #[clap(
+            short = 'x',
+            long = "dict",
+            value_name = "FILE",
+            help = "AFL/libFuzzer-style dictionary file, can be given multiple times"
+        )]
+        dicts: Vec<PathBuf>,
+
+        #[clap(
+            long,
+            help = "Don't store inputs exceeding the timeout in the output's hangs directory"
+        )]
+        no_hangs: bool,
+
+        #[clap(
+            long,
+            help = "Make every client load the whole initial corpus, instead of a shard of it"
+        )]
+        no_corpus_shards: bool,
+
+        #[clap(
+            long,
+            help = "Run the clients from the current directory, instead of their own \
+                    <output>/tmp/<client> directory"
+        )]
+        no_client_workdirs: bool,
+
+        #[clap(
+            long,
+            value_name = "N",
+            help = "Maximum length in bytes of the generated and mutated inputs"
+        )]
+        max_len: Option<NonZeroUsize>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            help = "Skip the inputs shorter than N bytes instead of executing them"
+        )]
+        min_len: Option<usize>,
+
+        #[clap(
+            long,
+            value_name = "BYTES",
+            value_parser = parse_escaped_bytes,
+            help = "Skip the inputs not starting with BYTES, which may hold Go escapes such as \
+                    \\x00, instead of executing them"
+        )]
+        prefix: Option<EscapedBytes>,
+
+        #[clap(
+            long,
+            value_name = "PATH",
+            help = "Skip the inputs the golibafl_filter function of the given dynamic library \
+                    returns 0 for, instead of executing them"
+        )]
+        filter_plugin: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            default_value = "4096",
+            help = "Number of queue entries each client keeps loaded in memory"
+        )]
+        corpus_cache: NonZeroUsize,
+
+        #[clap(
+            long,
+            conflicts_with = "corpus_cache",
+            help = "Keep the whole queue in memory, only writing it to disk"
+        )]
+        in_memory_corpus: bool,
+
+        #[clap(
+            long,
+            help = "Write the queue entries compressed with zstd, as .zst files, for targets with \
+                    large inputs"
+        )]
+        compress_corpus: bool,
+
+        #[clap(
+            long,
+            help = "Continue the previous campaign in the output directory, from its queue and \
+                    the state the clients saved, instead of the input directory"
+        )]
+        resume: bool,
 
-// Command line arguments with clap
-#[derive(Subcommand, Debug, Clone)]
-enum Mode {
-    Run {
-        #[clap(short, long, value_name = "DIR", default_value = "./input")]
-        input: PathBuf,
-    },
-    Fuzz {
         #[clap(
-            short = 'j',
             long,
-            value_parser = Cores::from_cmdline,
-            help = "Spawn clients in each of the provided cores. Broker runs in the 0th core. 'all' to select all available cores. 'none' to run a client without binding to any core. eg: '1,2-4,6' selects the cores 1,2,3,4,6.",
-            name = "CORES",
-            default_value = "all",
-            )]
-        cores: Cores,
+            conflicts_with = "resume",
+            help = "Clear the queue of a previous campaign in the output directory, keeping its \
+                    findings"
+        )]
+        force: bool,
 
         #[clap(
-            short = 'p',
             long,
-            help = "Choose the broker TCP port, default is 1337",
-            name = "PORT",
-            default_value = "1337"
+            value_name = "N",
+            help = "Drop redundant queue entries once a client's queue holds more than N entries"
         )]
-        broker_port: u16,
+        max_corpus_entries: Option<usize>,
+
+        #[clap(
+            long,
+            value_name = "BYTES",
+            help = "Drop redundant queue entries once a client's queue exceeds BYTES bytes"
+        )]
+        max_corpus_bytes: Option<u64>,
+
+        #[clap(
+            long,
+            value_name = "SECS",
+            help = "Stop fuzzing after the given number of seconds"
+        )]
+        max_total_time: Option<u64>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            help = "Stop each client after it executed the target N times"
+        )]
+        max_execs: Option<u64>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            help = "Relaunch each client after it executed the target N times, resetting the Go \
+                    heap and runtime state"
+        )]
+        restart_interval_execs: Option<u64>,
+
+        #[clap(
+            long,
+            help = "Stop all the clients on the first crash and exit with code 77"
+        )]
+        stop_on_crash: bool,
+
+        #[clap(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..),
+            conflicts_with = "stop_on_crash",
+            help = "Stop all the clients once N crashes with distinct signatures were found, \
+                    capturing the target's output to tell them apart"
+        )]
+        keep_going: Option<u32>,
+
+        #[clap(
+            long,
+            help = "Periodically append the clients' stats as NDJSON to the output's stats.ndjson, \
+                    and write the campaign's to its stats.json"
+        )]
+        json_stats: bool,
+
+        #[clap(
+            long,
+            help = "Write AFL++ plot_data files to the output's plot/<client> directories"
+        )]
+        plot_data: bool,
+
+        #[clap(
+            long,
+            help = "Store the target's stdout and stderr next to each crash as .<name>.output"
+        )]
+        capture_output: bool,
+
+        #[clap(
+            long,
+            help = "Store inputs triggering a data race in the output's races dir, for targets \
+                    built with -race (RACE=1 cargo build)"
+        )]
+        races: bool,
+
+        #[clap(
+            long = "crash-pattern",
+            value_name = "REGEX",
+            help = "Save the inputs whose execution prints output matching REGEX as crashes, \
+                    e.g. \"unreachable state\". Can be repeated"
+        )]
+        crash_patterns: Vec<Regex>,
+
+        #[clap(
+            long,
+            value_name = "LIB",
+            help = "Also run each input through the entry point of this dynamic library, a \
+                    reference implementation, saving the inputs on which both diverge as \
+                    crashes"
+        )]
+        reference: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "SYMBOL",
+            requires = "reference",
+            help = "Entry point of the reference implementation, LLVMFuzzerTestOneInput by default"
+        )]
+        reference_symbol: Option<String>,
 
         #[clap(
-            short,
             long,
             value_name = "DIR",
-            default_value = "./input",
-            help = "Initial corpus directory (will only be read)"
+            help = "Write each client's stdout and stderr to DIR/client-<id>.log, rotated at 16 MiB"
         )]
-        input: PathBuf,
+        client_logs: Option<PathBuf>,
 
         #[clap(
-            short,
             long,
-            value_name = "OUTPUT",
-            default_value = "./output",
-            help = "Fuzzer's output directory"
+            conflicts_with = "client_logs",
+            help = "Keep the clients' output on the console, for debugging"
         )]
-        output: PathBuf,
+        foreground: bool,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "fast",
+            help = "Power schedule deciding how much each corpus entry is fuzzed"
+        )]
+        power_schedule: Schedule,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "weighted",
+            help = "Corpus scheduler picking the next entry to fuzz"
+        )]
+        scheduler: Scheduler,
+
+        #[clap(
+            long,
+            help = "Don't favor the smallest and fastest corpus entry reaching each edge"
+        )]
+        no_minimizer: bool,
+
+        #[clap(
+            long,
+            help = "Favor the corpus entries hitting rarely seen edges, with the weighted scheduler"
+        )]
+        rare_edges: bool,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "mopt",
+            help = "Mutations applied to the scheduled inputs"
+        )]
+        mutators: Mutators,
+
+        #[clap(
+            long,
+            value_name = "PATH",
+            help = "Also mutate the scheduled inputs with the golibafl_mutate function of this \
+                    dynamic library"
+        )]
+        mutator_plugin: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            help = "Seed the random number generators, client n using N + n"
+        )]
+        seed: Option<u64>,
+
+        #[clap(
+            long,
+            value_enum,
+            default_value = "routines",
+            help = "Comparisons traced for the input-to-state mutations, off for targets built \
+                    without the comparison hooks"
+        )]
+        cmplog: CmpLog,
+
+        #[clap(
+            long,
+            value_name = "N|P",
+            value_parser = CmpLogRate::from_cmdline,
+            help = "Only trace the comparisons of every Nth scheduled corpus entry, or of each \
+                    one with probability P (e.g. 0.25), instead of all of them"
+        )]
+        cmplog_rate: Option<CmpLogRate>,
+
+        #[clap(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u16).range(1..),
+            conflicts_with = "skip_calibration",
+            help = "Calibrate the new corpus entries with N runs each, without tracking their \
+                    unstable edges"
+        )]
+        calibration_cycles: Option<u16>,
+
+        #[clap(
+            long,
+            help = "Don't run the new corpus entries to calibrate them, e.g. for short campaigns \
+                    on large corpora"
+        )]
+        skip_calibration: bool,
+
+        #[clap(
+            long,
+            value_name = "MB",
+            help = "Store inputs growing the memory usage by more than MB MiB in the output's \
+                    oom dir, checked after each execution"
+        )]
+        rss_limit_mb: Option<u64>,
+
+        #[clap(
+            long,
+            help = "Store inputs leaving file descriptors open in output's fd-leaks dir"
+        )]
+        fd_leaks: bool,
+
+        #[clap(
+            long,
+            value_name = "PERCENT",
+            default_value = "90",
+            value_parser = clap::value_parser!(u8).range(0..=100),
+            help = "Warn when the stability of a client, the share of its edges reached \
+                    deterministically, drops below PERCENT, 0 to never warn"
+        )]
+        stability_threshold: u8,
+
+        #[clap(
+            long,
+            value_name = "URL",
+            help = "POST a JSON payload to this webhook for each crash with a new signature, \
+                    capturing the target's output to tell crashes apart"
+        )]
+        notify_url: Option<String>,
+
+        #[clap(
+            long,
+            conflicts_with = "notify_url",
+            help = "Once initialized, forbid the clients to reach the network and, with Landlock, \
+                    to write outside of the output, sync and client logs directories. Linux only"
+        )]
+        sandbox: bool,
+
+        #[clap(
+            long = "report",
+            value_name = "FORMAT=PATH",
+            value_parser = CampaignReport::from_cmdline,
+            help = "Once the campaign is over, replay its crashes and write a junit=<path> or \
+                    sarif=<path> report. Can be repeated"
+        )]
+        reports: Vec<CampaignReport>,
+
+        #[clap(
+            long,
+            help = "Keep inputs making progress on the target's comparisons, like libFuzzer's \
+                    -use_value_profile=1"
+        )]
+        value_profile: bool,
+
+        #[clap(
+            long,
+            help = "Keep inputs reaching new coverage of the cgo code built with \
+                    -fsanitize-coverage=trace-pc-guard, n-gram or context-sensitive with the \
+                    ngram or ctx feature"
+        )]
+        pcguard_coverage: bool,
+
+        #[clap(
+            long,
+            help = "Run each input in a forked child, isolating the executions at the cost of \
+                    throughput. Disables comparison tracing, value profiling and --rss-limit-mb"
+        )]
+        forking: bool,
+
+        #[clap(
+            long,
+            value_name = "K",
+            value_parser = clap::value_parser!(u16).range(2..),
+            help = "Execute the mutants in batches of K with a single observers sweep, for very \
+                    fast targets. Uses havoc and token mutations, whatever --mutators"
+        )]
+        batch_size: Option<u16>,
+
+        #[clap(
+            long,
+            help = "Ignore the edges of the Go runtime packages (runtime, internal/..., sync), \
+                    which vary with garbage collection and scheduling"
+        )]
+        mask_runtime_edges: bool,
+
+        #[clap(
+            long,
+            value_name = "PKG",
+            help = "Only reward the edges of this Go package, e.g. github.com/me/lib/... to \
+                    include its subpackages. Can be repeated"
+        )]
+        focus: Vec<String>,
+
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "File of the Go functions changed by a patch, one symbol name per line: only \
+                    reward the edges of the packages defining them, coverage being attributed to \
+                    packages rather than functions"
+        )]
+        focus_changed_packages: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "AFL++ sync directory to exchange queue entries with other fuzzer instances"
+        )]
+        sync_dir: Option<PathBuf>,
+
+        #[clap(
+            long,
+            value_name = "ID",
+            default_value = "golibafl",
+            help = "Prefix of the clients' fuzzer ids in the sync directory"
+        )]
+        sync_id: String,
+
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "Directory polled every minute for new seeds, imported if they add coverage. \
+                    Can be repeated"
+        )]
+        sync_in: Vec<PathBuf>,
+
+        #[clap(
+            long,
+            help = "Also poll the input directories for new seeds during the campaign"
+        )]
+        watch_input: bool,
+
+        #[cfg(feature = "prometheus")]
+        #[clap(
+            long,
+            value_name = "ADDR",
+            help = "Expose Prometheus metrics on the given address, e.g. 0.0.0.0:8080"
+        )]
+        prometheus: Option<String>,
+
+        #[cfg(feature = "http")]
+        #[clap(
+            long,
+            value_name = "ADDR",
+            help = "Serve the campaign's stats and crashes, and stop and pause requests, over HTTP \
+                    on the given address, e.g. 127.0.0.1:8081"
+        )]
+        http: Option<String>,
+
+        #[cfg(feature = "cloud")]
+        #[clap(
+            long,
+            value_name = "URL",
+            conflicts_with = "sandbox",
+            help = "Upload the queue and the crashes every 5 minutes to s3://<bucket>/<prefix> or \
+                    gs://<bucket>/<prefix>"
+        )]
+        cloud_bucket: Option<String>,
+
+        #[cfg(feature = "cloud")]
+        #[clap(
+            long,
+            requires = "cloud_bucket",
+            help = "Seed the campaign with the queue uploaded to the cloud bucket"
+        )]
+        cloud_seed: bool,
+
+        #[cfg(feature = "grammar")]
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "Generate and mutate the inputs from the given Nautilus JSON grammar"
+        )]
+        grammar: Option<PathBuf>,
+
+        #[cfg(feature = "scripting")]
+        #[clap(
+            long,
+            value_name = "FILE",
+            help = "Declare an input a solution when the `is_objective` function of the given Rhai \
+                    script returns true for its execution"
+        )]
+        objective_script: Option<PathBuf>,
     },
 }
 // Clap top level struct for args
@@ -93,230 +1095,434 @@ struct Cli {
     mode: Mode,
 }
 
-// Run the corpus without fuzzing
-fn run(input: PathBuf) {
-    let files = if input.is_dir() {
-        input
-            .read_dir()
-            .expect("Unable to read dir")
-            .filter_map(core::result::Result::ok)
-            .map(|e| e.path())
-            .collect()
-    } else {
-        vec![input]
-    };
+// Campaign file of the `fuzz` mode
+struct CampaignFile {
+    // Flags set by the file, to insert before the command line ones
+    args: Vec<String>,
+    clients: BTreeMap<usize, ClientOverrides>,
+}
 
-    // Call LLVMFuzzerInitialize() if present.
-    let args: Vec<String> = env::args().collect();
-    if unsafe { libfuzzer_initialize(&args) } == -1 {
-        println!("Warning: LLVMFuzzerInitialize failed with -1");
+// Path of the campaign file given with `--config`, looked up before parsing the command line
+fn config_path(args: &[String]) -> Option<PathBuf> {
+    args.iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix("--config")? {
+            "" => args.get(i + 1).map(PathBuf::from),
+            path => path.strip_prefix('=').map(PathBuf::from),
+        })
+}
+
+// Command line form of a scalar TOML value
+fn toml_arg(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(n) => Ok(n.to_string()),
+        toml::Value::Float(f) => Ok(f.to_string()),
+        value => Err(format!("unsupported value {value}")),
     }
+}
 
-    for f in &files {
-        println!("\x1b[33mRunning: {}\x1b[0m", f.display());
-        let inp =
-            std::fs::read(f).unwrap_or_else(|_| panic!("Unable to read file {}", &f.display()));
-        if inp.len() > 1 {
-            println!("INPUT: {inp:?}");
-            unsafe {
-                libfuzzer_test_one_input(&inp);
+// Settings of a `[clients.<id>]` table
+fn client_overrides(table: &toml::Table) -> Result<ClientOverrides, String> {
+    let mut overrides = ClientOverrides::default();
+    for (key, value) in table {
+        let value = toml_arg(value).map_err(|err| format!("{key}: {err}"))?;
+        let value = value.as_str();
+        match key.as_str() {
+            "mutators" => overrides.mutators = Some(Mutators::from_str(value, true)?.into()),
+            "power_schedule" => {
+                overrides.power_schedule = Some(Schedule::from_str(value, true)?.into());
             }
+            "scheduler" => overrides.scheduler = Some(Scheduler::from_str(value, true)?.into()),
+            "cmplog" => overrides.cmplog = Some(CmpLog::from_str(value, true)?.into()),
+            "cmplog_rate" => overrides.cmplog_rate = Some(CmpLogRate::from_cmdline(value)?),
+            _ => return Err(format!("{key} can't be set per client")),
         }
     }
+    Ok(overrides)
 }
 
-// Fuzzing function, wrapping the exported libfuzzer functions from golang
-#[allow(clippy::too_many_lines)]
-#[allow(static_mut_refs)]
-fn fuzz(cores: &Cores, broker_port: u16, input: &PathBuf, output: &Path) {
-    let args: Vec<String> = env::args().collect();
-    if unsafe { libfuzzer_initialize(&args) } == -1 {
-        println!("Warning: LLVMFuzzerInitialize failed with -1");
-    }
-    let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
-    let monitor = SimpleMonitor::new(|s| println!("{s}"));
-
-    let mut run_client = |state: Option<_>,
-                          mut restarting_mgr,
-                          client_description: ClientDescription| {
-        // We assume COUNTERS_MAP len == 1  so that we can use StdMapObserver instead of Multimapobserver to improve performance.
-        let counters_map_len = unsafe { COUNTERS_MAPS.len() };
-        assert!(
-            (counters_map_len == 1),
-            "{}",
-            format!("Unexpected COUNTERS_MAPS length: {counters_map_len}")
-        );
-        let edges = unsafe { extra_counters() };
-        let edges_observer =
-            StdMapObserver::from_mut_slice("edges", edges.into_iter().next().unwrap())
-                .track_indices();
-
-        // Observers
-        let time_observer = TimeObserver::new("time");
-        let cmplog_observer = CmpLogObserver::new("cmplog", true);
-        let map_feedback = MaxMapFeedback::new(&edges_observer);
-        let calibration = CalibrationStage::new(&map_feedback);
-
-        let mut feedback = feedback_or_fast!(
-            // New maximization map feedback linked to the edges observer and the feedback state
-            map_feedback,
-            // Time feedback, this one does not need a feedback state
-            TimeFeedback::new(&time_observer)
-        );
-
-        // A feedback to choose if an input is a solution or not
-        let mut objective = feedback_or_fast!(CrashFeedback::new());
-
-        // create a State from scratch
-        let mut state = state.unwrap_or_else(|| {
-            StdState::new(
-                StdRand::new(),
-                // Corpus that will be evolved
-                CachedOnDiskCorpus::new(
-                    format!("{}/queue/{}", output.display(), client_description.id()),
-                    4096,
-                )
-                .unwrap(),
-                // Corpus in which we store solutions
-                OnDiskCorpus::new(format!("{}/crashes", output.display())).unwrap(),
-                &mut feedback,
-                &mut objective,
-            )
-            .unwrap()
-        });
-
-        // Setup a randomic Input2State stage
-        let i2s =
-            StdMutationalStage::new(StdScheduledMutator::new(tuple_list!(I2SRandReplace::new())));
-
-        // Setup a MOPT mutator
-        let mutator = StdMOptMutator::new(
-            &mut state,
-            havoc_mutations().merge(tokens_mutations()),
-            7,
-            5,
-        )?;
-
-        let power: StdPowerMutationalStage<_, _, BytesInput, _, _, _> =
-            StdPowerMutationalStage::new(mutator);
-
-        let scheduler = IndexesLenTimeMinimizerScheduler::new(
-            &edges_observer,
-            StdWeightedScheduler::with_schedule(
-                &mut state,
-                &edges_observer,
-                Some(PowerSchedule::fast()),
-            ),
-        );
-
-        // A fuzzer with feedbacks and a corpus scheduler
-        let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective);
-
-        // The closure that we want to fuzz
-        let mut harness = |input: &BytesInput| {
-            let target = input.target_bytes();
-            unsafe {
-                libfuzzer_test_one_input(&target);
-            }
-            ExitKind::Ok
-        };
-
-        let executor = InProcessExecutor::with_timeout(
-            &mut harness,
-            tuple_list!(edges_observer, time_observer),
-            &mut fuzzer,
-            &mut state,
-            &mut restarting_mgr,
-            Duration::new(1, 0),
-        )?;
-
-        let mut executor = ShadowExecutor::new(executor, tuple_list!(cmplog_observer));
-
-        // Setup a tracing stage in which we log comparisons
-        let tracing = ShadowTracingStage::new();
+// Parse a campaign file. Top-level keys are the flags of the `fuzz` mode, by field or long name
+// (e.g. `cores = "0-7"`, `timeout = 500`, `dict = ["a.dict", "b.dict"]`), `[env]` sets
+// environment variables, and `[clients.<id>]` overrides the mutators, schedules and comparison
+// tracing of a client.
+fn load_campaign(path: &Path) -> Result<CampaignFile, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut table: toml::Table = content
+        .parse()
+        .map_err(|err: toml::de::Error| err.to_string())?;
 
-        let mut stages = tuple_list!(calibration, tracing, i2s, power);
-
-        if state.metadata_map().get::<Tokens>().is_none() {
-            let mut toks = Tokens::default();
-            toks += autotokens()?;
-
-            if !toks.is_empty() {
-                state.add_metadata(toks);
+    // Passed as `--env`, so that the command line ones override them
+    let mut args = Vec::new();
+    match table.remove("env") {
+        None => {}
+        Some(toml::Value::Table(env)) => {
+            for (key, value) in &env {
+                args.push("--env".to_string());
+                args.push(format!("{key}={}", toml_arg(value)?));
             }
         }
+        Some(_) => return Err("env must be a table".into()),
+    }
+
+    let clients = match table.remove("clients") {
+        None => BTreeMap::new(),
+        Some(toml::Value::Table(clients)) => clients
+            .iter()
+            .map(|(id, overrides)| {
+                let id = id.parse().map_err(|_| format!("invalid client id {id}"))?;
+                let overrides = overrides
+                    .as_table()
+                    .ok_or_else(|| format!("clients.{id} must be a table"))?;
+                Ok((id, client_overrides(overrides)?))
+            })
+            .collect::<Result<_, String>>()?,
+        Some(_) => return Err("clients must be a table".into()),
+    };
 
-        // Load corpus from input folder
-        // In case the corpus is empty (on first run), reset
-        if state.must_load_initial_inputs() {
-            if read_dir(input).iter().len() == 0 {
-                // Generator of printable bytearrays of max size 32
-                let mut generator = RandBytesGenerator::new(nonzero!(32));
-
-                // Generate 8 initial inputs
-                state
-                    .generate_initial_inputs(
-                        &mut fuzzer,
-                        &mut executor,
-                        &mut generator,
-                        &mut restarting_mgr,
-                        8,
-                    )
-                    .expect("Failed to generate the initial corpus");
-                println!(
-                    "We imported {} inputs from the generator.",
-                    state.corpus().count()
-                );
-            } else {
-                println!("Loading from {:?}", input);
-                // Load from disk
-                state
-                    .load_initial_inputs(
-                        &mut fuzzer,
-                        &mut executor,
-                        &mut restarting_mgr,
-                        &[input.to_path_buf()],
-                    )
-                    .unwrap_or_else(|_| {
-                        panic!("Failed to load initial corpus at {:?}", input);
-                    });
-                println!("We imported {} inputs from disk.", state.corpus().count());
+    let command = Cli::command();
+    let fuzz = command.find_subcommand("fuzz").unwrap();
+    for (key, value) in &table {
+        let long = fuzz
+            .get_arguments()
+            .find(|arg| {
+                arg.get_id().as_str().eq_ignore_ascii_case(key)
+                    || arg
+                        .get_long()
+                        .is_some_and(|long| long.replace('-', "_") == *key)
+            })
+            .and_then(|arg| arg.get_long())
+            .ok_or_else(|| format!("unknown option {key}"))?;
+        let flag = format!("--{long}");
+        match value {
+            toml::Value::Boolean(true) => args.push(flag),
+            toml::Value::Boolean(false) => {}
+            toml::Value::Array(values) => {
+                for value in values {
+                    args.push(flag.clone());
+                    args.push(toml_arg(value)?);
+                }
+            }
+            value => {
+                args.push(flag);
+                args.push(toml_arg(value)?);
             }
         }
-
-        fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut restarting_mgr)?;
-        Ok(())
-    };
-    match Launcher::builder()
-        .shmem_provider(shmem_provider)
-        .configuration(EventConfig::from_name("default"))
-        .monitor(monitor)
-        .run_client(&mut run_client)
-        .cores(cores)
-        .broker_port(broker_port)
-        .stdout_file(Some("/dev/null")) // Comment this out for debugging
-        .build()
-        .launch()
-    {
-        Ok(()) => (),
-        Err(Error::ShuttingDown) => println!("Fuzzing stopped by user. Good bye."),
-        Err(err) => panic!("Failed to run launcher: {err:?}"),
     }
+
+    Ok(CampaignFile { args, clients })
 }
 
 // Entry point wrapping clap and calling fuzz or run
 pub fn main() {
-    let cli = Cli::parse();
+    let mut args: Vec<String> = env::args().collect();
+    let campaign = config_path(&args).map(|path| {
+        load_campaign(&path).unwrap_or_else(|err| {
+            eprintln!("Invalid campaign file {}: {err}", path.display());
+            process::exit(1);
+        })
+    });
+    let mut client_overrides = BTreeMap::new();
+    if let Some(campaign) = campaign {
+        if let Some(fuzz) = args.iter().position(|arg| arg == "fuzz") {
+            let cli_args = args.split_off(fuzz + 1);
+            args.extend(campaign.args);
+            args.extend(cli_args);
+        }
+        client_overrides = campaign.clients;
+    }
+    let cli = Cli::parse_from(args);
 
     match cli.mode {
         Mode::Fuzz {
+            config: _,
+            mut env,
+            no_gomaxprocs,
             cores,
             broker_port,
+            connect_to,
+            broker_only,
+            config_name,
             input,
             output,
-        } => fuzz(&cores, broker_port, &input, &output),
-        Mode::Run { input } => {
-            run(input);
+            timeout,
+            auto_timeout,
+            dicts,
+            no_hangs,
+            no_corpus_shards,
+            no_client_workdirs,
+            max_len,
+            min_len,
+            prefix,
+            filter_plugin,
+            corpus_cache,
+            in_memory_corpus,
+            compress_corpus,
+            resume,
+            force,
+            max_corpus_entries,
+            max_corpus_bytes,
+            max_total_time,
+            max_execs,
+            restart_interval_execs,
+            stop_on_crash,
+            keep_going,
+            json_stats,
+            plot_data,
+            capture_output,
+            races,
+            crash_patterns,
+            reference,
+            reference_symbol,
+            client_logs,
+            foreground,
+            power_schedule,
+            scheduler,
+            no_minimizer,
+            rare_edges,
+            mutators,
+            mutator_plugin,
+            seed,
+            cmplog,
+            cmplog_rate,
+            calibration_cycles,
+            skip_calibration,
+            rss_limit_mb,
+            fd_leaks,
+            stability_threshold,
+            notify_url,
+            sandbox,
+            reports,
+            value_profile,
+            pcguard_coverage,
+            forking,
+            batch_size,
+            mask_runtime_edges,
+            focus,
+            focus_changed_packages,
+            sync_dir,
+            sync_id,
+            sync_in,
+            watch_input,
+            #[cfg(feature = "prometheus")]
+            prometheus,
+            #[cfg(feature = "http")]
+            http,
+            #[cfg(feature = "cloud")]
+            cloud_bucket,
+            #[cfg(feature = "cloud")]
+            cloud_seed,
+            #[cfg(feature = "grammar")]
+            grammar,
+            #[cfg(feature = "scripting")]
+            objective_script,
+        } => {
+            // Each client is bound to a single core, while the Go runtime otherwise runs as many
+            // threads as there are CPUs in every client
+            let bound = !broker_only && !cores.ids.is_empty();
+            if bound
+                && !no_gomaxprocs
+                && env::var_os("GOMAXPROCS").is_none()
+                && !env.iter().any(|(key, _)| key == "GOMAXPROCS")
+            {
+                env.push(("GOMAXPROCS".into(), "1".into()));
+            }
+            apply_env(&env);
+            let builder = GoLibAfl::builder()
+                .env(env)
+                .client_overrides(client_overrides)
+                .cores(cores)
+                .broker_port(broker_port)
+                .remote_broker(connect_to)
+                .broker_only(broker_only)
+                .config_name(config_name)
+                .inputs(input)
+                .output(output)
+                .timeout(Duration::from_millis(timeout))
+                .auto_timeout(auto_timeout)
+                .dicts(dicts)
+                .hangs(!no_hangs)
+                .corpus_shards(!no_corpus_shards)
+                .client_workdirs(!no_client_workdirs)
+                .max_len(max_len)
+                .input_filter(min_len, prefix.map(|prefix| prefix.0), filter_plugin)
+                .corpus_cache(corpus_cache)
+                .in_memory_corpus(in_memory_corpus)
+                .compress_corpus(compress_corpus)
+                .resume(resume)
+                .force(force)
+                .max_corpus_entries(max_corpus_entries)
+                .max_corpus_bytes(max_corpus_bytes)
+                .max_total_time(max_total_time.map(Duration::from_secs))
+                .max_execs(max_execs)
+                .restart_interval_execs(restart_interval_execs)
+                .stop_on_crash(stop_on_crash)
+                .keep_going(keep_going.map(|n| n as usize))
+                .json_stats(json_stats)
+                .plot_data(plot_data)
+                .capture_output(capture_output)
+                .races(races)
+                .crash_patterns(crash_patterns)
+                .reference(reference, reference_symbol)
+                .client_logs(client_logs)
+                .foreground(foreground)
+                .power_schedule(power_schedule.into())
+                .scheduler(scheduler.into())
+                .minimizer(!no_minimizer)
+                .rare_edges(rare_edges)
+                .mutators(mutators.into())
+                .mutator_plugin(mutator_plugin)
+                .seed(seed)
+                .cmplog(cmplog.into())
+                .cmplog_rate(cmplog_rate)
+                .calibration_cycles(if skip_calibration {
+                    Some(0)
+                } else {
+                    calibration_cycles.map(usize::from)
+                })
+                .rss_limit_mb(rss_limit_mb)
+                .fd_leaks(fd_leaks)
+                .stability_threshold(stability_threshold)
+                .notify_url(notify_url)
+                .sandbox(sandbox)
+                .reports(reports)
+                .value_profile(value_profile)
+                .pcguard_coverage(pcguard_coverage)
+                .forking(forking)
+                .batch_size(batch_size.map(usize::from))
+                .mask_runtime_edges(mask_runtime_edges)
+                .focus(focus)
+                .focus_changed_packages(focus_changed_packages)
+                .sync_dir(sync_dir)
+                .sync_id(sync_id)
+                .sync_in(sync_in)
+                .watch_input(watch_input);
+            #[cfg(feature = "prometheus")]
+            let builder = builder.prometheus(prometheus);
+            #[cfg(feature = "http")]
+            let builder = builder.http(http);
+            #[cfg(feature = "cloud")]
+            let builder = builder.cloud_bucket(cloud_bucket).cloud_seed(cloud_seed);
+            #[cfg(feature = "grammar")]
+            let builder = builder.grammar(grammar);
+            #[cfg(feature = "scripting")]
+            let builder = builder.objective_script(objective_script);
+            let crash = builder.build().fuzz();
+            if let Some(crash) = crash {
+                println!("Crash found: {}", crash.display());
+                process::exit(CRASH_EXIT_CODE);
+            }
+        }
+        Mode::Run {
+            input,
+            timeout,
+            coverage,
+            env,
+            reference,
+            reference_symbol,
+            libfuzzer_args,
+        } => {
+            apply_env(&env);
+            let (mut run_options, inputs) = RunOptions::from_libfuzzer_flags(&libfuzzer_args);
+            if let Some(timeout) = timeout {
+                run_options.timeout = Some(Duration::from_millis(timeout));
+            }
+            run_options.coverage = coverage;
+            run_options.reference = reference;
+            run_options.reference_symbol = reference_symbol;
+            let input = match inputs.as_slice() {
+                [] => input,
+                [path] => path.clone(),
+                _ => {
+                    eprintln!("Only a single input file or directory can be run at once");
+                    process::exit(1);
+                }
+            };
+            GoLibAfl::builder()
+                .input(input)
+                .run_options(run_options)
+                .build()
+                .run();
         }
+        Mode::Cov {
+            input,
+            coverpkg,
+            format,
+        } => GoLibAfl::builder()
+            .input(input)
+            .build()
+            .cov(coverpkg.as_deref(), format.into()),
+        Mode::Doctor => GoLibAfl::builder().build().doctor(),
+        Mode::Bench {
+            input,
+            seconds,
+            timeout,
+        } => GoLibAfl::builder()
+            .input(input)
+            .timeout(Duration::from_millis(timeout))
+            .build()
+            .bench(Duration::from_secs(seconds)),
+        Mode::Cmin { input, output } => GoLibAfl::builder()
+            .input(input)
+            .output(output)
+            .build()
+            .cmin(),
+        Mode::Merge { into, dirs } => GoLibAfl::builder().output(into).build().merge(&dirs),
+        Mode::Tmin { input, output } => GoLibAfl::builder()
+            .input(input)
+            .output(output)
+            .build()
+            .tmin(),
+        Mode::GoExport {
+            output,
+            package,
+            fuzz_target,
+        } => GoLibAfl::builder()
+            .output(output)
+            .build()
+            .export_go_corpus(&package, &fuzz_target),
+        Mode::ExportGoTests {
+            output,
+            package,
+            function,
+        } => GoLibAfl::builder()
+            .output(output)
+            .build()
+            .export_go_tests(&package, &function),
+        Mode::Export {
+            input,
+            output,
+            minimize,
+            metadata,
+        } => GoLibAfl::builder()
+            .output(input)
+            .build()
+            .export_queue(&output, minimize, metadata),
+        Mode::Triage {
+            output,
+            runs,
+            timeout,
+        } => GoLibAfl::builder()
+            .output(output)
+            .timeout(Duration::from_millis(timeout))
+            .build()
+            .triage(runs),
+        Mode::ReplayCrashes {
+            crashes,
+            report,
+            timeout,
+        } => GoLibAfl::builder()
+            .timeout(Duration::from_millis(timeout))
+            .build()
+            .replay_crashes(&crashes, report.as_deref()),
+        Mode::Sarif {
+            crashes,
+            report,
+            source_root,
+            timeout,
+        } => GoLibAfl::builder()
+            .timeout(Duration::from_millis(timeout))
+            .build()
+            .export_sarif(&crashes, report.as_deref(), source_root.as_deref()),
     }
 }
@@ -0,0 +1,253 @@
+use crate::{
+    output::{pin_filename, OutputObserver},
+    triage::{invariant_signature_id, signature_id, ObjectiveKind},
+};
+use libafl::{
+    corpus::Testcase,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::{HasTargetBytes, Input},
+    observers::{MapObserver, StdMapObserver, TimeObserver},
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    ffi::OsString,
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    io::ErrorKind,
+    os::unix::fs::symlink,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// Interval between two counts of the unique crashes
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// Attached to the solutions which broke an invariant instead of crashing, with the reason given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InvariantMetadata {
+    pub(crate) reason: String,
+}
+
+libafl_bolts::impl_serdeany!(InvariantMetadata);
+
+// Link the solution `name` in the `<kind>` subdirectory of `dir`. The link is relative and made
+// before the corpus stores the solution, so that it works once stored and if `dir` is moved.
+fn link_by_kind(dir: &Path, kind: ObjectiveKind, name: &str) -> Result<(), Error> {
+    let kind_dir = dir.join(kind.name());
+    create_dir_all(&kind_dir)?;
+    match symlink(Path::new("..").join(name), kind_dir.join(name)) {
+        Err(err) if err.kind() != ErrorKind::AlreadyExists => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+// Objective feedback writing a `.<name>.meta.json` file next to each solution, describing how it
+// was found: input SHA-256, client, timestamp, execution time, edges hit, kind of crash, panic
+// message, broken invariant and the arguments of `run` replaying it. Solutions are also linked in
+// a subdirectory per kind of crash. It only acts in `append_metadata`, once another objective made
+// the input a solution.
+pub(crate) struct CrashMetaFeedback<C> {
+    // Read rather than the counters maps, which the harness already moved into the observed map
+    edges_handle: Handle<C>,
+    time_handle: Handle<TimeObserver>,
+    output_handle: Handle<OutputObserver>,
+    client: usize,
+    dir: PathBuf,
+    // Other arguments of `run` reproducing the campaign's setup, which the replays pass along
+    run_args: Vec<String>,
+}
+
+impl<C> CrashMetaFeedback<C> {
+    // `dir` must be the directory of the solutions corpus
+    pub(crate) fn new(
+        edges_handle: Handle<C>,
+        time_observer: &TimeObserver,
+        output_observer: &OutputObserver,
+        client: usize,
+        dir: PathBuf,
+        run_args: Vec<String>,
+    ) -> Self {
+        Self {
+            edges_handle,
+            time_handle: time_observer.handle(),
+            output_handle: output_observer.handle(),
+            client,
+            dir,
+            run_args,
+        }
+    }
+}
+
+// Go panic message, from the `panic: ` line to the first blank line preceding the stack trace
+fn panic_message(output: &[u8]) -> Option<String> {
+    let output = String::from_utf8_lossy(output);
+    let start = output.find("panic: ")?;
+    let message = &output[start..];
+    let end = message.find("\n\n").unwrap_or(message.len());
+    Some(message[..end].trim_end().to_string())
+}
+
+impl<C> Named for CrashMetaFeedback<C> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("CrashMetaFeedback");
+        &NAME
+    }
+}
+
+impl<C, S> StateInitializer<S> for CrashMetaFeedback<C> {}
+
+impl<C, EM, I, OT, S> Feedback<EM, I, OT, S> for CrashMetaFeedback<C>
+where
+    C: AsRef<StdMapObserver<'static, u8, false>>,
+    I: Input + HasTargetBytes,
+    OT: MatchNameRef,
+{
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let name = pin_filename(testcase);
+        let sha256 = Sha256::digest(&*testcase.input().as_ref().unwrap().target_bytes());
+        let exec_time = observers
+            .get(&self.time_handle)
+            .and_then(|observer| *observer.last_runtime());
+        let edges = observers
+            .get(&self.edges_handle)
+            .ok_or_else(|| Error::key_not_found("Edges observer not found"))?
+            .as_ref()
+            .count_bytes();
+        let raw_output = observers
+            .get(&self.output_handle)
+            .filter(|observer| observer.is_capturing())
+            .map(OutputObserver::output);
+        let panic = raw_output.and_then(panic_message);
+        let output = raw_output.map(String::from_utf8_lossy);
+        let invariant = testcase
+            .metadata::<InvariantMetadata>()
+            .ok()
+            .map(|metadata| metadata.reason.clone());
+        // Without the output, crashes can't be told apart
+        let kind = if invariant.is_some() {
+            Some(ObjectiveKind::Invariant)
+        } else {
+            output.as_deref().map(ObjectiveKind::from_output)
+        };
+        if let Some(kind) = kind {
+            link_by_kind(&self.dir, kind, &name)?;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        // Same as the one `replay-crashes` groups the crashes by, both being computed from the
+        // output alone
+        let signature = match &invariant {
+            Some(reason) => Some(invariant_signature_id(reason)),
+            None => output.as_deref().map(signature_id),
+        };
+        let meta = json!({
+            "sha256": sha256.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+            "client": self.client,
+            "timestamp": timestamp,
+            "exec_time_us": exec_time.map(|t| t.as_micros()),
+            "edges": edges,
+            "kind": kind.map(ObjectiveKind::name),
+            "signature": signature,
+            "panic": panic,
+            "invariant": invariant,
+            "run_args": self.run_args,
+        });
+        write(
+            self.dir.join(format!(".{name}.meta.json")),
+            meta.to_string(),
+        )?;
+        Ok(())
+    }
+}
+
+// Counts the distinct signatures of the crashes of all the clients, from their metadata files,
+// to stop the campaign once `budget` of them were found. Crashes without a signature, found
+// without the output of the target, each count as unique.
+pub(crate) struct UniqueCrashBudget {
+    dir: PathBuf,
+    budget: usize,
+    // Metadata files already counted
+    counted: HashSet<OsString>,
+    signatures: HashSet<String>,
+    unsigned: usize,
+    last: Option<Instant>,
+}
+
+impl UniqueCrashBudget {
+    // `dir` must be the directory of the solutions corpus, the `stale` crashes in it aren't counted
+    pub(crate) fn new(dir: PathBuf, budget: usize, stale: &HashSet<OsString>) -> Self {
+        Self {
+            dir,
+            budget,
+            counted: stale
+                .iter()
+                .map(|name| format!(".{}.meta.json", name.to_string_lossy()).into())
+                .collect(),
+            signatures: HashSet::new(),
+            unsigned: 0,
+            last: None,
+        }
+    }
+
+    pub(crate) fn unique(&self) -> usize {
+        self.signatures.len() + self.unsigned
+    }
+
+    // Whether enough unique crashes were found, counting the new ones every few seconds
+    pub(crate) fn is_exhausted(&mut self) -> bool {
+        if self
+            .last
+            .is_none_or(|last| last.elapsed() >= BUDGET_CHECK_INTERVAL)
+        {
+            self.last = Some(Instant::now());
+            self.count();
+        }
+        self.unique() >= self.budget
+    }
+
+    fn count(&mut self) {
+        for entry in read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            let name = entry.file_name();
+            let is_meta = name
+                .to_str()
+                .is_some_and(|name| name.starts_with('.') && name.ends_with(".meta.json"));
+            if !is_meta || self.counted.contains(&name) {
+                continue;
+            }
+            // Possibly still being written
+            let Some(meta) = read_to_string(entry.path())
+                .ok()
+                .and_then(|meta| serde_json::from_str::<Value>(&meta).ok())
+            else {
+                continue;
+            };
+            match meta["signature"].as_str() {
+                Some(signature) => {
+                    self.signatures.insert(signature.to_string());
+                }
+                None => self.unsigned += 1,
+            }
+            self.counted.insert(name);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    edges::{edges_observer, trace},
+    gocorpus::read_testcase,
+    run::{collect_inputs, initialize},
+};
+use libafl::{
+    corpus::InMemoryCorpus,
+    events::SimpleEventManager,
+    executors::{inprocess_fork::InProcessForkExecutor, Executor, ExitKind},
+    feedbacks::CrashFeedback,
+    fuzzer::StdFuzzer,
+    inputs::{BytesInput, HasTargetBytes},
+    monitors::SimpleMonitor,
+    schedulers::QueueScheduler,
+    state::StdState,
+    Error,
+};
+use libafl_bolts::{
+    rands::StdRand,
+    shmem::{ShMemProvider, StdShMemProvider},
+};
+use libafl_targets::libfuzzer::libfuzzer_test_one_input;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{copy, create_dir_all},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Distill a corpus: keep, for every (edge, hitcount bucket) tuple, the smallest input reaching it
+pub(crate) fn cmin(input: &Path, output: &Path) {
+    let files = collect_inputs(input);
+    let (kept, tuples) = select_minimal(&files, read_testcase);
+    create_dir_all(output)
+        .unwrap_or_else(|_| panic!("Unable to create output dir {}", output.display()));
+    for f in &kept {
+        copy(f, output.join(f.file_name().unwrap()))
+            .unwrap_or_else(|_| panic!("Unable to copy file {}", f.display()));
+    }
+    println!(
+        "Kept {} out of {} inputs covering {} edge tuples in {}",
+        kept.len(),
+        files.len(),
+        tuples,
+        output.display()
+    );
+}
+
+// Select the smallest of `files` reaching each (edge, hitcount bucket) tuple, reading them with
+// `read`. Returns the kept files, in the order of `files`, and the number of tuples they cover.
+pub(crate) fn select_minimal(
+    files: &[PathBuf],
+    read: fn(&Path) -> Result<Vec<u8>, Error>,
+) -> (Vec<&PathBuf>, usize) {
+    initialize();
+    let mut edges_observer = edges_observer();
+
+    // (edge, bucket) -> (input size, index in `files`)
+    let mut best: HashMap<(usize, u8), (usize, usize)> = HashMap::new();
+    for (i, f) in files.iter().enumerate() {
+        let inp = read(f).unwrap_or_else(|_| panic!("Unable to read file {}", &f.display()));
+        for (edge, bucket) in trace(&mut edges_observer, &inp).into_iter().enumerate() {
+            if bucket == 0 {
+                continue;
+            }
+            let entry = best.entry((edge, bucket)).or_insert((inp.len(), i));
+            if inp.len() < entry.0 {
+                *entry = (inp.len(), i);
+            }
+        }
+    }
+
+    let kept: HashSet<usize> = best.values().map(|&(_, i)| i).collect();
+    let kept = files
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| kept.contains(i))
+        .map(|(_, f)| f)
+        .collect();
+    (kept, best.len())
+}
+
+// Merge corpora into `into`: inputs already there seed the coverage, then the candidates of
+// `dirs` are replayed from the smallest to the largest, and copied over only if they reach a new
+// (edge, hitcount bucket) tuple. Ordering by size then path keeps the result deterministic.
+pub(crate) fn merge(into: &Path, dirs: &[PathBuf]) {
+    create_dir_all(into)
+        .unwrap_or_else(|_| panic!("Unable to create output dir {}", into.display()));
+    initialize();
+    let mut edges_observer = edges_observer();
+
+    let mut covered: HashSet<(usize, u8)> = HashSet::new();
+    let mut cover = |data: &[u8], covered: &mut HashSet<(usize, u8)>| {
+        let mut new = false;
+        for (edge, bucket) in trace(&mut edges_observer, data).into_iter().enumerate() {
+            if bucket != 0 {
+                new |= covered.insert((edge, bucket));
+            }
+        }
+        new
+    };
+
+    let existing = collect_inputs(into);
+    for f in &existing {
+        let inp =
+            read_testcase(f).unwrap_or_else(|_| panic!("Unable to read file {}", f.display()));
+        cover(&inp, &mut covered);
+    }
+    let initial = covered.len();
+
+    let mut candidates: Vec<(Vec<u8>, PathBuf)> = dirs
+        .iter()
+        .flat_map(|dir| collect_inputs(dir))
+        .map(|f| {
+            let inp =
+                read_testcase(&f).unwrap_or_else(|_| panic!("Unable to read file {}", f.display()));
+            (inp, f)
+        })
+        .collect();
+    candidates
+        .sort_by(|(a, a_path), (b, b_path)| a.len().cmp(&b.len()).then_with(|| a_path.cmp(b_path)));
+
+    let mut added = 0;
+    for (inp, f) in &candidates {
+        if !cover(inp, &mut covered) {
+            continue;
+        }
+        // Corpora from different machines may reuse file names, don't overwrite inputs
+        let name = f.file_name().unwrap().to_string_lossy();
+        let mut dest = into.join(&*name);
+        let mut n = 1;
+        while dest.exists() {
+            dest = into.join(format!("{name}-{n}"));
+            n += 1;
+        }
+        copy(f, &dest).unwrap_or_else(|_| panic!("Unable to copy file {}", f.display()));
+        added += 1;
+    }
+    println!(
+        "Added {} out of {} inputs to {}, covering {} new edge tuples ({} in total)",
+        added,
+        candidates.len(),
+        into.display(),
+        covered.len() - initial,
+        covered.len()
+    );
+}
+
+// afl-tmin style reduction: remove blocks of decreasing size, then normalize the remaining bytes
+// to '0'
+fn shrink(mut data: Vec<u8>, mut keeps: impl FnMut(&[u8]) -> bool) -> Vec<u8> {
+    loop {
+        let len_before = data.len();
+
+        let mut block = data.len().next_power_of_two() / 2;
+        while block > 0 {
+            let mut pos = 0;
+            while pos < data.len() {
+                let end = (pos + block).min(data.len());
+                let candidate = [&data[..pos], &data[end..]].concat();
+                if keeps(&candidate) {
+                    data = candidate;
+                } else {
+                    pos += block;
+                }
+            }
+            block /= 2;
+        }
+
+        for pos in 0..data.len() {
+            if data[pos] != b'0' {
+                let mut candidate = data.clone();
+                candidate[pos] = b'0';
+                if keeps(&candidate) {
+                    data = candidate;
+                }
+            }
+        }
+
+        if data.len() == len_before {
+            return data;
+        }
+    }
+}
+
+// Minimize a single testcase, preserving its crash if it has one, or its coverage otherwise
+pub(crate) fn tmin(input: &Path, output: &Path) {
+    let inp =
+        read_testcase(input).unwrap_or_else(|_| panic!("Unable to read file {}", input.display()));
+    initialize();
+
+    // Crashes are reproduced in a forked child, so that the minimizer survives them
+    let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+    let mut mgr = SimpleEventManager::new(SimpleMonitor::new(|s| println!("{s}")));
+    let mut feedback = ();
+    let mut objective = CrashFeedback::new();
+    let mut state = StdState::new(
+        StdRand::new(),
+        InMemoryCorpus::<BytesInput>::new(),
+        InMemoryCorpus::new(),
+        &mut feedback,
+        &mut objective,
+    )
+    .expect("Failed to create the minimizer state");
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+    let mut harness = |input: &BytesInput| {
+        let target = input.target_bytes();
+        unsafe {
+            libfuzzer_test_one_input(&target);
+        }
+        ExitKind::Ok
+    };
+    let mut executor = InProcessForkExecutor::new(
+        &mut harness,
+        (),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+        Duration::new(1, 0),
+        shmem_provider,
+    )
+    .expect("Failed to create the fork executor");
+    let mut exit_kind = |data: &[u8]| {
+        executor
+            .run_target(
+                &mut fuzzer,
+                &mut state,
+                &mut mgr,
+                &BytesInput::new(data.to_vec()),
+            )
+            .expect("Failed to run the target")
+    };
+
+    let original = exit_kind(&inp);
+    let minimized = if original == ExitKind::Ok {
+        println!("Input does not crash, minimizing while preserving coverage");
+        let mut edges_observer = edges_observer();
+        let coverage = trace(&mut edges_observer, &inp);
+        shrink(inp.clone(), |candidate| {
+            exit_kind(candidate) == ExitKind::Ok
+                && trace(&mut edges_observer, candidate) == coverage
+        })
+    } else {
+        println!("Input exits with {original:?}, minimizing while preserving it");
+        shrink(inp.clone(), |candidate| exit_kind(candidate) == original)
+    };
+
+    std::fs::write(output, &minimized)
+        .unwrap_or_else(|_| panic!("Unable to write file {}", output.display()));
+    println!(
+        "Minimized {} bytes down to {} bytes in {}",
+        inp.len(),
+        minimized.len(),
+        output.display()
+    );
+}
@@ -0,0 +1,220 @@
+use libafl::{
+    corpus::{CorpusId, Testcase},
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    mutators::{MutationResult, Mutator},
+    Error, HasMetadata,
+};
+use libafl_bolts::{tuples::MappingFunctor, Named};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    mem::take,
+};
+
+// Executed mutants a mutator took part in, and how many of them were added to the corpus or met
+// an objective: solutions and hangs
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) struct MutationHits {
+    pub(crate) mutants: u64,
+    pub(crate) corpus: u64,
+    pub(crate) objectives: u64,
+}
+
+impl MutationHits {
+    // Share of the mutants added to the corpus, in percent
+    pub(crate) fn hit_rate(&self) -> f64 {
+        if self.mutants == 0 {
+            return 0.0;
+        }
+        100.0 * self.corpus as f64 / self.mutants as f64
+    }
+}
+
+// Mutators are keyed by their name, which they borrow, so that tracking them doesn't allocate
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct MutationStatsMetadata {
+    hits: BTreeMap<Cow<'static, str>, MutationHits>,
+    // Mutators applied to the mutant being built, and to the last one executed. A mutant skipped
+    // before its execution is forgotten when the next one is built.
+    pending: BTreeSet<Cow<'static, str>>,
+    executed: BTreeSet<Cow<'static, str>>,
+}
+
+libafl_bolts::impl_serdeany!(MutationStatsMetadata);
+
+impl MutationStatsMetadata {
+    fn credit(&mut self, credit: impl Fn(&mut MutationHits)) {
+        for name in &self.executed {
+            credit(self.hits.entry(name.clone()).or_default());
+        }
+    }
+}
+
+// Hits of each mutator of a client, by name
+pub(crate) fn mutation_hits<S: HasMetadata>(state: &S) -> BTreeMap<String, MutationHits> {
+    state
+        .metadata_map()
+        .get::<MutationStatsMetadata>()
+        .map(|metadata| {
+            metadata
+                .hits
+                .iter()
+                .map(|(name, hits)| (name.to_string(), *hits))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Mutator recording the mutants it takes part in, for the `MutationHitsFeedback`s to credit it
+// with their execution, and the corpus entries and objectives they turn out to be
+pub(crate) struct TrackedMutator<M> {
+    inner: M,
+}
+
+impl<M: Named> Named for TrackedMutator<M> {
+    fn name(&self) -> &Cow<'static, str> {
+        self.inner.name()
+    }
+}
+
+impl<I, M, S> Mutator<I, S> for TrackedMutator<M>
+where
+    M: Mutator<I, S>,
+    S: HasMetadata,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let result = self.inner.mutate(state, input)?;
+        if result == MutationResult::Mutated {
+            // Mutators stacked several times on a mutant count once
+            state
+                .metadata_or_insert_with(MutationStatsMetadata::default)
+                .pending
+                .insert(self.inner.name().clone());
+        }
+        Ok(result)
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
+
+// Mutator building each mutant with the `TrackedMutator`s it wraps, usually through a scheduled
+// mutator, starting from an empty set of applied mutators
+pub(crate) struct MutantScope<M> {
+    inner: M,
+}
+
+impl<M> MutantScope<M> {
+    pub(crate) fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<M: Named> Named for MutantScope<M> {
+    fn name(&self) -> &Cow<'static, str> {
+        self.inner.name()
+    }
+}
+
+impl<I, M, S> Mutator<I, S> for MutantScope<M>
+where
+    M: Mutator<I, S>,
+    S: HasMetadata,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        if let Ok(metadata) = state.metadata_mut::<MutationStatsMetadata>() {
+            metadata.pending.clear();
+        }
+        self.inner.mutate(state, input)
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
+
+// Wraps each mutator of a tuple into a `TrackedMutator`, with `tuple.map(TrackMutations)`
+pub(crate) struct TrackMutations;
+
+impl<M> MappingFunctor<M> for TrackMutations {
+    type Output = TrackedMutator<M>;
+
+    fn apply(&mut self, inner: M) -> Self::Output {
+        TrackedMutator { inner }
+    }
+}
+
+// Credits the mutators of each executed mutant. Evaluated first among the objectives, it takes
+// the mutators of the mutant, credits them with its execution, with an objective if it timed
+// out, as hangs are stored apart from the solutions, and again once the mutant is stored as a
+// solution, whatever objective it met. Among the feedbacks, it credits them when the mutant is
+// added to the corpus. Crediting the mutators doesn't make the mutant interesting, the other
+// feedbacks decide.
+pub(crate) struct MutationHitsFeedback {
+    objective: bool,
+}
+
+impl MutationHitsFeedback {
+    pub(crate) fn objective() -> Self {
+        Self { objective: true }
+    }
+
+    pub(crate) fn corpus() -> Self {
+        Self { objective: false }
+    }
+}
+
+impl Named for MutationHitsFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("MutationHitsFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for MutationHitsFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for MutationHitsFeedback
+where
+    S: HasMetadata,
+{
+    fn is_interesting(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        _observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        if self.objective {
+            if let Ok(metadata) = state.metadata_mut::<MutationStatsMetadata>() {
+                metadata.executed = take(&mut metadata.pending);
+                metadata.credit(|hits| hits.mutants += 1);
+                if *exit_kind == ExitKind::Timeout {
+                    metadata.credit(|hits| hits.objectives += 1);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn append_metadata(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        _testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Ok(metadata) = state.metadata_mut::<MutationStatsMetadata>() {
+            if self.objective {
+                metadata.credit(|hits| hits.objectives += 1);
+            } else {
+                metadata.credit(|hits| hits.corpus += 1);
+            }
+            metadata.executed.clear();
+        }
+        Ok(())
+    }
+}
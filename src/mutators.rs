@@ -0,0 +1,278 @@
+use libafl::{
+    corpus::CorpusId,
+    inputs::BytesInput,
+    mutators::{MutationResult, Mutator, Tokens},
+    state::HasRand,
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    rands::Rand,
+    tuples::{tuple_list, tuple_list_type},
+    Named,
+};
+use std::{borrow::Cow, num::NonZeroUsize};
+
+// Mutator sets of the power mutational stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutatorSet {
+    // MOpt-scheduled havoc and token mutations
+    MOpt,
+    // Havoc mutations only
+    Havoc,
+    // Havoc and token mutations, uniformly scheduled
+    HavocTokens,
+    // UTF-8 preserving mutations, for harnesses rejecting invalid strings
+    Unicode,
+    // Field-aligned mutations, for go-118-fuzz-build harnesses
+    Fields,
+}
+
+// Runes exercising the multi-byte, case folding and normalization corner cases of string parsers
+const INTERESTING_RUNES: &[char] = &[
+    'é',
+    'ß',
+    'İ',
+    'ı',
+    'ſ',
+    'K',
+    'Å',
+    'ﬀ',
+    'ǅ',
+    '€',
+    '中',
+    '\u{0301}',
+    '\u{200b}',
+    '\u{200d}',
+    '\u{202e}',
+    '\u{fe0f}',
+    '\u{feff}',
+    '\u{fffd}',
+    '😀',
+    '𝄞',
+    '\u{10ffff}',
+    '\0',
+];
+
+// Whether `c` extends the grapheme of the preceding rune: combining marks, joiners and
+// variation selectors
+fn is_grapheme_extend(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036f}' | '\u{200c}' | '\u{200d}' | '\u{fe00}'..='\u{fe0f}')
+}
+
+// The input as a string, replacing invalid sequences, so that mutations start from valid UTF-8
+fn as_string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// Index in `0..n`, `n` being non-zero
+pub(crate) fn below<S: HasRand>(state: &mut S, n: usize) -> usize {
+    state.rand_mut().below(NonZeroUsize::new(n).unwrap())
+}
+
+// Random rune boundary of `s`, including its end
+fn boundary<S: HasRand>(state: &mut S, s: &str) -> usize {
+    let boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).chain([s.len()]).collect();
+    boundaries[below(state, boundaries.len())]
+}
+
+// Random rune of `s`, as its byte range, or `None` if `s` is empty
+fn rune<S: HasRand>(state: &mut S, s: &str) -> Option<(usize, usize)> {
+    let count = s.chars().count();
+    if count == 0 {
+        return None;
+    }
+    let (start, c) = s.char_indices().nth(below(state, count))?;
+    Some((start, start + c.len_utf8()))
+}
+
+// Random rune, interesting most of the time
+fn random_rune<S: HasRand>(state: &mut S) -> char {
+    if state.rand_mut().coinflip(0.75) {
+        INTERESTING_RUNES[below(state, INTERESTING_RUNES.len())]
+    } else {
+        // Any scalar value, surrogates excluded
+        loop {
+            if let Some(c) = char::from_u32(below(state, 0x11_0000) as u32) {
+                return c;
+            }
+        }
+    }
+}
+
+// Mutator of a decoded view of the input: `$decode` turns the input's bytes into `$view`, which the
+// block mutates, returning whether it did, and `$encode` turns it back into bytes
+macro_rules! decoded_mutator {
+    ($name:ident, $decode:expr, $encode:expr, |$state:ident, $view:ident| $mutate:block) => {
+        #[derive(Debug, Default)]
+        pub(crate) struct $name;
+
+        impl Named for $name {
+            fn name(&self) -> &Cow<'static, str> {
+                static NAME: Cow<'static, str> = Cow::Borrowed(stringify!($name));
+                &NAME
+            }
+        }
+
+        impl<S> Mutator<BytesInput, S> for $name
+        where
+            S: HasRand + HasMetadata,
+        {
+            fn mutate(
+                &mut self,
+                $state: &mut S,
+                input: &mut BytesInput,
+            ) -> Result<MutationResult, Error> {
+                let bytes: &[u8] = input.as_ref();
+                let mut $view = $decode(bytes);
+                let mutated: bool = $mutate;
+                if !mutated {
+                    return Ok(MutationResult::Skipped);
+                }
+                *input = BytesInput::new($encode($view));
+                Ok(MutationResult::Mutated)
+            }
+
+            fn post_exec(
+                &mut self,
+                _state: &mut S,
+                _new_corpus_id: Option<CorpusId>,
+            ) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use decoded_mutator;
+
+macro_rules! utf8_mutator {
+    ($name:ident, |$state:ident, $s:ident| $mutate:block) => {
+        decoded_mutator!($name, as_string, String::into_bytes, |$state, $s| $mutate);
+    };
+}
+
+utf8_mutator!(
+    // Insert a rune, most of the time a multi-byte one
+    RuneInsertMutator,
+    |state, s| {
+        let at = boundary(state, &s);
+        let c = random_rune(state);
+        s.insert(at, c);
+        true
+    }
+);
+
+utf8_mutator!(
+    // Replace a rune by another one
+    RuneReplaceMutator,
+    |state, s| {
+        match rune(state, &s) {
+            Some((start, end)) => {
+                let c = random_rune(state);
+                s.replace_range(start..end, c.encode_utf8(&mut [0; 4]));
+                true
+            }
+            None => false,
+        }
+    }
+);
+
+utf8_mutator!(
+    // Delete a rune
+    RuneDeleteMutator,
+    |state, s| {
+        match rune(state, &s) {
+            Some((start, end)) => {
+                s.replace_range(start..end, "");
+                true
+            }
+            None => false,
+        }
+    }
+);
+
+utf8_mutator!(
+    // Flip the case of a rune, whose encoding may change length (e.g. 'ß' to "SS")
+    CaseFlipMutator,
+    |state, s| {
+        match rune(state, &s) {
+            Some((start, end)) => {
+                let c = s[start..end].chars().next().unwrap();
+                let flipped: String = if c.is_lowercase() {
+                    c.to_uppercase().collect()
+                } else {
+                    c.to_lowercase().collect()
+                };
+                let changed = flipped != s[start..end];
+                s.replace_range(start..end, &flipped);
+                changed
+            }
+            None => false,
+        }
+    }
+);
+
+utf8_mutator!(
+    // Repeat a grapheme (a rune followed by its combining marks) up to 16 times
+    GraphemeDupMutator,
+    |state, s| {
+        match rune(state, &s) {
+            Some((start, mut end)) => {
+                while let Some(c) = s[end..].chars().next().filter(|&c| is_grapheme_extend(c)) {
+                    end += c.len_utf8();
+                }
+                let grapheme = s[start..end].repeat(1 + below(state, 16));
+                s.insert_str(end, &grapheme);
+                true
+            }
+            None => false,
+        }
+    }
+);
+
+utf8_mutator!(
+    // Insert a token of the dictionaries, if it is valid UTF-8
+    Utf8TokenInsertMutator,
+    |state, s| {
+        let tokens: Vec<String> = state
+            .metadata_map()
+            .get::<Tokens>()
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .filter_map(|token| String::from_utf8(token.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if tokens.is_empty() {
+            false
+        } else {
+            let token = &tokens[below(state, tokens.len())];
+            let at = boundary(state, &s);
+            s.insert_str(at, token);
+            true
+        }
+    }
+);
+
+// Type of the UTF-8 preserving mutations
+pub(crate) type Utf8MutationsType = tuple_list_type!(
+    RuneInsertMutator,
+    RuneReplaceMutator,
+    RuneDeleteMutator,
+    CaseFlipMutator,
+    GraphemeDupMutator,
+    Utf8TokenInsertMutator
+);
+
+// Mutations keeping the inputs valid UTF-8, repairing invalid ones first
+pub(crate) fn utf8_mutations() -> Utf8MutationsType {
+    tuple_list!(
+        RuneInsertMutator,
+        RuneReplaceMutator,
+        RuneDeleteMutator,
+        CaseFlipMutator,
+        GraphemeDupMutator,
+        Utf8TokenInsertMutator
+    )
+}
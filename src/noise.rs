@@ -0,0 +1,129 @@
+use crate::elf::elf_symbols;
+use libafl_targets::COUNTERS_MAPS;
+use std::{
+    env,
+    fs::{self, read_to_string},
+    os::raw::{c_int, c_void},
+    path::Path,
+    ptr::addr_of_mut,
+};
+
+// Packages whose coverage depends on the state of the Go runtime rather than on the input:
+// garbage collection, scheduling, map iteration order...
+const RUNTIME_PACKAGES: [&str; 3] = ["runtime", "internal", "sync"];
+
+// Prefix of the names the Go compiler gives to its static variables, the counters among them,
+// after the package path
+const STATIC_PREFIX: &str = "..stmp_";
+
+// Load address of the executable, which `dl_iterate_phdr` reports first
+unsafe extern "C" fn load_address(
+    info: *mut libc::dl_phdr_info,
+    _size: usize,
+    data: *mut c_void,
+) -> c_int {
+    *data.cast::<usize>() = (*info).dlpi_addr as usize;
+    1
+}
+
+// Whether `package` is `pattern`, or one of its subpackages for `<path>/...` patterns
+fn matches(pattern: &str, package: &str) -> bool {
+    match pattern.strip_suffix("/...") {
+        Some(prefix) => {
+            package == prefix
+                || package
+                    .strip_prefix(prefix)
+                    .is_some_and(|s| s.starts_with('/'))
+        }
+        None => package == pattern,
+    }
+}
+
+// Package path of a Go function, from its symbol name, e.g. `github.com/me/lib` for
+// `github.com/me/lib.(*Parser).Parse`
+fn function_package(function: &str) -> Option<&str> {
+    // Type arguments of generic functions may contain paths too
+    let function = function.split('[').next()?;
+    let last = function.rfind('/').map_or(0, |slash| slash + 1);
+    let dot = function[last..].find('.')?;
+    Some(&function[..last + dot])
+}
+
+// Packages defining the functions listed in `path`, one Go symbol name per line, as `--focus`
+// patterns. Empty lines and `#` comments are skipped.
+pub(crate) fn changed_packages(path: &Path) -> Vec<String> {
+    let functions = read_to_string(path)
+        .unwrap_or_else(|_| panic!("Unable to read the changed functions {}", path.display()));
+    let mut packages: Vec<String> = functions
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(function_package)
+        .map(str::to_string)
+        .collect();
+    packages.sort_unstable();
+    packages.dedup();
+    packages
+}
+
+fn is_runtime_package(package: &str) -> bool {
+    RUNTIME_PACKAGES
+        .iter()
+        .any(|p| matches(&format!("{p}/..."), package))
+}
+
+// Indexes, in the edges map, of the counters to ignore: the ones of the Go runtime packages if
+// `runtime` is set, and the ones of the packages outside of `focus` unless it is empty. Each
+// counter is a `<package>..stmp_<n>` static of the package it instruments, so they are attributed
+// through the executable's symbol table, which must not be stripped.
+#[allow(static_mut_refs)]
+pub(crate) fn masked_edges(runtime: bool, focus: &[String]) -> Result<Vec<usize>, String> {
+    let symbols = env::current_exe()
+        .and_then(fs::read)
+        .ok()
+        .and_then(|exe| elf_symbols(&exe))
+        .ok_or("Unable to read the symbol table of the executable (stripped?)")?;
+    let mut base = 0usize;
+    unsafe {
+        libc::dl_iterate_phdr(Some(load_address), addr_of_mut!(base).cast());
+    }
+
+    // Address range of each counters map, and its offset in the edges map
+    let mut maps = Vec::new();
+    let mut offset = 0;
+    for map in unsafe { COUNTERS_MAPS.iter() } {
+        let start = map.as_ptr() as usize;
+        maps.push((start..start + map.len(), offset));
+        offset += map.len();
+    }
+
+    // Package of each counter
+    let counters: Vec<(&str, usize)> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let (package, _) = symbol.name.split_once(STATIC_PREFIX)?;
+            let address = base + symbol.value;
+            maps.iter()
+                .find(|(range, _)| range.contains(&address))
+                .map(|(range, offset)| (package, offset + address - range.start))
+        })
+        .collect();
+    let focused = |package: &str| focus.iter().any(|pattern| matches(pattern, package));
+    if !focus.is_empty() && !counters.iter().any(|(package, _)| focused(package)) {
+        return Err(format!(
+            "No instrumented package matches {}",
+            focus.join(", ")
+        ));
+    }
+
+    let mut edges: Vec<usize> = counters
+        .into_iter()
+        .filter(|(package, _)| {
+            (runtime && is_runtime_package(package)) || (!focus.is_empty() && !focused(package))
+        })
+        .map(|(_, edge)| edge)
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    Ok(edges)
+}
@@ -0,0 +1,167 @@
+use crate::{
+    meta::InvariantMetadata,
+    output::{pin_filename, OutputObserver},
+    triage::{crash_message, invariant_signature_id, signature_id},
+};
+use libafl::{
+    corpus::Testcase,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    fs::{create_dir_all, remove_file, rename, OpenOptions},
+    io::ErrorKind,
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Objective feedback POSTing a JSON payload to a webhook for each new crash signature: crash
+// path, signature, panic message, client and timestamp. Signatures are the ids the metadata files
+// and `replay-crashes` group the crashes by, so that crashes without a panic message, e.g. killed
+// by a signal, are notified once too. Notified from `append_metadata`, which pins the name the
+// solution is then stored under.
+pub(crate) struct NotifyFeedback {
+    url: Option<String>,
+    output_handle: Handle<OutputObserver>,
+    client: usize,
+    // Directory of the solutions corpus
+    crashes: PathBuf,
+    // One file per notified signature, shared by the clients
+    notified: PathBuf,
+}
+
+impl NotifyFeedback {
+    // Doesn't notify anything if `url` is `None`
+    pub(crate) fn new(
+        url: Option<String>,
+        output_observer: &OutputObserver,
+        client: usize,
+        crashes: PathBuf,
+        notified: PathBuf,
+    ) -> Self {
+        Self {
+            url,
+            output_handle: output_observer.handle(),
+            client,
+            crashes,
+            notified,
+        }
+    }
+
+    // Claim the notification of `signature`, returning the files marking it as being notified
+    // and as notified, or `None` if another client is notifying or notified it already
+    fn claim(&self, signature: &str) -> Result<Option<(PathBuf, PathBuf)>, Error> {
+        create_dir_all(&self.notified)?;
+        let digest = Sha256::digest(signature.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        let notified = self.notified.join(&name);
+        if notified.exists() {
+            return Ok(None);
+        }
+        let pending = self.notified.join(format!("{name}.pending"));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&pending)
+        {
+            Ok(_) => Ok(Some((pending, notified))),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+// POST `payload` with curl, returning whether the endpoint accepted it. The objectives are
+// evaluated right before a crashing client exits, a background thread would die with it.
+fn post(url: &str, payload: &str) -> bool {
+    let status = Command::new("curl")
+        .args(["-fsS", "-m", "5", "-o", "/dev/null", "-X", "POST"])
+        .args([
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            payload,
+        ])
+        .arg(url)
+        .stdin(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => true,
+        Ok(status) => {
+            eprintln!("Warning: notifying {url} failed with {status}");
+            false
+        }
+        Err(err) => {
+            eprintln!("Warning: unable to run curl to notify {url}: {err}");
+            false
+        }
+    }
+}
+
+impl Named for NotifyFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("NotifyFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for NotifyFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for NotifyFeedback
+where
+    I: Input,
+    OT: MatchNameRef,
+{
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let Some(url) = &self.url else {
+            return Ok(());
+        };
+        let name = pin_filename(testcase);
+        let output = observers
+            .get(&self.output_handle)
+            .map(|observer| String::from_utf8_lossy(observer.output()).into_owned())
+            .unwrap_or_default();
+        let message = crash_message(&output);
+        let signature = match testcase.metadata::<InvariantMetadata>() {
+            Ok(metadata) => invariant_signature_id(&metadata.reason),
+            Err(_) => signature_id(&output),
+        };
+        let Some((pending, notified)) = self.claim(&signature)? else {
+            return Ok(());
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let payload = json!({
+            "crash": self.crashes.join(&name),
+            "signature": signature,
+            "panic": message,
+            "client": self.client,
+            "timestamp": timestamp,
+        });
+        // The signature is notified again on its next crash if the endpoint didn't accept it
+        if post(url, &payload.to_string()) {
+            rename(pending, notified)?;
+        } else {
+            remove_file(pending)?;
+        }
+        Ok(())
+    }
+}
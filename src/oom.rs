@@ -0,0 +1,182 @@
+use libafl::{
+    corpus::{Corpus, OnDiskCorpus, Testcase},
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    observers::Observer,
+    Error,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::{read_dir, File},
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+// Inputs exceeding the limit stored at most, the first ones being enough to debug the growth
+const MAX_STORED_OOMS: usize = 100;
+
+// Observer measuring how much the resident memory of the process grew during each execution.
+// As the Go harness disables the garbage collector, this is close to what the input allocated.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct RssObserver {
+    name: Cow<'static, str>,
+    #[serde(skip)]
+    statm: Option<File>,
+    #[serde(skip)]
+    rss_before: u64,
+    #[serde(skip)]
+    rss_growth: u64,
+}
+
+impl RssObserver {
+    // Measure the executions if `enabled`, otherwise the observer does nothing
+    pub(crate) fn new(name: &'static str, enabled: bool) -> Result<Self, Error> {
+        let statm = if enabled {
+            Some(File::open("/proc/self/statm")?)
+        } else {
+            None
+        };
+        Ok(Self {
+            name: Cow::Borrowed(name),
+            statm,
+            rss_before: 0,
+            rss_growth: 0,
+        })
+    }
+
+    // Resident memory growth in bytes during the last execution
+    pub(crate) fn rss_growth(&self) -> u64 {
+        self.rss_growth
+    }
+
+    fn rss(&mut self) -> Result<u64, Error> {
+        let Some(statm) = self.statm.as_mut() else {
+            return Ok(0);
+        };
+        let mut buf = String::new();
+        statm.seek(SeekFrom::Start(0))?;
+        statm.read_to_string(&mut buf)?;
+        // The second field is the number of resident pages
+        let pages: u64 = buf
+            .split_whitespace()
+            .nth(1)
+            .and_then(|pages| pages.parse().ok())
+            .ok_or_else(|| Error::illegal_state("Unexpected /proc/self/statm format"))?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        Ok(pages * page_size)
+    }
+}
+
+impl Named for RssObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for RssObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        self.rss_before = self.rss()?;
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.rss_growth = self.rss()?.saturating_sub(self.rss_before);
+        Ok(())
+    }
+}
+
+// Objective feedback storing inputs allocating more than a limit in their own corpus, like
+// libFuzzer's `-rss_limit_mb`. Unlike libFuzzer's, the limit is checked once the execution is
+// over, and doesn't stop it. Past the harness's `debug.SetMemoryLimit`, 1 GiB in the template, the
+// Go runtime collects garbage to stay below it, hiding the growth: limits above it aren't reached.
+// The inputs aren't solutions, as they don't crash when replayed. One input is stored per power of
+// two of the growth and up to `MAX_STORED_OOMS`, as most mutants of an input close to the limit
+// exceed it.
+pub(crate) struct OomFeedback<I> {
+    observer_handle: Handle<RssObserver>,
+    // Limit in bytes, and corpus of the inputs exceeding it
+    ooms: Option<(u64, OnDiskCorpus<I>)>,
+    // Powers of two of the growths an input was stored for
+    seen: HashSet<u32>,
+    // Inputs in the oom directory, including the ones of the other clients when starting
+    stored: usize,
+}
+
+impl<I> OomFeedback<I>
+where
+    I: Input,
+{
+    // Inputs growing the resident memory by more than `limit_mb` MiB are stored in `dir`.
+    // Without a limit, the feedback does nothing.
+    pub(crate) fn new(
+        observer: &RssObserver,
+        limit_mb: Option<u64>,
+        dir: PathBuf,
+    ) -> Result<Self, Error> {
+        let stored = read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .count();
+        let ooms = match limit_mb {
+            Some(limit_mb) => Some((limit_mb * 1024 * 1024, OnDiskCorpus::new(dir)?)),
+            None => None,
+        };
+        Ok(Self {
+            observer_handle: observer.handle(),
+            ooms,
+            seen: HashSet::new(),
+            stored,
+        })
+    }
+}
+
+impl<I> Named for OomFeedback<I> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("OomFeedback");
+        &NAME
+    }
+}
+
+impl<I, S> StateInitializer<S> for OomFeedback<I> {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for OomFeedback<I>
+where
+    I: Input,
+    OT: MatchNameRef,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let Some((limit, ooms)) = self.ooms.as_mut() else {
+            return Ok(false);
+        };
+        let observer = observers
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::key_not_found("RssObserver not found"))?;
+        let growth = observer.rss_growth();
+        if growth > *limit && self.stored < MAX_STORED_OOMS && self.seen.insert(growth.ilog2()) {
+            ooms.add(Testcase::new(input.clone()))?;
+            self.stored += 1;
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,243 @@
+use crate::meta::InvariantMetadata;
+use libafl::{
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    observers::Observer,
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    env,
+    fs::{remove_file, write, File},
+    io::{Read, Seek, SeekFrom},
+    os::fd::AsRawFd,
+    path::PathBuf,
+    process,
+};
+
+// Observer capturing what the target writes to stdout and stderr during each execution.
+// Both are redirected to an unlinked temporary file, which is rewound before every execution.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct OutputObserver {
+    name: Cow<'static, str>,
+    #[serde(skip)]
+    capture: Option<File>,
+    #[serde(skip)]
+    output: Vec<u8>,
+}
+
+impl OutputObserver {
+    // Redirect the process' stdout and stderr if `enabled`, otherwise the observer does nothing
+    pub(crate) fn new(name: &'static str, enabled: bool) -> Result<Self, Error> {
+        let capture = if enabled {
+            let path = env::temp_dir().join(format!("golibafl-output-{}", process::id()));
+            let file = File::options()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&path)?;
+            remove_file(&path)?;
+            for fd in [libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+                if unsafe { libc::dup2(file.as_raw_fd(), fd) } == -1 {
+                    return Err(Error::last_os_error(
+                        "Failed to redirect the target's output",
+                    ));
+                }
+            }
+            Some(file)
+        } else {
+            None
+        };
+        Ok(Self {
+            name: Cow::Borrowed(name),
+            capture,
+            output: Vec::new(),
+        })
+    }
+
+    // Whether the output of the target is captured
+    pub(crate) fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    // Output of the last execution
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+impl Named for OutputObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for OutputObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        if let Some(capture) = self.capture.as_mut() {
+            capture.set_len(0)?;
+            capture.seek(SeekFrom::Start(0))?;
+        }
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.output.clear();
+        if let Some(capture) = self.capture.as_mut() {
+            capture.seek(SeekFrom::Start(0))?;
+            capture.read_to_end(&mut self.output)?;
+        }
+        Ok(())
+    }
+}
+
+// Objective feedback writing the output captured by an `OutputObserver` next to each solution,
+// as `.<name>.output` like LibAFL's `.<name>.metadata` files. The output is written from
+// `append_metadata`, for the inputs the other objectives made solutions.
+pub(crate) struct OutputFeedback {
+    observer_handle: Handle<OutputObserver>,
+    dir: PathBuf,
+}
+
+impl OutputFeedback {
+    // `dir` must be the directory of the solutions corpus
+    pub(crate) fn new(observer: &OutputObserver, dir: PathBuf) -> Self {
+        Self {
+            observer_handle: observer.handle(),
+            dir,
+        }
+    }
+}
+
+impl Named for OutputFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("OutputFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for OutputFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for OutputFeedback
+where
+    I: Input,
+    OT: MatchNameRef,
+{
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let Some(observer) = observers.get(&self.observer_handle) else {
+            return Ok(());
+        };
+        if observer.capture.is_none() {
+            return Ok(());
+        }
+        let name = pin_filename(testcase);
+        write(self.dir.join(format!(".{name}.output")), observer.output())?;
+        Ok(())
+    }
+}
+
+// Objective feedback reporting the executions whose captured output matches one of `patterns`,
+// e.g. a Go target logging "unreachable state" without panicking. The solutions are tagged with
+// an `InvariantMetadata` naming the matched text.
+pub(crate) struct CrashPatternFeedback {
+    observer_handle: Handle<OutputObserver>,
+    patterns: Vec<Regex>,
+    // Text the last execution matched, to attach to its input
+    matched: Option<String>,
+}
+
+impl CrashPatternFeedback {
+    // Without patterns, the feedback never reports inputs
+    pub(crate) fn new(observer: &OutputObserver, patterns: Vec<Regex>) -> Self {
+        Self {
+            observer_handle: observer.handle(),
+            patterns,
+            matched: None,
+        }
+    }
+}
+
+impl Named for CrashPatternFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("CrashPatternFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for CrashPatternFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for CrashPatternFeedback
+where
+    OT: MatchNameRef,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        self.matched = None;
+        if self.patterns.is_empty() {
+            return Ok(false);
+        }
+        let observer = observers
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::key_not_found("OutputObserver not found"))?;
+        let output = String::from_utf8_lossy(observer.output());
+        self.matched = self
+            .patterns
+            .iter()
+            .find_map(|pattern| pattern.find(&output))
+            .map(|found| found.as_str().to_string());
+        Ok(self.matched.is_some())
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(reason) = self.matched.take() {
+            testcase.add_metadata(InvariantMetadata { reason });
+        }
+        Ok(())
+    }
+}
+
+// Pin the file name of a solution before the corpus stores it, so that it matches the one of the
+// sidecar files written next to it
+pub(crate) fn pin_filename<I>(testcase: &mut Testcase<I>) -> String
+where
+    I: Input,
+{
+    let name = match testcase.filename() {
+        Some(name) => name.clone(),
+        None => testcase.input().as_ref().unwrap().generate_name(None),
+    };
+    testcase.set_filename(name.clone());
+    name
+}
@@ -0,0 +1,13 @@
+use crate::{CmpLogMode, CmpLogRate, MutatorSet, SchedulerKind};
+use libafl::schedulers::powersched::PowerSchedule;
+
+// Settings of a single client overriding the campaign's ones, e.g. to run a client with UTF-8
+// mutations next to clients with the default ones
+#[derive(Debug, Clone, Default)]
+pub struct ClientOverrides {
+    pub mutators: Option<MutatorSet>,
+    pub power_schedule: Option<PowerSchedule>,
+    pub scheduler: Option<SchedulerKind>,
+    pub cmplog: Option<CmpLogMode>,
+    pub cmplog_rate: Option<CmpLogRate>,
+}
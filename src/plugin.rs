@@ -0,0 +1,95 @@
+use libafl::{
+    corpus::CorpusId,
+    inputs::BytesInput,
+    mutators::{MutationResult, Mutator},
+    state::{HasMaxSize, HasRand},
+    Error,
+};
+use libafl_bolts::{rands::Rand, Named};
+use libloading::Library;
+use std::{borrow::Cow, path::Path};
+
+// Function a mutator plugin exports, in C:
+//   size_t golibafl_mutate(uint8_t *data, size_t size, size_t max_size, uint64_t seed);
+// `data` holds the `size` bytes of the input, in a buffer of `max_size` bytes. The plugin mutates
+// them in place, drawing its randomness from `seed`, and returns the size of the mutant, at most
+// `max_size`, or 0 to skip the input.
+const MUTATE_SYMBOL: &[u8] = b"golibafl_mutate\0";
+
+type MutateFn = unsafe extern "C" fn(*mut u8, usize, usize, u64) -> usize;
+
+// Mutator of a dynamic library, for target-specific mutations shipped apart from GoLibAFL. Without
+// a library, it skips every input.
+pub(crate) struct PluginMutator {
+    // Kept loaded as long as `mutate` may be called
+    plugin: Option<(Library, MutateFn)>,
+    // Buffer the plugin mutates, grown to the largest `max_size` seen and reused across mutants
+    buffer: Vec<u8>,
+}
+
+impl PluginMutator {
+    pub(crate) fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let Some(path) = path else {
+            return Ok(Self {
+                plugin: None,
+                buffer: Vec::new(),
+            });
+        };
+        let invalid = |err: libloading::Error| {
+            Error::illegal_argument(format!(
+                "Failed to load the mutator plugin {}: {err}",
+                path.display()
+            ))
+        };
+        // Loading runs the library's initializers, which the user trusts by passing it
+        let library = unsafe { Library::new(path) }.map_err(invalid)?;
+        let mutate = *unsafe { library.get::<MutateFn>(MUTATE_SYMBOL) }.map_err(invalid)?;
+        Ok(Self {
+            plugin: Some((library, mutate)),
+            buffer: Vec::new(),
+        })
+    }
+}
+
+impl Named for PluginMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("PluginMutator");
+        &NAME
+    }
+}
+
+impl<S> Mutator<BytesInput, S> for PluginMutator
+where
+    S: HasRand + HasMaxSize,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let Some((_, mutate)) = &self.plugin else {
+            return Ok(MutationResult::Skipped);
+        };
+        let bytes: &[u8] = input.as_ref();
+        let size = bytes.len();
+        let max_size = state.max_size().max(size);
+        if self.buffer.len() < max_size {
+            self.buffer.resize(max_size, 0);
+        }
+        // The bytes past the input are left from the previous mutants
+        self.buffer[..size].copy_from_slice(bytes);
+        let seed = state.rand_mut().next();
+        let new_size = unsafe { mutate(self.buffer.as_mut_ptr(), size, max_size, seed) };
+        if new_size == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+        if new_size > max_size {
+            return Err(Error::illegal_state(format!(
+                "The mutator plugin returned a mutant of {new_size} bytes, past the buffer of \
+                 {max_size} bytes"
+            )));
+        }
+        *input = BytesInput::from(&self.buffer[..new_size]);
+        Ok(MutationResult::Mutated)
+    }
+
+    fn post_exec(&mut self, _state: &mut S, _new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        Ok(())
+    }
+}
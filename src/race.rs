@@ -0,0 +1,154 @@
+use crate::output::{pin_filename, OutputObserver};
+use libafl::{
+    corpus::{Corpus, OnDiskCorpus, Testcase},
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    Error,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use std::{borrow::Cow, fs::write, path::PathBuf};
+
+// Line framing each report of Go's race detector
+const RACE_SEPARATOR: &str = "==================";
+
+// Reports of the race detector in the output of an execution, separators included
+fn race_reports(output: &[u8]) -> Option<String> {
+    let output = String::from_utf8_lossy(output);
+    let mut reports = String::new();
+    let mut report: Option<String> = None;
+    for line in output.lines() {
+        match report.as_mut() {
+            Some(lines) if line == RACE_SEPARATOR => {
+                lines.push_str(line);
+                lines.push('\n');
+                if lines.contains("WARNING: DATA RACE") {
+                    reports.push_str(lines);
+                }
+                report = None;
+            }
+            Some(lines) => {
+                lines.push_str(line);
+                lines.push('\n');
+            }
+            None if line == RACE_SEPARATOR => report = Some(format!("{line}\n")),
+            None => {}
+        }
+    }
+    // The report of a race detected at the end of the execution may be cut
+    if let Some(lines) = report.filter(|lines| lines.contains("WARNING: DATA RACE")) {
+        reports.push_str(&lines);
+    }
+    (!reports.is_empty()).then_some(reports)
+}
+
+// Objective feedback storing the inputs making the race detector of targets built with `-race`
+// report a data race in their own corpus, with the report next to them as `.<name>.race`. The
+// detector only reports each race once per process, and the execution goes on, so a racy input
+// isn't a solution: replaying it in a fresh process would report the race again, not crash.
+pub(crate) struct RaceFeedback<I> {
+    observer_handle: Handle<OutputObserver>,
+    client: usize,
+    // Directory and corpus of the racy inputs
+    races: Option<(PathBuf, OnDiskCorpus<I>)>,
+}
+
+impl<I> RaceFeedback<I>
+where
+    I: Input,
+{
+    // Racy inputs are stored in `dir`, or not looked for if it is `None`. The observer must
+    // capture the output of the target.
+    pub(crate) fn new(
+        observer: &OutputObserver,
+        client: usize,
+        dir: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        let races = dir
+            .map(|dir| OnDiskCorpus::new(&dir).map(|corpus| (dir, corpus)))
+            .transpose()?;
+        Ok(Self {
+            observer_handle: observer.handle(),
+            client,
+            races,
+        })
+    }
+}
+
+impl<I> Named for RaceFeedback<I> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("RaceFeedback");
+        &NAME
+    }
+}
+
+impl<I, S> StateInitializer<S> for RaceFeedback<I> {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for RaceFeedback<I>
+where
+    I: Input,
+    OT: MatchNameRef,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let Some((dir, races)) = self.races.as_mut() else {
+            return Ok(false);
+        };
+        let observer = observers
+            .get(&self.observer_handle)
+            .ok_or_else(|| Error::key_not_found("OutputObserver not found"))?;
+        let Some(reports) = race_reports(observer.output()) else {
+            return Ok(false);
+        };
+        let mut testcase = Testcase::new(input.clone());
+        let name = pin_filename(&mut testcase);
+        races.add(testcase)?;
+        write(dir.join(format!(".{name}.race")), reports)?;
+        // On stderr, which the clients keep
+        eprintln!(
+            "Client {} found a data race, stored as {}",
+            self.client,
+            dir.join(&name).display()
+        );
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RACE: &str = "==================
+WARNING: DATA RACE
+Write at 0x00c000012345 by goroutine 7:
+  main.update()
+==================
+";
+
+    #[test]
+    fn race_reports_are_extracted() {
+        let output = format!("INPUT: [1]\n{RACE}ok\n");
+        assert_eq!(race_reports(output.as_bytes()).as_deref(), Some(RACE));
+        // A report cut at the end of the execution is kept
+        let cut = RACE.trim_end_matches("==================\n");
+        assert_eq!(race_reports(cut.as_bytes()).as_deref(), Some(cut));
+    }
+
+    #[test]
+    fn other_framed_output_is_not_a_race() {
+        assert_eq!(
+            race_reports(b"==================\nsome banner\n==================\n"),
+            None
+        );
+        assert_eq!(race_reports(b"panic: boom\n"), None);
+    }
+}
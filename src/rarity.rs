@@ -0,0 +1,120 @@
+use libafl::{
+    corpus::Testcase,
+    feedbacks::{Feedback, MapIndexesMetadata, StateInitializer},
+    schedulers::{
+        testcase_score::{CorpusWeightTestcaseScore, TestcaseScore},
+        WeightedScheduler,
+    },
+    state::HasCorpus,
+    Error, HasMetadata,
+};
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+// Weight multiplier of the corpus entries hitting a rare edge
+const RARE_BOOST: f64 = 4.0;
+
+// Number of corpus entries which hit each edge, and the rarity cutoff derived from them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EdgeHitsMetadata {
+    hits: Vec<u32>,
+    cutoff: u32,
+}
+
+libafl_bolts::impl_serdeany!(EdgeHitsMetadata);
+
+impl EdgeHitsMetadata {
+    // Like FairFuzz, an edge is rare if it was hit at most as many times as the smallest power of
+    // two above the hit count of the rarest edge
+    fn update_cutoff(&mut self) {
+        self.cutoff = self
+            .hits
+            .iter()
+            .copied()
+            .filter(|&hits| hits > 0)
+            .min()
+            .map_or(0, u32::next_power_of_two);
+    }
+
+    fn is_rare(&self, edge: usize) -> bool {
+        self.hits
+            .get(edge)
+            .is_some_and(|&hits| hits > 0 && hits <= self.cutoff)
+    }
+}
+
+// Feedback counting the corpus entries hitting each edge in the state's `EdgeHitsMetadata`, for
+// the `RareEdgeTestcaseScore` to favor the ones hitting rarely seen edges. Counted from the edges
+// the map feedback, placed before it, lists for each new entry, so that executions don't pay for
+// walking the map. It never reports inputs as interesting itself, and does nothing unless enabled.
+pub(crate) struct RareEdgeFeedback {
+    enabled: bool,
+}
+
+impl RareEdgeFeedback {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl Named for RareEdgeFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("RareEdgeFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for RareEdgeFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for RareEdgeFeedback
+where
+    S: HasMetadata,
+{
+    fn append_metadata(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let Ok(indexes) = testcase.metadata::<MapIndexesMetadata>() else {
+            return Ok(());
+        };
+        let metadata = state.metadata_or_insert_with(EdgeHitsMetadata::default);
+        for &edge in &indexes.list {
+            if edge >= metadata.hits.len() {
+                metadata.hits.resize(edge + 1, 0);
+            }
+            metadata.hits[edge] = metadata.hits[edge].saturating_add(1);
+        }
+        metadata.update_cutoff();
+        Ok(())
+    }
+}
+
+// Weight of the weighted scheduler, multiplied for the corpus entries hitting a rare edge. The
+// edges of an entry are those the minimizer keeps it for.
+pub(crate) struct RareEdgeTestcaseScore;
+
+impl<I, S> TestcaseScore<I, S> for RareEdgeTestcaseScore
+where
+    S: HasCorpus<I> + HasMetadata,
+{
+    fn compute(state: &S, entry: &mut Testcase<I>) -> Result<f64, Error> {
+        let weight = CorpusWeightTestcaseScore::compute(state, entry)?;
+        let Some(hits) = state.metadata_map().get::<EdgeHitsMetadata>() else {
+            return Ok(weight);
+        };
+        let rare = entry
+            .metadata::<MapIndexesMetadata>()
+            .is_ok_and(|indexes| indexes.list.iter().any(|&edge| hits.is_rare(edge)));
+        Ok(if rare { weight * RARE_BOOST } else { weight })
+    }
+}
+
+// Weighted scheduler favoring the corpus entries hitting rare edges
+pub(crate) type RareEdgeScheduler<C, O> = WeightedScheduler<C, RareEdgeTestcaseScore, O>;
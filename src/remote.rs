@@ -0,0 +1,75 @@
+use libafl::Error;
+use std::{
+    fs::{create_dir_all, remove_dir_all, remove_file},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+// Directory of the downloaded seed archives, in the output directory
+const REMOTE_DIR: &str = "remote";
+
+// Whether an input is the URL of a seed archive rather than a local path
+pub(crate) fn is_url(input: &Path) -> bool {
+    let input = input.to_string_lossy();
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+// Run a command, failing if it doesn't succeed
+fn run(command: &mut Command, what: &str) -> Result<(), Error> {
+    let status = command
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|err| Error::os_error(err, format!("Failed to {what}")))?;
+    if !status.success() {
+        return Err(Error::unknown(format!("Failed to {what}: {status}")));
+    }
+    Ok(())
+}
+
+// Download the seed archive at `url` with curl, and unpack it with tar in `dest`, emptied first.
+// tar detects the compression, e.g. gzip, xz or zstd, from the archive itself.
+fn fetch(url: &str, dest: &Path) -> Result<(), Error> {
+    let _ = remove_dir_all(dest);
+    create_dir_all(dest)?;
+    let archive = dest.with_extension("archive");
+    run(
+        Command::new("curl")
+            .args(["-fsSL", "--retry", "3", "-o"])
+            .arg(&archive)
+            .arg(url),
+        &format!("download {url}"),
+    )?;
+    let unpacked = run(
+        Command::new("tar")
+            .arg("-xf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(dest),
+        &format!("unpack {url}"),
+    );
+    let _ = remove_file(&archive);
+    unpacked
+}
+
+// Replace the URLs among `inputs` by the directories their archives are unpacked in, under
+// `<output>/remote`. The archives are downloaded again on every start, as CI jobs pull the latest
+// shared corpus.
+pub(crate) fn fetch_remote_inputs(
+    inputs: &[PathBuf],
+    output: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| {
+            if !is_url(input) {
+                return Ok(input.clone());
+            }
+            let url = input.to_string_lossy();
+            let dest = output.join(REMOTE_DIR).join(i.to_string());
+            println!("Downloading the seeds of {url}");
+            fetch(&url, &dest)?;
+            Ok(dest)
+        })
+        .collect()
+}
@@ -0,0 +1,121 @@
+use crate::{
+    sarif::export_sarif,
+    triage::{group_crashes, mask_numbers},
+};
+use std::{
+    fmt::Write as _,
+    fs::write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Report written once a campaign is over, for CI jobs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CampaignReport {
+    // JUnit XML, each crash signature being a failed test case
+    Junit(PathBuf),
+    // SARIF, each crash signature being a code scanning alert
+    Sarif(PathBuf),
+}
+
+impl CampaignReport {
+    // Parse `<format>=<path>`, e.g. `junit=report.xml`
+    pub fn from_cmdline(report: &str) -> Result<Self, String> {
+        match report.split_once('=') {
+            Some(("junit", path)) if !path.is_empty() => Ok(Self::Junit(PathBuf::from(path))),
+            Some(("sarif", path)) if !path.is_empty() => Ok(Self::Sarif(PathBuf::from(path))),
+            _ => Err(format!(
+                "Expected junit=<path> or sarif=<path>, got {report}"
+            )),
+        }
+    }
+
+    // Replay the crashes of the campaign and write the report
+    pub(crate) fn write(&self, crashes: &Path, duration: Duration, timeout: Duration) {
+        match self {
+            Self::Junit(path) => junit(crashes, path, duration, timeout),
+            Self::Sarif(path) => export_sarif(crashes, path, None, timeout),
+        }
+    }
+}
+
+// Escape text for XML attributes and content
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// JUnit XML report of the crashes still reproducing: one failed test case per signature, holding
+// the panic and the reproducers. A campaign without crashes is a single passing test case.
+fn junit(crashes: &Path, path: &Path, duration: Duration, timeout: Duration) {
+    let groups = group_crashes(crashes, timeout);
+    let tests = groups.len().max(1);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"golibafl\" tests=\"{tests}\" failures=\"{}\" time=\"{}\">",
+        groups.len(),
+        duration.as_secs()
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"fuzzing\" tests=\"{tests}\" failures=\"{}\" time=\"{}\">",
+        groups.len(),
+        duration.as_secs()
+    );
+    if groups.is_empty() {
+        let _ = writeln!(
+            xml,
+            "    <testcase classname=\"golibafl\" name=\"no crash found\"/>"
+        );
+    }
+    for (id, group) in &groups {
+        let message = group
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("Crash ({})", group.kind));
+        let name = format!(
+            "{id}: {} in {}",
+            mask_numbers(&message),
+            group.frame.as_deref().unwrap_or("?")
+        );
+        let reproducers: Vec<String> = group
+            .files
+            .iter()
+            .map(|file| format!("Reproducer: {}", file.display()))
+            .collect();
+        let _ = writeln!(
+            xml,
+            "    <testcase classname=\"golibafl.crashes\" name=\"{}\">",
+            escape(&name)
+        );
+        let _ = writeln!(
+            xml,
+            "      <failure type=\"{}\" message=\"{}\">{}\n{}</failure>",
+            group.kind,
+            escape(&message),
+            escape(&reproducers.join("\n")),
+            escape(&group.stderr)
+        );
+        let _ = writeln!(xml, "    </testcase>");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    write(path, xml).unwrap_or_else(|_| panic!("Unable to write {}", path.display()));
+    println!(
+        "{} crash signatures written to {}",
+        groups.len(),
+        path.display()
+    );
+}
@@ -0,0 +1,122 @@
+use crate::output::pin_filename;
+use libafl::{
+    corpus::Testcase,
+    feedbacks::{Feedback, StateInitializer},
+    inputs::Input,
+    Error,
+};
+use libafl_bolts::Named;
+use std::{
+    borrow::Cow,
+    env,
+    fs::{set_permissions, write, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Quote a string for a POSIX shell
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn quote_path(path: &Path) -> String {
+    quote(&path.display().to_string())
+}
+
+// Objective feedback writing a `.<name>.repro.sh` script next to each solution, running it
+// through `golibafl run` with the environment and the timeout of the campaign, and printing how
+// to run it. Extra arguments of the script are passed to `run`. The script is written from
+// `append_metadata`, which pins the name the solution is then stored under.
+pub(crate) struct ReproFeedback {
+    client: usize,
+    // Directory of the solutions corpus
+    dir: PathBuf,
+    env: Vec<(String, String)>,
+    timeout: Duration,
+    // Other arguments of `run` reproducing the campaign's setup
+    run_args: Vec<String>,
+}
+
+impl ReproFeedback {
+    // `dir` must be the directory of the solutions corpus, and `env` the environment variables
+    // set for the target
+    pub(crate) fn new(
+        client: usize,
+        dir: PathBuf,
+        env: Vec<(String, String)>,
+        timeout: Duration,
+        run_args: Vec<String>,
+    ) -> Self {
+        Self {
+            client,
+            dir,
+            env,
+            timeout,
+            run_args,
+        }
+    }
+
+    // The crash isn't written yet, only its directory is made absolute
+    fn script(&self, name: &str) -> String {
+        let exe = env::current_exe().unwrap_or_else(|_| PathBuf::from("golibafl"));
+        let crash = self
+            .dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.dir.clone())
+            .join(name);
+        let env: String = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{key}={} ", quote(value)))
+            .collect();
+        let run_args: String = self
+            .run_args
+            .iter()
+            .map(|arg| format!("{} ", quote(arg)))
+            .collect();
+        format!(
+            "#!/bin/sh\n\
+             # Reproduces the crash found by client {}. Set GOLIBAFL to use another build.\n\
+             exec env {env}\"${{GOLIBAFL:-{}}}\" run --timeout-ms {} {run_args}--input {} \"$@\"\n",
+            self.client,
+            exe.display(),
+            self.timeout.as_millis(),
+            quote_path(&crash)
+        )
+    }
+}
+
+impl Named for ReproFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("ReproFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for ReproFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for ReproFeedback
+where
+    I: Input,
+{
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        let name = pin_filename(testcase);
+        let script = self.dir.join(format!(".{name}.repro.sh"));
+        write(&script, self.script(&name))?;
+        set_permissions(&script, Permissions::from_mode(0o755))?;
+        // On stderr, which the clients keep
+        eprintln!(
+            "Client {} saved a crash, reproduce it with: sh {}",
+            self.client,
+            quote_path(&script)
+        );
+        Ok(())
+    }
+}
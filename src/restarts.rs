@@ -0,0 +1,380 @@
+use libafl::{executors::ExitKind, inputs::HasTargetBytes, observers::Observer, Error};
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{create_dir_all, read, read_to_string, write, OpenOptions},
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicPtr, Ordering},
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// Directory, in the output one, of the clients' restart records and last inputs
+const RESTARTS_DIR: &str = "restarts";
+
+// Clients dying before running this long, outside of an execution, are restarted with backoff
+const FAST_DEATH: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+// Size of the header of the last input file: the length of the input, whether it was being
+// executed, and whether it was part of a batch
+const HEADER: usize = 16;
+// Inputs are truncated to this size in the last input file
+const LAST_INPUT_CAPACITY: usize = 1 << 20;
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+// Identifier of a new campaign, taken before spawning the clients so that they all share it
+pub(crate) fn campaign_id() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_nanos() as u64)
+}
+
+// Restart record of a client, in `<output>/restarts/<client>.record`
+struct Record {
+    // Identifies the campaign, so that the records of a previous one in the same output
+    // directory are ignored
+    campaign: u64,
+    restarts: u64,
+    // Consecutive deaths outside of an execution, right after starting
+    fast_deaths: u32,
+    // Unix time of the last start
+    started: u64,
+    // The client restarted itself after `--restart-interval-execs` executions
+    voluntary: bool,
+}
+
+impl Record {
+    fn parse(content: &str) -> Option<Self> {
+        let mut fields = content.split_whitespace().map(str::parse::<u64>);
+        Some(Self {
+            campaign: fields.next()?.ok()?,
+            restarts: fields.next()?.ok()?,
+            fast_deaths: fields.next()?.ok()?.try_into().ok()?,
+            started: fields.next()?.ok()?,
+            voluntary: fields.next()?.ok()? != 0,
+        })
+    }
+
+    fn format(&self) -> String {
+        format!(
+            "{} {} {} {} {}\n",
+            self.campaign,
+            self.restarts,
+            self.fast_deaths,
+            self.started,
+            u8::from(self.voluntary)
+        )
+    }
+}
+
+// Mapping of the last input file of the client, for the inputs executed in batches
+static LAST_INPUT: AtomicPtr<u8> = AtomicPtr::new(std::ptr::null_mut());
+
+// Last input of the previous run of a client, whether it died executing it, and whether the input
+// was part of a batch
+fn last_input(path: &Path) -> Option<(Vec<u8>, bool, bool)> {
+    let content = read(path).ok()?;
+    let (header, bytes) = content.split_at_checked(HEADER)?;
+    let len = u64::from_le_bytes(header[..8].try_into().ok()?) as usize;
+    let executing = header[8] != 0;
+    let batched = header[9] != 0;
+    Some((bytes.get(..len)?.to_vec(), executing, batched))
+}
+
+fn record_input(map: &mut [u8], bytes: &[u8], batched: bool) {
+    let len = bytes.len().min(LAST_INPUT_CAPACITY);
+    map[HEADER..HEADER + len].copy_from_slice(&bytes[..len]);
+    map[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    map[8] = 1;
+    map[9] = u8::from(batched);
+}
+
+// Record an input of a batch before executing it, as the `LastInputObserver` only sees the batch
+// as a whole
+pub(crate) fn record_batched_input(bytes: &[u8]) {
+    let map = LAST_INPUT.load(Ordering::Relaxed);
+    if !map.is_null() {
+        let map = unsafe { std::slice::from_raw_parts_mut(map, HEADER + LAST_INPUT_CAPACITY) };
+        record_input(map, bytes, true);
+    }
+}
+
+// Record the start of a client and return its number of restarts in this campaign, and the input
+// of a batch it died executing, if any, to execute it again on its own. When the client restarts,
+// the last input of its previous run is kept as `<client>-<restart>.input` for debugging. If the
+// client keeps dying right after starting outside of an execution, e.g. on Go runtime fatal
+// errors, its start is delayed exponentially, up to a minute, instead of spinning.
+// Must be called before creating the client's `LastInputObserver`, which resets the last input.
+pub(crate) fn client_started(
+    output: &Path,
+    campaign: u64,
+    client: usize,
+) -> Result<(u64, Option<Vec<u8>>), Error> {
+    let dir = output.join(RESTARTS_DIR);
+    create_dir_all(&dir)?;
+    let record_path = record_path(output, client);
+    let previous = read_to_string(&record_path)
+        .ok()
+        .and_then(|content| Record::parse(&content))
+        .filter(|record| record.campaign == campaign);
+
+    let mut batched_input = None;
+    let record = match previous {
+        None => Record {
+            campaign,
+            restarts: 0,
+            fast_deaths: 0,
+            started: now(),
+            voluntary: false,
+        },
+        // Neither a death nor a crash, the last input was executed fine
+        Some(previous) if previous.voluntary => Record {
+            campaign,
+            restarts: previous.restarts + 1,
+            fast_deaths: 0,
+            started: now(),
+            voluntary: false,
+        },
+        Some(previous) => {
+            let restarts = previous.restarts + 1;
+            let last = last_input(&last_input_path(output, client));
+            let executing = last.as_ref().is_some_and(|(_, executing, _)| *executing);
+            if let Some((input, _, batched)) = last {
+                write(dir.join(format!("{client}-{restarts}.input")), &input)?;
+                batched_input = (executing && batched).then_some(input);
+            }
+
+            // Crashes and timeouts leave their input marked as being executed, they are the
+            // target's findings rather than deaths of the client
+            let fast = !executing && now().saturating_sub(previous.started) < FAST_DEATH.as_secs();
+            let fast_deaths = if fast { previous.fast_deaths + 1 } else { 0 };
+            if fast_deaths > 0 {
+                let backoff = Duration::from_secs(1 << fast_deaths.min(6)).min(MAX_BACKOFF);
+                eprintln!(
+                    "Client {client} died {fast_deaths} time(s) in a row right after starting, \
+                     restarting it in {}s",
+                    backoff.as_secs()
+                );
+                sleep(backoff);
+            }
+            Record {
+                campaign,
+                restarts,
+                fast_deaths,
+                started: now(),
+                voluntary: false,
+            }
+        }
+    };
+    write(&record_path, record.format())?;
+    Ok((record.restarts, batched_input))
+}
+
+// Mark the next start of `client` as following a voluntary restart, which neither counts as a
+// death nor keeps the last input
+pub(crate) fn record_voluntary_restart(output: &Path, client: usize) -> Result<(), Error> {
+    let record_path = record_path(output, client);
+    let record = read_to_string(&record_path)
+        .ok()
+        .and_then(|content| Record::parse(&content));
+    if let Some(mut record) = record {
+        record.voluntary = true;
+        write(&record_path, record.format())?;
+    }
+    Ok(())
+}
+
+fn record_path(output: &Path, client: usize) -> PathBuf {
+    output.join(RESTARTS_DIR).join(format!("{client}.record"))
+}
+
+fn last_input_path(output: &Path, client: usize) -> PathBuf {
+    output
+        .join(RESTARTS_DIR)
+        .join(format!("{client}.last_input"))
+}
+
+// Observer writing each input to a shared file mapping before it is executed, so that it survives
+// the death of the client, even when the Go runtime exits without unwinding
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LastInputObserver {
+    name: Cow<'static, str>,
+    #[serde(skip)]
+    map: Option<&'static mut [u8]>,
+}
+
+impl LastInputObserver {
+    pub(crate) fn new(name: &'static str, output: &Path, client: usize) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(last_input_path(output, client))?;
+        file.set_len((HEADER + LAST_INPUT_CAPACITY) as u64)?;
+        let map = unsafe {
+            let ptr = libc::mmap(
+                std::ptr::null_mut(),
+                HEADER + LAST_INPUT_CAPACITY,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                return Err(Error::last_os_error("Failed to map the last input file"));
+            }
+            std::slice::from_raw_parts_mut(ptr.cast::<u8>(), HEADER + LAST_INPUT_CAPACITY)
+        };
+        LAST_INPUT.store(map.as_mut_ptr(), Ordering::Relaxed);
+        Ok(Self {
+            name: Cow::Borrowed(name),
+            map: Some(map),
+        })
+    }
+}
+
+// The mapping is left out, it is 1 MiB
+impl fmt::Debug for LastInputObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LastInputObserver")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Named for LastInputObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for LastInputObserver
+where
+    I: HasTargetBytes,
+{
+    fn pre_exec(&mut self, _state: &mut S, input: &I) -> Result<(), Error> {
+        if let Some(map) = self.map.as_mut() {
+            record_input(map, &input.target_bytes(), false);
+        }
+        Ok(())
+    }
+
+    // The crash and timeout handlers run the observers too before the client exits: the input
+    // is then left marked as being executed, so that a batched one is executed again on its own
+    fn post_exec(&mut self, _state: &mut S, _input: &I, exit_kind: &ExitKind) -> Result<(), Error> {
+        if let Some(map) = self.map.as_mut().filter(|_| *exit_kind == ExitKind::Ok) {
+            map[8] = 0;
+            map[9] = 0;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libafl::inputs::BytesInput;
+    use std::{fs::remove_dir_all, process, sync::Mutex};
+
+    // The tests share the mapping of the last input file
+    static LAST_INPUT_LOCK: Mutex<()> = Mutex::new(());
+
+    fn temp_output(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("golibafl-restarts-{}-{name}", process::id()));
+        let _ = remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn records_are_parsed_back() {
+        let record = Record {
+            campaign: 42,
+            restarts: 3,
+            fast_deaths: 2,
+            started: 1_700_000_000,
+            voluntary: true,
+        };
+        let parsed = Record::parse(&record.format()).unwrap();
+        assert_eq!(
+            (
+                parsed.campaign,
+                parsed.restarts,
+                parsed.fast_deaths,
+                parsed.started,
+                parsed.voluntary
+            ),
+            (42, 3, 2, 1_700_000_000, true)
+        );
+        assert!(Record::parse("42 3 2 1700000000").is_none());
+        assert!(Record::parse("42 3 x 1700000000 0").is_none());
+        assert!(Record::parse("42 3 4294967296 1700000000 0").is_none());
+    }
+
+    #[test]
+    fn batched_crash_is_executed_again() {
+        let _lock = LAST_INPUT_LOCK.lock().unwrap();
+        let output = temp_output("batched-crash");
+        let campaign = campaign_id();
+        client_started(&output, campaign, 0).unwrap();
+        let mut observer = LastInputObserver::new("last_input", &output, 0).unwrap();
+        let batch = BytesInput::new(b"whole batch".to_vec());
+        Observer::<_, ()>::pre_exec(&mut observer, &mut (), &batch).unwrap();
+        record_batched_input(b"crashing input");
+        Observer::<_, ()>::post_exec(&mut observer, &mut (), &batch, &ExitKind::Crash).unwrap();
+
+        let (restarts, batched_input) = client_started(&output, campaign, 0).unwrap();
+        assert_eq!(restarts, 1);
+        assert_eq!(batched_input.as_deref(), Some(&b"crashing input"[..]));
+        let _ = remove_dir_all(&output);
+    }
+
+    #[test]
+    fn crash_after_starting_is_not_a_fast_death() {
+        let _lock = LAST_INPUT_LOCK.lock().unwrap();
+        let output = temp_output("fast-crash");
+        let campaign = campaign_id();
+        client_started(&output, campaign, 0).unwrap();
+        let mut observer = LastInputObserver::new("last_input", &output, 0).unwrap();
+        let input = BytesInput::new(b"crashing input".to_vec());
+        Observer::<_, ()>::pre_exec(&mut observer, &mut (), &input).unwrap();
+        Observer::<_, ()>::post_exec(&mut observer, &mut (), &input, &ExitKind::Crash).unwrap();
+
+        let (_, batched_input) = client_started(&output, campaign, 0).unwrap();
+        assert_eq!(batched_input, None);
+        let record = read_to_string(record_path(&output, 0)).unwrap();
+        assert_eq!(Record::parse(&record).unwrap().fast_deaths, 0);
+        let _ = remove_dir_all(&output);
+    }
+
+    #[test]
+    fn voluntary_restart_is_not_a_death() {
+        let _lock = LAST_INPUT_LOCK.lock().unwrap();
+        let output = temp_output("voluntary");
+        let campaign = campaign_id();
+        client_started(&output, campaign, 0).unwrap();
+        let mut observer = LastInputObserver::new("last_input", &output, 0).unwrap();
+        let input = BytesInput::new(b"input".to_vec());
+        Observer::<_, ()>::pre_exec(&mut observer, &mut (), &input).unwrap();
+        Observer::<_, ()>::post_exec(&mut observer, &mut (), &input, &ExitKind::Ok).unwrap();
+        record_voluntary_restart(&output, 0).unwrap();
+
+        let (restarts, batched_input) = client_started(&output, campaign, 0).unwrap();
+        assert_eq!((restarts, batched_input), (1, None));
+        let record = read_to_string(record_path(&output, 0)).unwrap();
+        let record = Record::parse(&record).unwrap();
+        assert_eq!((record.fast_deaths, record.voluntary), (0, false));
+        assert!(!output.join(RESTARTS_DIR).join("0-1.input").exists());
+        let _ = remove_dir_all(&output);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    diff::{load_reference, test_one_input, DEFAULT_REFERENCE_SYMBOL},
+    edges::covered_edges,
+    gocorpus::read_testcase,
+};
+use libafl_targets::libfuzzer_initialize;
+use std::{
+    env,
+    fs::{remove_file, write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Exit code when an input exceeds the `run` mode timeout, same as libFuzzer's default
+const TIMEOUT_EXIT_CODE: i32 = 70;
+
+// Options of the `run` mode, mirroring the libFuzzer flags used by OSS-Fuzz-style infrastructure
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    // Number of times each input is executed
+    pub runs: usize,
+    // Inputs are truncated to this length
+    pub max_len: Option<usize>,
+    // Abort with exit code 70 if a single execution takes longer. Inputs of a directory run in
+    // their own process, so that the next ones still run.
+    pub timeout: Option<Duration>,
+    // Path where the input being executed is saved if it crashes or times out
+    pub artifact_path: Option<PathBuf>,
+    // Report the edges each input adds to the coverage of the previous ones, and the total
+    pub coverage: bool,
+    // Dynamic library of a reference implementation to run after the harness, aborting if both
+    // diverge, and its entry point
+    pub reference: Option<PathBuf>,
+    pub reference_symbol: Option<String>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            runs: 1,
+            max_len: None,
+            timeout: None,
+            artifact_path: None,
+            coverage: false,
+            reference: None,
+            reference_symbol: None,
+        }
+    }
+}
+
+impl RunOptions {
+    // Parse `-runs=`, `-max_len=`, `-timeout=` (in seconds) and `-exact_artifact_path=`.
+    // Other `-flag=value` arguments are left to LLVMFuzzerInitialize, and the remaining arguments
+    // are returned as input paths.
+    pub fn from_libfuzzer_flags(args: &[String]) -> (Self, Vec<PathBuf>) {
+        let mut options = Self::default();
+        let mut inputs = Vec::new();
+        for arg in args {
+            let Some((flag, value)) = arg.strip_prefix('-').and_then(|a| a.split_once('=')) else {
+                inputs.push(PathBuf::from(arg));
+                continue;
+            };
+            match flag {
+                // libFuzzer uses -1 for unlimited runs, which means a single one on given inputs
+                "runs" => options.runs = value.parse::<usize>().unwrap_or(1),
+                "max_len" => options.max_len = value.parse().ok().filter(|&len| len > 0),
+                "timeout" => {
+                    options.timeout = value
+                        .parse()
+                        .ok()
+                        .filter(|&secs| secs > 0)
+                        .map(Duration::from_secs);
+                }
+                "exact_artifact_path" => options.artifact_path = Some(PathBuf::from(value)),
+                _ => {}
+            }
+        }
+        (options, inputs)
+    }
+}
+
+// List the files of a corpus directory, or the input itself if it is a single file.
+// Hidden files, such as LibAFL's testcase metadata, are skipped.
+pub(crate) fn collect_inputs(input: &Path) -> Vec<PathBuf> {
+    if input.is_dir() {
+        input
+            .read_dir()
+            .expect("Unable to read dir")
+            .filter_map(core::result::Result::ok)
+            .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect()
+    } else {
+        vec![input.to_path_buf()]
+    }
+}
+
+// Call LLVMFuzzerInitialize() if present.
+pub(crate) fn initialize() {
+    let args: Vec<String> = env::args().collect();
+    if unsafe { libfuzzer_initialize(&args) } == -1 {
+        println!("Warning: LLVMFuzzerInitialize failed with -1");
+    }
+}
+
+// Exit like libFuzzer once an execution started more than `timeout` ago. Executions are tracked
+// through the returned start time, which must be set before and cleared after each one.
+fn spawn_watchdog(timeout: Duration) -> Arc<Mutex<Option<Instant>>> {
+    let current = Arc::new(Mutex::new(None::<Instant>));
+    let watched = Arc::clone(&current);
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_millis(100));
+        if watched
+            .lock()
+            .unwrap()
+            .is_some_and(|start| start.elapsed() > timeout)
+        {
+            eprintln!(
+                "ALARM: working on the last Unit for {} seconds",
+                timeout.as_secs()
+            );
+            process::exit(TIMEOUT_EXIT_CODE);
+        }
+    });
+    current
+}
+
+// Run each input in its own process, through the `run` mode of the current executable, so that a
+// crash doesn't prevent the remaining inputs from running. The options are forwarded as libFuzzer
+// flags, along with the ones meant for LLVMFuzzerInitialize. Exits with code 1 if any input
+// crashed or timed out.
+fn run_isolated(files: &[PathBuf], options: &RunOptions) {
+    let exe = env::current_exe().expect("Unable to locate the golibafl executable");
+    // Given first, as the libFuzzer flags are parsed as positional arguments
+    let mut flags: Vec<String> = options
+        .timeout
+        .map(|timeout| vec!["--timeout-ms".into(), timeout.as_millis().to_string()])
+        .unwrap_or_default();
+    if let Some(reference) = &options.reference {
+        flags.extend(["--reference".into(), reference.display().to_string()]);
+    }
+    if let Some(symbol) = &options.reference_symbol {
+        flags.extend(["--reference-symbol".into(), symbol.clone()]);
+    }
+    flags.push(format!("-runs={}", options.runs));
+    flags.extend(options.max_len.map(|len| format!("-max_len={len}")));
+    flags.extend(
+        options
+            .artifact_path
+            .as_ref()
+            .map(|path| format!("-exact_artifact_path={}", path.display())),
+    );
+    flags.extend(env::args().skip(1).filter(|arg| {
+        arg.strip_prefix('-')
+            .and_then(|arg| arg.split_once('='))
+            .is_some_and(|(flag, _)| {
+                !flag.starts_with('-')
+                    && !["runs", "max_len", "timeout", "exact_artifact_path"].contains(&flag)
+            })
+    }));
+
+    let (mut crashes, mut timeouts) = (0, 0);
+    for f in files {
+        let status = Command::new(&exe)
+            .arg("run")
+            .args(&flags)
+            .arg(f)
+            .status()
+            .expect("Failed to spawn the replay process");
+        let outcome = match status.code() {
+            Some(0) => "PASS",
+            Some(TIMEOUT_EXIT_CODE) => {
+                timeouts += 1;
+                "TIMEOUT"
+            }
+            _ => {
+                crashes += 1;
+                "CRASH"
+            }
+        };
+        println!("{outcome}: {}", f.display());
+    }
+
+    println!(
+        "{} inputs run: {} passed, {crashes} crashed, {timeouts} timed out",
+        files.len(),
+        files.len() - crashes - timeouts
+    );
+    if crashes + timeouts > 0 {
+        process::exit(1);
+    }
+}
+
+// Run the corpus without fuzzing. A single input runs in this process, so that its crash is the
+// process' one like with libFuzzer, while several inputs are isolated from each other, unless
+// their coverage is accumulated.
+pub(crate) fn run(input: &Path, options: &RunOptions) {
+    let files = collect_inputs(input);
+    if files.len() > 1 && !options.coverage {
+        run_isolated(&files, options);
+        return;
+    }
+    initialize();
+    if let Some(reference) = &options.reference {
+        let symbol = options
+            .reference_symbol
+            .as_deref()
+            .unwrap_or(DEFAULT_REFERENCE_SYMBOL);
+        load_reference(reference, symbol).unwrap_or_else(|err| panic!("{err}"));
+    }
+    let watchdog = options.timeout.map(spawn_watchdog);
+
+    // The counters maps are never reset here, so that they accumulate the coverage of the inputs
+    let mut edges = covered_edges();
+    for f in &files {
+        println!("\x1b[33mRunning: {}\x1b[0m", f.display());
+        let mut inp =
+            read_testcase(f).unwrap_or_else(|_| panic!("Unable to read file {}", &f.display()));
+        if let Some(max_len) = options.max_len {
+            inp.truncate(max_len);
+        }
+        // Every input is executed, down to the empty one, as crashes may be a single byte long
+        println!("INPUT: {inp:?}");
+        // Saved beforehand, as crashes don't give us a chance to do it. Never overwrite the
+        // input itself, which is the artifact when reproducing a crash.
+        let artifact = options
+            .artifact_path
+            .as_ref()
+            .filter(|path| path.canonicalize().ok() != f.canonicalize().ok());
+        if let Some(artifact) = artifact {
+            write(artifact, &inp)
+                .unwrap_or_else(|_| panic!("Unable to write file {}", artifact.display()));
+        }
+
+        for _ in 0..options.runs {
+            if let Some(watchdog) = &watchdog {
+                *watchdog.lock().unwrap() = Some(Instant::now());
+            }
+            test_one_input(&inp);
+            if let Some(watchdog) = &watchdog {
+                *watchdog.lock().unwrap() = None;
+            }
+        }
+
+        if let Some(artifact) = artifact {
+            let _ = remove_file(artifact);
+        }
+
+        if options.coverage {
+            let total = covered_edges();
+            println!("COVERAGE: {} new edges, {total} total", total - edges);
+            edges = total;
+        }
+    }
+
+    if options.coverage {
+        println!("{edges} edges covered by {} inputs", files.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn libfuzzer_flags_are_parsed() {
+        let (options, inputs) = RunOptions::from_libfuzzer_flags(&args(&[
+            "-runs=5",
+            "-max_len=64",
+            "-timeout=3",
+            "-exact_artifact_path=/tmp/crash",
+            "-dict=ignored.dict",
+            "crash-1",
+            "corpus",
+        ]));
+        assert_eq!(options.runs, 5);
+        assert_eq!(options.max_len, Some(64));
+        assert_eq!(options.timeout, Some(Duration::from_secs(3)));
+        assert_eq!(options.artifact_path, Some(PathBuf::from("/tmp/crash")));
+        assert_eq!(inputs, [PathBuf::from("crash-1"), PathBuf::from("corpus")]);
+    }
+
+    #[test]
+    fn unlimited_libfuzzer_values_are_ignored() {
+        let (options, inputs) =
+            RunOptions::from_libfuzzer_flags(&args(&["-runs=-1", "-max_len=0", "-timeout=0"]));
+        assert_eq!(options.runs, 1);
+        assert_eq!(options.max_len, None);
+        assert_eq!(options.timeout, None);
+        assert!(inputs.is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+use libafl::Error;
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+
+// Landlock filesystem access rights of the first ABI which modify the filesystem
+const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+const LANDLOCK_ACCESS_FS_WRITE: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE
+    | 1 << 4 // REMOVE_DIR
+    | 1 << 5 // REMOVE_FILE
+    | 1 << 6 // MAKE_CHAR
+    | 1 << 7 // MAKE_DIR
+    | 1 << 8 // MAKE_REG
+    | 1 << 9 // MAKE_SOCK
+    | 1 << 10 // MAKE_FIFO
+    | 1 << 11 // MAKE_BLOCK
+    | 1 << 12; // MAKE_SYM
+const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+#[repr(C)]
+struct LandlockRulesetAttr {
+    handled_access_fs: u64,
+}
+
+#[repr(C, packed)]
+struct LandlockPathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+// Seccomp filter program, and the fields of `struct seccomp_data` it loads
+const SECCOMP_SET_MODE_FILTER: libc::c_uint = 1;
+const SECCOMP_FILTER_FLAG_TSYNC: libc::c_uint = 1;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+const BPF_LD_W_ABS: u16 = 0x20;
+const BPF_JEQ_K: u16 = 0x15;
+const BPF_RET_K: u16 = 0x06;
+const SECCOMP_DATA_NR: u32 = 0;
+const SECCOMP_DATA_ARCH: u32 = 4;
+const SECCOMP_DATA_ARG0: u32 = 16;
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: Option<u32> = Some(0xc000_003e);
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: Option<u32> = Some(0xc000_00b7);
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const AUDIT_ARCH: Option<u32> = None;
+
+// Sandbox a client once it is initialized, so that the target can't reach the network nor write
+// outside of `writable` directories:
+// - a seccomp filter makes the creation of IPv4 and IPv6 sockets fail with EACCES, in all the
+//   threads of the client
+// - a Landlock ruleset denies the writes to the filesystem outside of `writable`, /dev/shm, where
+//   the event manager maps new shared memory, and /dev/null. Landlock only restricts the client's
+//   thread and the threads and processes it creates from then on, not the threads of the Go
+//   runtime started beforehand, and is skipped with a warning on kernels without it (before 5.13).
+// Both are inherited by the forked executions, and the processes they run.
+pub(crate) fn sandbox(writable: &[&Path]) -> Result<(), Error> {
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } == -1 {
+        return Err(Error::last_os_error("Failed to forbid new privileges"));
+    }
+    deny_network()?;
+    match restrict_writes(writable) {
+        Err(err) if unsupported(&err) => {
+            eprintln!("Warning: Landlock is unavailable, writes aren't restricted by --sandbox");
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn deny_network() -> Result<(), Error> {
+    let Some(arch) = AUDIT_ARCH else {
+        return Err(Error::unsupported(
+            "--sandbox is only supported on x86_64 and aarch64",
+        ));
+    };
+    let statement = |code, k, jt, jf| libc::sock_filter { code, jt, jf, k };
+    let mut filter = [
+        statement(BPF_LD_W_ABS, SECCOMP_DATA_ARCH, 0, 0),
+        statement(BPF_JEQ_K, arch, 0, 5),
+        statement(BPF_LD_W_ABS, SECCOMP_DATA_NR, 0, 0),
+        statement(BPF_JEQ_K, libc::SYS_socket as u32, 0, 3),
+        // The lower half of the domain on little-endian architectures
+        statement(BPF_LD_W_ABS, SECCOMP_DATA_ARG0, 0, 0),
+        statement(BPF_JEQ_K, libc::AF_INET as u32, 2, 0),
+        statement(BPF_JEQ_K, libc::AF_INET6 as u32, 1, 0),
+        statement(BPF_RET_K, SECCOMP_RET_ALLOW, 0, 0),
+        statement(BPF_RET_K, SECCOMP_RET_ERRNO | libc::EACCES as u32, 0, 0),
+    ];
+    let program = libc::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_mut_ptr(),
+    };
+    let installed = unsafe {
+        libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            SECCOMP_FILTER_FLAG_TSYNC,
+            &program,
+        )
+    };
+    if installed != 0 {
+        return Err(Error::last_os_error("Failed to install the seccomp filter"));
+    }
+    Ok(())
+}
+
+fn restrict_writes(writable: &[&Path]) -> Result<(), Error> {
+    let attr = LandlockRulesetAttr {
+        handled_access_fs: LANDLOCK_ACCESS_FS_WRITE,
+    };
+    let ruleset = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            &attr,
+            size_of::<LandlockRulesetAttr>(),
+            0,
+        )
+    };
+    if ruleset == -1 {
+        return Err(Error::last_os_error(
+            "Failed to create the Landlock ruleset",
+        ));
+    }
+    let ruleset = ruleset as libc::c_int;
+
+    let dirs = writable
+        .iter()
+        .copied()
+        .chain([Path::new("/dev/shm")])
+        .map(|dir| (dir, LANDLOCK_ACCESS_FS_WRITE));
+    // Rules on files only take the rights applying to files
+    let files = [(Path::new("/dev/null"), LANDLOCK_ACCESS_FS_WRITE_FILE)];
+    let result = dirs
+        .chain(files)
+        .try_for_each(|(path, access)| allow(ruleset, path, access))
+        .and_then(|()| {
+            if unsafe { libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0) } != 0 {
+                return Err(Error::last_os_error(
+                    "Failed to enforce the Landlock ruleset",
+                ));
+            }
+            Ok(())
+        });
+    unsafe { libc::close(ruleset) };
+    result
+}
+
+fn allow(ruleset: libc::c_int, path: &Path, access: u64) -> Result<(), Error> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| Error::illegal_argument(format!("Invalid path {}", path.display())))?;
+    let fd = unsafe { libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+    // Paths which don't exist can't be written to anyway, except by creating them
+    if fd == -1 {
+        return Ok(());
+    }
+    let rule = LandlockPathBeneathAttr {
+        allowed_access: access,
+        parent_fd: fd,
+    };
+    let added = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_add_rule,
+            ruleset,
+            LANDLOCK_RULE_PATH_BENEATH,
+            &rule,
+            0,
+        )
+    };
+    unsafe { libc::close(fd) };
+    if added != 0 {
+        return Err(Error::last_os_error(format!(
+            "Failed to allow the writes to {}",
+            path.to_string_lossy()
+        )));
+    }
+    Ok(())
+}
+
+// Landlock isn't built in the kernel, or is disabled at boot
+fn unsupported(err: &Error) -> bool {
+    let Error::OsError(err, ..) = err else {
+        return false;
+    };
+    matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EOPNOTSUPP))
+}
@@ -0,0 +1,27 @@
+// Death callback of the sanitizers: abort, so that the crash handler of the executor saves the
+// input as a crash, along with the report the sanitizer printed when the output is captured.
+// Otherwise the sanitizer exits and the client is restarted without any objective.
+extern "C" fn sanitizer_died() {
+    unsafe {
+        libc::raise(libc::SIGABRT);
+    }
+}
+
+// Register the death callback of the sanitizer runtime linked with the C code of cgo targets,
+// e.g. ASan or MSan, if any. Looked up at runtime, as most targets don't link one, which the build
+// script makes possible by exporting the symbols of the executable. Returns whether a sanitizer
+// runtime was found.
+pub(crate) fn install_sanitizer_callback() -> bool {
+    let symbol = unsafe {
+        libc::dlsym(
+            libc::RTLD_DEFAULT,
+            c"__sanitizer_set_death_callback".as_ptr(),
+        )
+    };
+    if symbol.is_null() {
+        return false;
+    }
+    let set_death_callback: extern "C" fn(extern "C" fn()) = unsafe { std::mem::transmute(symbol) };
+    set_death_callback(sanitizer_died);
+    true
+}
@@ -0,0 +1,118 @@
+use crate::triage::{group_crashes, mask_numbers, CrashGroup};
+use serde_json::{json, Value};
+use std::{
+    fs::write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// Path of a source file relative to `source_root`, as code scanning expects, or as is if it lies
+// outside of it
+fn source_uri(file: &str, source_root: Option<&Path>) -> String {
+    source_root
+        .and_then(|root| Path::new(file).strip_prefix(root).ok())
+        .map_or_else(|| file.to_string(), |path| path.display().to_string())
+}
+
+fn sarif_result(rule: &str, finding: &CrashGroup, source_root: Option<&Path>) -> Value {
+    let message = finding
+        .message
+        .clone()
+        .unwrap_or_else(|| format!("Crash ({})", finding.kind));
+    let files: Vec<String> = finding
+        .files
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect();
+    let text = format!(
+        "{message}\nReproduced by {} input(s): {}",
+        files.len(),
+        files.join(", ")
+    );
+    let locations: Vec<Value> = finding
+        .location
+        .iter()
+        .map(|(file, line)| {
+            json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": source_uri(file, source_root) },
+                    "region": { "startLine": line },
+                },
+                "logicalLocations": finding
+                    .frame
+                    .iter()
+                    .map(|frame| json!({ "fullyQualifiedName": frame, "kind": "function" }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "ruleId": rule,
+        "level": "error",
+        "message": { "text": text },
+        "locations": locations,
+        "partialFingerprints": { "golibafl/v1": rule },
+    })
+}
+
+// Replay the crashes in `crashes` once each, in their own process, and write the ones which
+// reproduce to `report` in SARIF, one result per signature: the panic type and message, and the
+// Go source location of the function it happened in when the traceback has it. Paths under
+// `source_root` are made relative to it.
+pub(crate) fn export_sarif(
+    crashes: &Path,
+    report: &Path,
+    source_root: Option<&Path>,
+    timeout: Duration,
+) {
+    let findings = group_crashes(crashes, timeout);
+    let rules: Vec<Value> = findings
+        .iter()
+        .map(|(rule, finding)| {
+            json!({
+                "id": rule,
+                "name": format!("GoCrash/{}", finding.kind),
+                "shortDescription": {
+                    "text": finding
+                        .message
+                        .as_deref()
+                        .map_or_else(|| format!("Crash ({})", finding.kind), mask_numbers),
+                },
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|(rule, finding)| sarif_result(rule, finding, source_root))
+        .collect();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "golibafl",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    write(
+        report,
+        serde_json::to_string_pretty(&sarif).expect("Failed to serialize the SARIF report"),
+    )
+    .unwrap_or_else(|_| panic!("Unable to write {}", report.display()));
+    println!(
+        "{} crash signatures written to {}",
+        findings.len(),
+        report.display()
+    );
+}
+
+// Default location of the SARIF report: next to the crashes directory, which would otherwise
+// replay it as a crash
+pub(crate) fn default_sarif_report(crashes: &Path) -> PathBuf {
+    crashes.with_file_name("crashes.sarif")
+}
@@ -0,0 +1,168 @@
+use libafl::{
+    corpus::{Corpus, CorpusId, SchedulerTestcaseMetadata, Testcase},
+    schedulers::{powersched::SchedulerMetadata, RemovableScheduler, Scheduler},
+    state::HasCorpus,
+    Error, HasMetadata,
+};
+use libafl_bolts::tuples::MatchName;
+
+// Corpus schedulers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulerKind {
+    // Probabilistic sampling weighted by the power schedule
+    Weighted,
+    // Corpus entries in order, each fuzzed according to the power schedule
+    PowerQueue,
+    // Corpus entries in order
+    Queue,
+    // Uniformly random corpus entries
+    Rand,
+}
+
+// One of two schedulers, picked at runtime without changing the type of the fuzzer
+pub(crate) enum EitherScheduler<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A, B, I, S> Scheduler<I, S> for EitherScheduler<A, B>
+where
+    A: Scheduler<I, S>,
+    B: Scheduler<I, S>,
+{
+    fn on_add(&mut self, state: &mut S, id: CorpusId) -> Result<(), Error> {
+        match self {
+            Self::Left(scheduler) => scheduler.on_add(state, id),
+            Self::Right(scheduler) => scheduler.on_add(state, id),
+        }
+    }
+
+    fn on_evaluation<OT>(&mut self, state: &mut S, input: &I, observers: &OT) -> Result<(), Error>
+    where
+        OT: MatchName,
+    {
+        match self {
+            Self::Left(scheduler) => scheduler.on_evaluation(state, input, observers),
+            Self::Right(scheduler) => scheduler.on_evaluation(state, input, observers),
+        }
+    }
+
+    fn next(&mut self, state: &mut S) -> Result<CorpusId, Error> {
+        match self {
+            Self::Left(scheduler) => scheduler.next(state),
+            Self::Right(scheduler) => scheduler.next(state),
+        }
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut S,
+        next_id: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Left(scheduler) => scheduler.set_current_scheduled(state, next_id),
+            Self::Right(scheduler) => scheduler.set_current_scheduled(state, next_id),
+        }
+    }
+}
+
+impl<A, B, I, S> RemovableScheduler<I, S> for EitherScheduler<A, B>
+where
+    A: RemovableScheduler<I, S>,
+    B: RemovableScheduler<I, S>,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut S,
+        id: CorpusId,
+        testcase: &Option<Testcase<I>>,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Left(scheduler) => scheduler.on_remove(state, id, testcase),
+            Self::Right(scheduler) => scheduler.on_remove(state, id, testcase),
+        }
+    }
+
+    fn on_replace(&mut self, state: &mut S, id: CorpusId, prev: &Testcase<I>) -> Result<(), Error> {
+        match self {
+            Self::Left(scheduler) => scheduler.on_replace(state, id, prev),
+            Self::Right(scheduler) => scheduler.on_replace(state, id, prev),
+        }
+    }
+}
+
+// Scheduler maintaining the metadata the calibration and power mutational stages rely on, for
+// schedulers ignoring the power schedule: the state's `SchedulerMetadata`, and the depth of each
+// corpus entry in the tree of mutations
+pub(crate) struct PowerMetadata<CS>(CS);
+
+impl<CS> PowerMetadata<CS> {
+    pub(crate) fn new<S: HasMetadata>(state: &mut S, scheduler: CS) -> Self {
+        if !state.has_metadata::<SchedulerMetadata>() {
+            state.add_metadata(SchedulerMetadata::new(None));
+        }
+        Self(scheduler)
+    }
+}
+
+impl<CS, I, S> Scheduler<I, S> for PowerMetadata<CS>
+where
+    CS: Scheduler<I, S>,
+    S: HasCorpus<I>,
+{
+    fn on_add(&mut self, state: &mut S, id: CorpusId) -> Result<(), Error> {
+        let parent = state.corpus().get(id)?.borrow().parent_id();
+        let depth = match parent {
+            Some(parent) => state
+                .corpus()
+                .get(parent)?
+                .borrow()
+                .metadata::<SchedulerTestcaseMetadata>()
+                .map_or(0, |metadata| metadata.depth() + 1),
+            None => 0,
+        };
+        state
+            .corpus()
+            .get(id)?
+            .borrow_mut()
+            .add_metadata(SchedulerTestcaseMetadata::new(depth));
+        self.0.on_add(state, id)
+    }
+
+    fn on_evaluation<OT>(&mut self, state: &mut S, input: &I, observers: &OT) -> Result<(), Error>
+    where
+        OT: MatchName,
+    {
+        self.0.on_evaluation(state, input, observers)
+    }
+
+    fn next(&mut self, state: &mut S) -> Result<CorpusId, Error> {
+        self.0.next(state)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut S,
+        next_id: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        self.0.set_current_scheduled(state, next_id)
+    }
+}
+
+impl<CS, I, S> RemovableScheduler<I, S> for PowerMetadata<CS>
+where
+    CS: RemovableScheduler<I, S>,
+{
+    fn on_remove(
+        &mut self,
+        state: &mut S,
+        id: CorpusId,
+        testcase: &Option<Testcase<I>>,
+    ) -> Result<(), Error> {
+        self.0.on_remove(state, id, testcase)
+    }
+
+    fn on_replace(&mut self, state: &mut S, id: CorpusId, prev: &Testcase<I>) -> Result<(), Error> {
+        self.0.on_replace(state, id, prev)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{meta::InvariantMetadata, output::OutputObserver};
+use libafl::{
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, StateInitializer},
+    observers::{MapObserver, StdMapObserver, TimeObserver},
+    Error, HasMetadata,
+};
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchNameRef},
+    Named,
+};
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::{borrow::Cow, path::Path};
+
+// Function the objective script defines, called after each execution which didn't crash:
+//   fn is_objective(output, exec_time_us, edges, exit_kind)
+// with the output the target printed, empty unless captured, the execution time in microseconds,
+// the number of edges the execution hit, and "ok", "timeout" or "oom". It returns true, or a
+// non-empty string naming the broken invariant, to make the input a solution.
+const OBJECTIVE_FN: &str = "is_objective";
+// Operations a call of the script may run, so that a runaway loop fails the call instead of
+// stalling the client
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+// Objective feedback declaring the inputs a Rhai script picks solutions, for the properties of
+// the target which are broken without crashing, e.g. a function returning an error which should
+// be impossible. The solutions are tagged with an `InvariantMetadata`.
+pub(crate) struct ScriptFeedback<C> {
+    // Compiled once, the engine and the script are reused for every execution
+    script: Option<(Engine, AST)>,
+    edges_handle: Handle<C>,
+    time_handle: Handle<TimeObserver>,
+    output_handle: Handle<OutputObserver>,
+    // Invariant the last input broke, to attach to it
+    reason: Option<String>,
+    // Whether a failed call was logged, the next ones failing the same way
+    failure_logged: bool,
+}
+
+impl<C> ScriptFeedback<C> {
+    // Without a script, the feedback never reports inputs
+    pub(crate) fn new(
+        path: Option<&Path>,
+        edges_handle: Handle<C>,
+        time_observer: &TimeObserver,
+        output_observer: &OutputObserver,
+    ) -> Result<Self, Error> {
+        let script = path
+            .map(|path| {
+                let mut engine = Engine::new();
+                engine.set_max_operations(MAX_OPERATIONS);
+                let ast = engine.compile_file(path.to_path_buf()).map_err(|err| {
+                    Error::illegal_argument(format!(
+                        "Failed to compile the objective script {}: {err}",
+                        path.display()
+                    ))
+                })?;
+                Ok::<_, Error>((engine, ast))
+            })
+            .transpose()?;
+        Ok(Self {
+            script,
+            edges_handle,
+            time_handle: time_observer.handle(),
+            output_handle: output_observer.handle(),
+            reason: None,
+            failure_logged: false,
+        })
+    }
+
+    // Log the first failed call of the script, whose input is then not a solution
+    fn log_failure(&mut self, message: &str) {
+        if !self.failure_logged {
+            eprintln!("Warning: {message}, further failures are not logged");
+            self.failure_logged = true;
+        }
+    }
+}
+
+impl<C> Named for ScriptFeedback<C> {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("ScriptFeedback");
+        &NAME
+    }
+}
+
+impl<C, S> StateInitializer<S> for ScriptFeedback<C> {}
+
+impl<C, EM, I, OT, S> Feedback<EM, I, OT, S> for ScriptFeedback<C>
+where
+    C: AsRef<StdMapObserver<'static, u8, false>>,
+    OT: MatchNameRef,
+{
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        self.reason = None;
+        let Some((engine, ast)) = &self.script else {
+            return Ok(false);
+        };
+        let exit_kind = match exit_kind {
+            ExitKind::Ok => "ok",
+            ExitKind::Timeout => "timeout",
+            ExitKind::Oom => "oom",
+            _ => return Ok(false),
+        };
+        let output = observers
+            .get(&self.output_handle)
+            .map(|observer| String::from_utf8_lossy(observer.output()).into_owned())
+            .unwrap_or_default();
+        let exec_time = observers
+            .get(&self.time_handle)
+            .and_then(|observer| *observer.last_runtime())
+            .unwrap_or_default();
+        let edges = observers
+            .get(&self.edges_handle)
+            .ok_or_else(|| Error::key_not_found("Edges observer not found"))?
+            .as_ref()
+            .count_bytes();
+        let args = (
+            output,
+            exec_time.as_micros() as i64,
+            edges as i64,
+            exit_kind.to_string(),
+        );
+        let result = match engine.call_fn::<Dynamic>(&mut Scope::new(), ast, OBJECTIVE_FN, args) {
+            Ok(result) => result,
+            Err(err) => {
+                self.log_failure(&format!("the objective script failed: {err}"));
+                return Ok(false);
+            }
+        };
+        self.reason = if let Ok(objective) = result.as_bool() {
+            objective.then(|| String::from("objective script"))
+        } else if let Ok(reason) = result.into_string() {
+            Some(reason).filter(|reason| !reason.is_empty())
+        } else {
+            self.log_failure(&format!("{OBJECTIVE_FN} must return a bool or a string"));
+            return Ok(false);
+        };
+        Ok(self.reason.is_some())
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(reason) = self.reason.take() {
+            testcase.add_metadata(InvariantMetadata { reason });
+        }
+        Ok(())
+    }
+}
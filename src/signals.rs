@@ -0,0 +1,60 @@
+use crate::control::{self, request_dump, set_paused};
+use libafl::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set once SIGINT or SIGTERM was received
+static STOP: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_stop(_signal: libc::c_int) {
+    STOP.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn request_pause(_signal: libc::c_int) {
+    set_paused(true);
+}
+
+extern "C" fn request_resume(_signal: libc::c_int) {
+    set_paused(false);
+}
+
+extern "C" fn request_state_dump(_signal: libc::c_int) {
+    request_dump();
+}
+
+fn install_handler(signal: libc::c_int, handler: extern "C" fn(libc::c_int)) -> Result<(), Error> {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as usize;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(signal, &action, std::ptr::null_mut()) == -1 {
+            return Err(Error::last_os_error(
+                "Failed to install the signal handlers",
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Turn SIGINT and SIGTERM into a stop request, checked by the clients between two fuzzing
+// iterations, instead of killing them in the middle of an execution or a corpus write
+pub(crate) fn install_stop_handlers() -> Result<(), Error> {
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        install_handler(signal, request_stop)?;
+    }
+    Ok(())
+}
+
+// Pause all the clients on SIGUSR1, resume them on SIGUSR2, and make them dump their state on
+// SIGQUIT, whichever process of the campaign receives it. Installed by the launcher before
+// forking the clients, and by the clients after their executor, whose crash handlers include
+// SIGUSR2.
+pub(crate) fn install_request_handlers() -> Result<(), Error> {
+    install_handler(libc::SIGUSR1, request_pause)?;
+    install_handler(libc::SIGUSR2, request_resume)?;
+    install_handler(libc::SIGQUIT, request_state_dump)
+}
+
+// Also set by a stop request made to all the clients
+pub(crate) fn stop_requested() -> bool {
+    STOP.load(Ordering::SeqCst) || control::stop_requested()
+}
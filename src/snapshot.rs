@@ -0,0 +1,115 @@
+use libafl::{
+    mutators::{MOpt, Tokens},
+    schedulers::powersched::SchedulerMetadata,
+    state::HasExecutions,
+    Error, HasMetadata,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{create_dir_all, read, rename, write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// Directory, in the output one, of the clients' state snapshots
+pub(crate) const STATE_DIR: &str = "state";
+// Interval between two snapshots of a client
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(300);
+
+// What a client learned about the target, besides its corpus. The metadata tied to corpus ids,
+// or to the coverage, is left out: the corpus is imported again on resume, and would otherwise
+// be found uninteresting.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    executions: u64,
+    // Path frequencies and calibration averages of the power schedule
+    scheduler: Option<SchedulerMetadata>,
+    // Mutator probabilities learned by MOpt
+    mopt: Option<MOpt>,
+    tokens: Option<Tokens>,
+}
+
+// Periodically saves the state of a client to `<output>/state/<client>`, to restore it when the
+// campaign is resumed
+pub(crate) struct StateSnapshots {
+    path: PathBuf,
+    last: Instant,
+}
+
+impl StateSnapshots {
+    pub(crate) fn new(output: &Path, client: usize) -> Result<Self, Error> {
+        let dir = output.join(STATE_DIR);
+        create_dir_all(&dir)?;
+        Ok(Self {
+            path: dir.join(client.to_string()),
+            last: Instant::now(),
+        })
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= SNAPSHOT_INTERVAL
+    }
+
+    // Written to a temporary file first, so that a client dying meanwhile keeps the previous one
+    pub(crate) fn save<S>(&mut self, state: &S) -> Result<(), Error>
+    where
+        S: HasMetadata + HasExecutions,
+    {
+        self.last = Instant::now();
+        let metadata = state.metadata_map();
+        let snapshot = Snapshot {
+            executions: *state.executions(),
+            scheduler: metadata.get::<SchedulerMetadata>().cloned(),
+            mopt: metadata.get::<MOpt>().cloned(),
+            tokens: metadata.get::<Tokens>().cloned(),
+        };
+        let json = serde_json::to_vec(&snapshot)
+            .map_err(|e| Error::serialize(format!("Failed to serialize the state: {e}")))?;
+        let tmp = self.path.with_extension("tmp");
+        write(&tmp, json)?;
+        rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    // Restore the snapshot of a previous run of the client, if any. Returns whether one was
+    // found. Must be called once the mutators, the scheduler and the dictionaries added their
+    // own metadata, which is replaced. The power schedule metadata is only restored if the
+    // schedule didn't change.
+    pub(crate) fn restore<S>(&self, state: &mut S) -> Result<bool, Error>
+    where
+        S: HasMetadata + HasExecutions,
+    {
+        let Ok(json) = read(&self.path) else {
+            return Ok(false);
+        };
+        let snapshot: Snapshot = serde_json::from_slice(&json).map_err(|e| {
+            Error::serialize(format!(
+                "Invalid state snapshot {}: {e}",
+                self.path.display()
+            ))
+        })?;
+
+        *state.executions_mut() = snapshot.executions;
+        let current = state
+            .metadata_map()
+            .get::<SchedulerMetadata>()
+            .map(SchedulerMetadata::strat);
+        if let Some(scheduler) = snapshot
+            .scheduler
+            .filter(|scheduler| current == Some(scheduler.strat()))
+        {
+            state.add_metadata(scheduler);
+        }
+        if let Some(mopt) = snapshot.mopt.filter(|_| state.has_metadata::<MOpt>()) {
+            state.add_metadata(mopt);
+        }
+        // Merged with the tokens of the dictionaries, which may have changed
+        if let Some(tokens) = snapshot.tokens {
+            match state.metadata_map_mut().get_mut::<Tokens>() {
+                Some(current) => *current += tokens,
+                None => state.add_metadata(tokens),
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,69 @@
+use libafl::{stages::calibrate::UnstableEntriesMetadata, HasMetadata};
+use std::time::{Duration, Instant};
+
+// Interval between two stability checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+// Edges of a client whose hit counts varied between the calibration runs of an input, and all
+// the edges the calibration saw
+fn unstable_edges<S: HasMetadata>(state: &S) -> Option<(usize, usize)> {
+    let metadata = state.metadata_map().get::<UnstableEntriesMetadata>()?;
+    let filled = metadata.filled_entries_count();
+    (filled > 0).then(|| (metadata.unstable_entries().len().min(filled), filled))
+}
+
+// Share of the edges seen by the calibration which are stable, in percent
+pub(crate) fn stability<S: HasMetadata>(state: &S) -> Option<f64> {
+    unstable_edges(state).map(|(unstable, filled)| percent(filled - unstable, filled))
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    100.0 * part as f64 / total as f64
+}
+
+// Periodically checks the stability of a client, warning once it drops below the threshold.
+// Go targets are often nondeterministic, through map iteration order, goroutines or the garbage
+// collector, which makes the fuzzer keep inputs for edges they don't reliably reach.
+pub(crate) struct StabilityCheck {
+    client: usize,
+    // In percent, 0 disables the warning
+    threshold: u8,
+    last: Instant,
+    // Whether the stability is currently reported as below the threshold
+    warned: bool,
+}
+
+impl StabilityCheck {
+    pub(crate) fn new(client: usize, threshold: u8) -> Self {
+        Self {
+            client,
+            threshold,
+            last: Instant::now(),
+            warned: false,
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= CHECK_INTERVAL
+    }
+
+    // Return the unstable and total edges, to report them in the monitor. Warns on stderr, which
+    // the clients keep, when the stability drops below the threshold.
+    pub(crate) fn check<S: HasMetadata>(&mut self, state: &S) -> Option<(usize, usize)> {
+        self.last = Instant::now();
+        let (unstable, filled) = unstable_edges(state)?;
+        let stability = percent(filled - unstable, filled);
+        let below = stability < f64::from(self.threshold);
+        if below && !self.warned {
+            eprintln!(
+                "\x1b[31mWARNING: client {} stability dropped to {stability:.2}% ({unstable} of \
+                 {filled} edges vary between executions of the same input), below {}%. The \
+                 target is nondeterministic: check for goroutines, map iterations or timers \
+                 reached by the harness, and consider --mask-runtime-edges.\x1b[0m",
+                self.client, self.threshold
+            );
+        }
+        self.warned = below;
+        Some((unstable, filled))
+    }
+}
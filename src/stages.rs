@@ -0,0 +1,56 @@
+use libafl::{
+    stages::{Restartable, Stage},
+    Error,
+};
+
+// Stage built only when it is selected, so that the unselected ones neither allocate their
+// mutators nor add their metadata to the state. Unlike an `IfStage`, the choice is made once,
+// when the client starts.
+pub(crate) struct OptionalStage<T>(Option<T>);
+
+impl<T> OptionalStage<T> {
+    // Build the stage with `build` if `selected`
+    pub(crate) fn build(
+        selected: bool,
+        build: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<Self, Error> {
+        Ok(Self(selected.then(build).transpose()?))
+    }
+}
+
+impl<E, EM, S, T, Z> Stage<E, EM, S, Z> for OptionalStage<T>
+where
+    T: Stage<E, EM, S, Z>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        match self.0.as_mut() {
+            Some(stage) => stage.perform(fuzzer, executor, state, manager),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S, T> Restartable<S> for OptionalStage<T>
+where
+    T: Restartable<S>,
+{
+    fn should_restart(&mut self, state: &mut S) -> Result<bool, Error> {
+        match self.0.as_mut() {
+            Some(stage) => stage.should_restart(state),
+            None => Ok(true),
+        }
+    }
+
+    fn clear_progress(&mut self, state: &mut S) -> Result<(), Error> {
+        match self.0.as_mut() {
+            Some(stage) => stage.clear_progress(state),
+            None => Ok(()),
+        }
+    }
+}
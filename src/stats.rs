@@ -0,0 +1,308 @@
+use crate::mutation_stats::MutationHits;
+use libafl::{monitors::Monitor, statistics::manager::ClientStatsManager, Error};
+use libafl_bolts::ClientId;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fs::{rename, write, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// Snapshot of a client's progress
+pub(crate) struct ClientStats {
+    pub(crate) executions: u64,
+    pub(crate) current: usize,
+    pub(crate) corpus: usize,
+    pub(crate) objectives: usize,
+    pub(crate) hangs: usize,
+    pub(crate) edges: usize,
+    pub(crate) edges_total: usize,
+    // In percent, once the calibration ran
+    pub(crate) stability: Option<f64>,
+    // By mutator name
+    pub(crate) mutations: BTreeMap<String, MutationHits>,
+}
+
+enum StatsFormat {
+    // One JSON object per line, all the clients sharing the same file
+    Json { client: usize },
+    // AFL++'s `plot_data`, one file per client
+    PlotData,
+}
+
+// Periodically appends the stats of a client to a file. Each record is written at once in append
+// mode, so that clients can share a file.
+pub(crate) struct StatsWriter {
+    file: File,
+    format: StatsFormat,
+    interval: Duration,
+    start: Instant,
+    last: Option<(Instant, u64)>,
+}
+
+impl StatsWriter {
+    // NDJSON records, every 15 seconds. `start` is the beginning of the campaign.
+    pub(crate) fn json(path: &Path, client: usize, start: Instant) -> io::Result<Self> {
+        Self::new(
+            path,
+            StatsFormat::Json { client },
+            Duration::from_secs(15),
+            start,
+        )
+    }
+
+    // AFL++ `plot_data` records, every 5 seconds. `start` is the beginning of the campaign.
+    pub(crate) fn plot_data(path: &Path, start: Instant) -> io::Result<Self> {
+        let mut writer = Self::new(path, StatsFormat::PlotData, Duration::from_secs(5), start)?;
+        if writer.file.metadata()?.len() == 0 {
+            writer.file.write_all(
+                b"# relative_time, cycles_done, cur_item, corpus_count, pending_total, \
+                  pending_favs, map_size, saved_crashes, saved_hangs, max_depth, \
+                  execs_per_sec, total_execs, edges_found\n",
+            )?;
+        }
+        Ok(writer)
+    }
+
+    fn new(
+        path: &Path,
+        format: StatsFormat,
+        interval: Duration,
+        start: Instant,
+    ) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file,
+            format,
+            interval,
+            start,
+            last: None,
+        })
+    }
+
+    // Whether the previous record is old enough to write a new one
+    pub(crate) fn is_due(&self) -> bool {
+        self.last
+            .is_none_or(|(last_time, _)| last_time.elapsed() >= self.interval)
+    }
+
+    pub(crate) fn write(&mut self, stats: &ClientStats) -> io::Result<()> {
+        let now = Instant::now();
+        let (last_time, last_executions) = self.last.unwrap_or((now, stats.executions));
+        let elapsed = (now - last_time).as_secs_f64();
+        let execs_per_sec = if elapsed > 0.0 {
+            stats.executions.saturating_sub(last_executions) as f64 / elapsed
+        } else {
+            0.0
+        };
+        let run_time = (now - self.start).as_secs();
+
+        let record = match self.format {
+            StatsFormat::Json { client } => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let record = json!({
+                    "timestamp": timestamp,
+                    "run_time": run_time,
+                    "client": client,
+                    "execs": stats.executions,
+                    "execs_per_sec": execs_per_sec,
+                    "corpus": stats.corpus,
+                    "objectives": stats.objectives,
+                    "hangs": stats.hangs,
+                    "edges": stats.edges,
+                    "edges_total": stats.edges_total,
+                    "stability": stats.stability,
+                    "mutators": stats.mutations,
+                });
+                format!("{record}\n")
+            }
+            StatsFormat::PlotData => {
+                let map_size = if stats.edges_total > 0 {
+                    stats.edges as f64 * 100.0 / stats.edges_total as f64
+                } else {
+                    0.0
+                };
+                format!(
+                    "{run_time}, 0, {}, {}, 0, 0, {map_size:.2}%, {}, {}, 0, {execs_per_sec:.2}, {}, {}\n",
+                    stats.current,
+                    stats.corpus,
+                    stats.objectives,
+                    stats.hangs,
+                    stats.executions,
+                    stats.edges,
+                )
+            }
+        };
+        self.file.write_all(record.as_bytes())?;
+        self.last = Some((now, stats.executions));
+        Ok(())
+    }
+}
+
+// Interval between two coverage lines of the broker
+const COVERAGE_INTERVAL: Duration = Duration::from_secs(60);
+
+// Stats of the whole campaign, as last aggregated by the broker
+pub(crate) type SharedStats = Arc<Mutex<Option<Value>>>;
+
+// Monitor of the broker, also aggregating the stats of the whole campaign: the totals, and the
+// breakdown per client as numbered by the broker. They are written to a JSON file every 15
+// seconds, if given one, replaced at once so that it can be polled, and shared every second with
+// the HTTP server, if any. Every minute, the broker also prints the share of the edges the
+// campaign covers, and how fast it grows.
+#[derive(Clone)]
+pub(crate) struct CampaignStatsMonitor<M> {
+    inner: M,
+    path: Option<PathBuf>,
+    shared: Option<SharedStats>,
+    start: Instant,
+    last_write: Option<Instant>,
+    last_share: Option<Instant>,
+    // Time and covered share of the edges of the last coverage line
+    last_coverage: Option<(Instant, f64)>,
+}
+
+impl<M> CampaignStatsMonitor<M> {
+    // `start` is the beginning of the campaign
+    pub(crate) fn new(
+        inner: M,
+        path: Option<PathBuf>,
+        shared: Option<SharedStats>,
+        start: Instant,
+    ) -> Self {
+        Self {
+            inner,
+            path,
+            shared,
+            start,
+            last_write: None,
+            last_share: None,
+            last_coverage: None,
+        }
+    }
+
+    // Print the share of the edges covered by the campaign, and its growth since the last line,
+    // extrapolated to an hour
+    fn print_coverage(&mut self, manager: &ClientStatsManager) {
+        let Some((edges, edges_total)) = campaign_edges(manager) else {
+            return;
+        };
+        let now = Instant::now();
+        let coverage = 100.0 * edges as f64 / edges_total as f64;
+        let growth = self.last_coverage.map(|(last, last_coverage)| {
+            (coverage - last_coverage) * 3600.0 / (now - last).as_secs_f64()
+        });
+        self.last_coverage = Some((now, coverage));
+        match growth {
+            Some(growth) => {
+                println!("[Coverage] {coverage:.2}% of {edges_total} edges, {growth:+.2}%/h");
+            }
+            None => println!("[Coverage] {coverage:.2}% of {edges_total} edges"),
+        }
+    }
+
+    fn record(&self, manager: &mut ClientStatsManager) -> Value {
+        let mut clients = manager
+            .client_stats()
+            .iter()
+            .filter(|(_, stats)| stats.enabled())
+            .map(|(id, stats)| {
+                let coverage = stats.edges_coverage();
+                (
+                    id.0,
+                    json!({
+                        "client": id.0,
+                        "execs": stats.executions(),
+                        "corpus": stats.corpus_size(),
+                        "objectives": stats.objective_size(),
+                        "edges": coverage.as_ref().map(|coverage| coverage.edges_hit),
+                        "edges_total": coverage.as_ref().map(|coverage| coverage.edges_total),
+                    }),
+                )
+            })
+            .collect::<Vec<_>>();
+        clients.sort_by_key(|(id, _)| *id);
+        let (edges, edges_total) = campaign_edges(manager).unzip();
+        let global = manager.global_stats();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        json!({
+            "timestamp": timestamp,
+            "run_time": self.start.elapsed().as_secs(),
+            "clients": global.client_stats_count,
+            "execs": global.total_execs,
+            "execs_per_sec": global.execs_per_sec,
+            "corpus": global.corpus_size,
+            "objectives": global.objective_size,
+            "edges": edges,
+            "edges_total": edges_total,
+            "per_client": clients.into_iter().map(|(_, stats)| stats).collect::<Vec<_>>(),
+        })
+    }
+}
+
+// Edges covered by the campaign, and size of the map. The clients share their corpus, the one
+// covering the most edges is the campaign's.
+fn campaign_edges(manager: &ClientStatsManager) -> Option<(u64, u64)> {
+    manager
+        .client_stats()
+        .values()
+        .filter(|stats| stats.enabled())
+        .filter_map(|stats| stats.edges_coverage())
+        .filter(|coverage| coverage.edges_total > 0)
+        .map(|coverage| (coverage.edges_hit, coverage.edges_total))
+        .max()
+}
+
+fn write_stats(path: &Path, record: &Value) -> io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    write(&tmp, format!("{record:#}\n"))?;
+    rename(&tmp, path)
+}
+
+impl<M: Monitor> Monitor for CampaignStatsMonitor<M> {
+    fn display(
+        &mut self,
+        client_stats_manager: &mut ClientStatsManager,
+        event_msg: &str,
+        sender_id: ClientId,
+    ) -> Result<(), Error> {
+        let write_due = self.path.is_some()
+            && self
+                .last_write
+                .is_none_or(|last| last.elapsed() >= Duration::from_secs(15));
+        let share_due = self.shared.is_some()
+            && self
+                .last_share
+                .is_none_or(|last| last.elapsed() >= Duration::from_secs(1));
+        if write_due || share_due {
+            let record = self.record(client_stats_manager);
+            if let Some(path) = self.path.as_deref().filter(|_| write_due) {
+                self.last_write = Some(Instant::now());
+                if let Err(err) = write_stats(path, &record) {
+                    eprintln!("Failed to write {}: {err}", path.display());
+                }
+            }
+            if let Some(shared) = self.shared.as_ref().filter(|_| share_due) {
+                self.last_share = Some(Instant::now());
+                *shared.lock().unwrap() = Some(record);
+            }
+        }
+        if self
+            .last_coverage
+            .is_none_or(|(last, _)| last.elapsed() >= COVERAGE_INTERVAL)
+        {
+            self.print_coverage(client_stats_manager);
+        }
+        self.inner
+            .display(client_stats_manager, event_msg, sender_id)
+    }
+}
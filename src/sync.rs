@@ -0,0 +1,71 @@
+use libafl::{
+    corpus::{Corpus, CorpusId},
+    inputs::{BytesInput, HasTargetBytes},
+    Error,
+};
+use std::{
+    fs::{create_dir_all, read_dir, write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+// Interval between two exports of the queue, and two imports of the other fuzzers' queues
+pub(crate) const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+// Exports the queue of a client to `<sync_dir>/<fuzzer_id>/queue`, named like AFL++ queue entries
+// (`id:NNNNNN,...`), as AFL++ only syncs files following this scheme
+pub(crate) struct AflSync {
+    queue: PathBuf,
+    exported: Option<CorpusId>,
+    last: Option<Instant>,
+}
+
+impl AflSync {
+    pub(crate) fn new(sync_dir: &Path, fuzzer_id: &str) -> Result<Self, Error> {
+        let queue = sync_dir.join(fuzzer_id).join("queue");
+        create_dir_all(&queue)?;
+        Ok(Self {
+            queue,
+            exported: None,
+            last: None,
+        })
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.is_none_or(|last| last.elapsed() >= SYNC_INTERVAL)
+    }
+
+    // Write the corpus entries added since the last export. Entries are numbered by their corpus
+    // id, which is stable when culled entries are removed, so that a respawned client overwrites
+    // its previous exports.
+    pub(crate) fn export<C>(&mut self, corpus: &C) -> Result<(), Error>
+    where
+        C: Corpus<BytesInput>,
+    {
+        let new: Vec<CorpusId> = corpus
+            .ids()
+            .filter(|&id| self.exported.is_none_or(|last| id > last))
+            .collect();
+        for id in new {
+            let input = corpus.cloned_input_for_id(id)?;
+            let name = format!("id:{:06},src:golibafl", id.0);
+            write(self.queue.join(name), &*input.target_bytes())?;
+            self.exported = Some(id);
+        }
+        self.last = Some(Instant::now());
+        Ok(())
+    }
+}
+
+// Queues of the other fuzzers in `sync_dir`, skipping the ones whose id starts with `own_prefix`:
+// golibafl clients already share their corpus through the broker
+pub(crate) fn foreign_queues(sync_dir: &Path, own_prefix: &str) -> Vec<PathBuf> {
+    read_dir(sync_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with(own_prefix))
+        .map(|entry| entry.path().join("queue"))
+        .filter(|queue| queue.is_dir())
+        .collect()
+}
@@ -0,0 +1,76 @@
+use libafl::{schedulers::powersched::SchedulerMetadata, HasMetadata};
+use std::time::{Duration, Instant};
+
+// Interval between two updates of the timeout
+const UPDATE_INTERVAL: Duration = Duration::from_secs(30);
+
+// Derived timeouts are rounded up to a multiple of this, which is also the smallest one, like AFL
+const TIMEOUT_ROUND: Duration = Duration::from_millis(20);
+
+// Multiplier of the average execution time, AFL's default
+const DEFAULT_MULTIPLIER: u32 = 5;
+
+// Timeout derived from the calibrated average execution time of the corpus entries, like AFL's
+// `-t auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoTimeout {
+    multiplier: u32,
+}
+
+impl AutoTimeout {
+    // Parse `auto` or `auto,x<N>`, e.g. `auto,x10` for ten times the average execution time
+    pub fn from_cmdline(timeout: &str) -> Result<Self, String> {
+        let multiplier = match timeout.split_once(',') {
+            None if timeout == "auto" => DEFAULT_MULTIPLIER,
+            Some(("auto", multiplier)) => multiplier
+                .strip_prefix('x')
+                .and_then(|multiplier| multiplier.parse().ok())
+                .filter(|&multiplier| multiplier > 0)
+                .ok_or_else(|| format!("Invalid multiplier {multiplier}, expected x<N>"))?,
+            _ => return Err(format!("Expected auto or auto,x<N>, got {timeout}")),
+        };
+        Ok(Self { multiplier })
+    }
+}
+
+// Periodically derives the timeout of a client from the calibration of its corpus, which records
+// the total time and number of the calibration runs. The fixed timeout is the upper bound.
+pub(crate) struct AdaptiveTimeout {
+    multiplier: u32,
+    max: Duration,
+    current: Duration,
+    last: Instant,
+}
+
+impl AdaptiveTimeout {
+    pub(crate) fn new(auto: AutoTimeout, max: Duration) -> Self {
+        Self {
+            multiplier: auto.multiplier,
+            max,
+            current: max,
+            last: Instant::now(),
+        }
+    }
+
+    pub(crate) fn is_due(&self) -> bool {
+        self.last.elapsed() >= UPDATE_INTERVAL
+    }
+
+    // The new timeout, if it changed
+    pub(crate) fn update<S: HasMetadata>(&mut self, state: &S) -> Option<Duration> {
+        self.last = Instant::now();
+        let metadata = state.metadata_map().get::<SchedulerMetadata>()?;
+        let runs = u32::try_from(metadata.cycles())
+            .ok()
+            .filter(|&runs| runs > 0)?;
+        let average = metadata.exec_time() / runs;
+        let rounds = (average * self.multiplier)
+            .div_duration_f64(TIMEOUT_ROUND)
+            .ceil() as u32;
+        let timeout = (TIMEOUT_ROUND * rounds.max(1)).min(self.max);
+        (timeout != self.current).then(|| {
+            self.current = timeout;
+            timeout
+        })
+    }
+}
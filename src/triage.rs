@@ -0,0 +1,551 @@
+use crate::{diff::DIVERGENCE_MESSAGE, run::collect_inputs};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt,
+    fs::{create_dir_all, read_to_string, rename, write},
+    io::Read,
+    os::unix::process::ExitStatusExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+use wait_timeout::ChildExt;
+
+// How a crash manifests when replayed
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CrashKind {
+    Panic,
+    FatalError,
+    Timeout,
+    Segfault,
+    Signal(i32),
+    Exit(i32),
+}
+
+impl fmt::Display for CrashKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic => write!(f, "panic"),
+            Self::FatalError => write!(f, "fatal error"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Segfault => write!(f, "SIGSEGV"),
+            Self::Signal(signal) => write!(f, "signal {signal}"),
+            Self::Exit(code) => write!(f, "exit code {code}"),
+        }
+    }
+}
+
+// Outcome of a single replay: `None` if the input did not crash, with the captured stderr
+fn replay(input: &Path, timeout: Duration) -> (Option<CrashKind>, String) {
+    // Replays go through the `run` mode of a fresh process, so that crashes don't kill the triage
+    let exe = env::current_exe().expect("Unable to locate the golibafl executable");
+    let mut child = Command::new(exe)
+        .arg("run")
+        .args(run_args(input))
+        .arg("-i")
+        .arg(input)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn the replay process");
+
+    let mut stderr = child.stderr.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    });
+    let status = child
+        .wait_timeout(timeout)
+        .expect("Failed to wait for the replay process");
+    if status.is_none() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let stderr = reader.join().unwrap_or_default();
+
+    let kind = match status {
+        None => Some(CrashKind::Timeout),
+        Some(_) if stderr.contains("fatal error:") => Some(CrashKind::FatalError),
+        Some(_) if stderr.contains("panic:") => Some(CrashKind::Panic),
+        Some(status) => match (status.signal(), status.code()) {
+            (Some(11), _) => Some(CrashKind::Segfault),
+            (Some(signal), _) => Some(CrashKind::Signal(signal)),
+            (None, Some(0)) | (None, None) => None,
+            (None, Some(code)) => Some(CrashKind::Exit(code)),
+        },
+    };
+    (kind, stderr)
+}
+
+// Content of the `.meta.json` file of the solution `input`
+fn read_meta(input: &Path) -> Option<serde_json::Value> {
+    let name = input.file_name()?.to_string_lossy();
+    let meta = read_to_string(input.with_file_name(format!(".{name}.meta.json"))).ok()?;
+    serde_json::from_str(&meta).ok()
+}
+
+// Arguments of `run` the campaign which found `input` replays it with, e.g. its reference
+// implementation, without which divergences wouldn't reproduce
+fn run_args(input: &Path) -> Vec<String> {
+    read_meta(input)
+        .and_then(|meta| serde_json::from_value(meta["run_args"].clone()).ok())
+        .unwrap_or_default()
+}
+
+// Invariant the solution `input` broke, read from its `.meta.json` file. Such inputs don't crash
+// when replayed, so they are reported apart instead of as fixed.
+pub(crate) fn invariant_reason(input: &Path) -> Option<String> {
+    let meta = read_meta(input)?;
+    if meta["kind"] != ObjectiveKind::Invariant.name() {
+        return None;
+    }
+    Some(meta["invariant"].as_str().unwrap_or_default().to_string())
+}
+
+// First line of the Go panic or fatal error message
+pub(crate) fn crash_message(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .find(|line| line.starts_with("panic:") || line.starts_with("fatal error:"))
+}
+
+// Function at the top of the stack of the crashing goroutine, skipping the runtime's frames
+pub(crate) fn top_frame(stderr: &str) -> Option<&str> {
+    stderr
+        .lines()
+        .skip_while(|line| !line.starts_with("goroutine "))
+        .skip(1)
+        .filter(|line| !line.starts_with('\t') && !line.is_empty())
+        .map(|line| line.rsplit_once('(').map_or(line, |(function, _)| function))
+        .find(|function| !function.starts_with("runtime.") && *function != "panic")
+}
+
+// Source location of the function `top_frame` returns, from the traceback line following it:
+// `\t<file>:<line> +0x<offset>`
+pub(crate) fn top_location(stderr: &str) -> Option<(&str, u32)> {
+    let mut lines = stderr
+        .lines()
+        .skip_while(|line| !line.starts_with("goroutine "))
+        .skip(1)
+        .filter(|line| !line.is_empty());
+    while let Some(line) = lines.next() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        let function = line.rsplit_once('(').map_or(line, |(function, _)| function);
+        let location = lines.next()?;
+        if !function.starts_with("runtime.") && function != "panic" {
+            let location = location.trim().split(" +").next()?;
+            let (file, line) = location.rsplit_once(':')?;
+            return Some((file, line.parse().ok()?));
+        }
+    }
+    None
+}
+
+// Message with its numbers masked, so that e.g. out of range panics on different indexes share
+// the same signature. Hexadecimal numbers are masked as a whole when prefixed with `0x`.
+pub(crate) fn mask_numbers(message: &str) -> String {
+    let mut masked = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !c.is_ascii_digit() {
+            masked.push(c);
+            continue;
+        }
+        masked.push('N');
+        let hex = c == '0' && chars.next_if(|&c| c == 'x' || c == 'X').is_some();
+        while chars
+            .next_if(|c| c.is_ascii_digit() || (hex && c.is_ascii_hexdigit()))
+            .is_some()
+        {}
+    }
+    masked
+}
+
+// How a solution crashed, from what the Go runtime printed, as their triage differs. Computed from
+// the output alone, so that the solutions and their replays get the same signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ObjectiveKind {
+    // A panic the harness recovered and rethrew
+    Panic,
+    // A fatal error of the runtime, e.g. "concurrent map writes", which can't be recovered
+    FatalError,
+    // A report of a sanitizer linked with cgo code, e.g. ASan or MSan
+    Sanitizer,
+    // A signal without any Go message, e.g. a SIGSEGV in cgo code
+    Signal,
+    // Diverging implementations, with a reference implementation
+    Divergence,
+    // An execution which didn't crash, but broke an invariant of the target, e.g. reported by the
+    // objective script
+    Invariant,
+}
+
+impl ObjectiveKind {
+    pub(crate) fn from_output(output: &str) -> Self {
+        if output.contains("==ERROR: AddressSanitizer")
+            || output.contains("WARNING: MemorySanitizer")
+        {
+            Self::Sanitizer
+        } else if output.contains(DIVERGENCE_MESSAGE) {
+            Self::Divergence
+        } else if output.contains("fatal error: ") {
+            Self::FatalError
+        } else if output.contains("panic: ") {
+            Self::Panic
+        } else {
+            Self::Signal
+        }
+    }
+
+    // Also the name of the subdirectory of the solutions it is linked in
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Panic => "panic",
+            Self::FatalError => "fatal-error",
+            Self::Sanitizer => "sanitizer",
+            Self::Signal => "signal",
+            Self::Divergence => "divergence",
+            Self::Invariant => "invariant",
+        }
+    }
+}
+
+// Hash identifying the signature of a crash: its kind, its masked message, and the function it
+// happened in, from the output of the target
+pub(crate) fn signature_id(stderr: &str) -> String {
+    let signature = format!(
+        "{}|{}|{}",
+        ObjectiveKind::from_output(stderr).name(),
+        crash_message(stderr).map(mask_numbers).unwrap_or_default(),
+        top_frame(stderr).unwrap_or_default()
+    );
+    digest_id(&signature)
+}
+
+// Hash identifying the signature of an invariant violation: the masked reason it was reported for
+pub(crate) fn invariant_signature_id(reason: &str) -> String {
+    digest_id(&format!("invariant|{}", mask_numbers(reason)))
+}
+
+fn digest_id(signature: &str) -> String {
+    let digest = Sha256::digest(signature.as_bytes());
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// Replay every file of `crashes` once, each in its own process, and group the ones which still
+// reproduce by signature: the kind of crash, its masked message, and the function it happened in.
+// Prints a summary table, and writes the results in JSON to `report`.
+pub(crate) fn replay_crashes(crashes: &Path, report: &Path, timeout: Duration) {
+    let inputs = collect_inputs(crashes);
+
+    let mut results = Vec::new();
+    // signature -> reproducing crashes
+    let mut groups: BTreeMap<(CrashKind, String, String), Vec<String>> = BTreeMap::new();
+    let mut fixed = 0;
+    let mut invariants = 0;
+    for input in &inputs {
+        let name = input.file_name().unwrap().to_string_lossy().into_owned();
+        if let Some(reason) = invariant_reason(input) {
+            println!("{name}: INVARIANT ({reason})");
+            results.push(json!({
+                "file": name,
+                "kind": ObjectiveKind::Invariant.name(),
+                "invariant": reason,
+                "signature": invariant_signature_id(&reason),
+            }));
+            invariants += 1;
+            continue;
+        }
+        let (kind, stderr) = replay(input, timeout);
+        let message = crash_message(&stderr);
+        let frame = top_frame(&stderr);
+        println!(
+            "{name}: {}",
+            kind.as_ref()
+                .map_or_else(|| "FIXED".to_string(), |kind| format!("CRASH ({kind})"))
+        );
+        results.push(json!({
+            "file": name,
+            "reproduced": kind.is_some(),
+            "kind": kind.as_ref().map(ToString::to_string),
+            "message": message,
+            "frame": frame,
+            "signature": kind.is_some().then(|| signature_id(&stderr)),
+        }));
+
+        match kind {
+            Some(kind) => groups
+                .entry((
+                    kind,
+                    message.map(mask_numbers).unwrap_or_default(),
+                    frame.unwrap_or_default().to_string(),
+                ))
+                .or_default()
+                .push(name),
+            None => fixed += 1,
+        }
+    }
+
+    let signatures: Vec<_> = groups
+        .iter()
+        .map(|((kind, message, frame), files)| {
+            json!({
+                "kind": kind.to_string(),
+                "message": message,
+                "frame": frame,
+                "count": files.len(),
+                "files": files,
+            })
+        })
+        .collect();
+    let json = json!({
+        "crashes": inputs.len() - invariants,
+        "reproduced": inputs.len() - invariants - fixed,
+        "invariants": invariants,
+        "signatures": signatures,
+        "results": results,
+    });
+    write(
+        report,
+        serde_json::to_string_pretty(&json).expect("Failed to serialize the replay report"),
+    )
+    .unwrap_or_else(|_| panic!("Unable to write {}", report.display()));
+
+    println!(
+        "\n{:>6}  {:<12}  {:<40}  message",
+        "count", "kind", "function"
+    );
+    for ((kind, message, frame), files) in &groups {
+        println!(
+            "{:>6}  {:<12}  {:<40}  {message}",
+            files.len(),
+            kind.to_string(),
+            frame
+        );
+    }
+    println!(
+        "{} of {} crashes still reproduce, in {} signatures, {invariants} broken invariants not \
+         replayed, report written to {}",
+        inputs.len() - invariants - fixed,
+        inputs.len() - invariants,
+        groups.len(),
+        report.display()
+    );
+}
+
+// Crashes sharing a signature
+pub(crate) struct CrashGroup {
+    pub(crate) kind: CrashKind,
+    // Unmasked message of the first crash of the group
+    pub(crate) message: Option<String>,
+    pub(crate) frame: Option<String>,
+    // Source file and line of `frame`
+    pub(crate) location: Option<(String, u32)>,
+    // Output of the first crash of the group
+    pub(crate) stderr: String,
+    pub(crate) files: Vec<PathBuf>,
+}
+
+// Replay the crashes in `crashes` once each, in their own process, and group the ones which still
+// reproduce by signature, identified by a hash of it
+pub(crate) fn group_crashes(crashes: &Path, timeout: Duration) -> BTreeMap<String, CrashGroup> {
+    let mut groups: BTreeMap<String, CrashGroup> = BTreeMap::new();
+    for input in collect_inputs(crashes) {
+        let name = input.file_name().unwrap().to_string_lossy().into_owned();
+        if let Some(reason) = invariant_reason(&input) {
+            println!("{name}: INVARIANT ({reason}), not replayed");
+            continue;
+        }
+        let (kind, stderr) = replay(&input, timeout);
+        let Some(kind) = kind else {
+            println!("{name}: FIXED");
+            continue;
+        };
+        println!("{name}: CRASH ({kind})");
+        let id = signature_id(&stderr);
+        let message = crash_message(&stderr).map(str::to_string);
+        let frame = top_frame(&stderr).map(str::to_string);
+        let location = top_location(&stderr).map(|(file, line)| (file.to_string(), line));
+        groups
+            .entry(id)
+            .or_insert_with(|| CrashGroup {
+                kind,
+                message,
+                frame,
+                location,
+                stderr,
+                files: Vec::new(),
+            })
+            .files
+            .push(input);
+    }
+    groups
+}
+
+// Default location of the `replay-crashes` report: next to the crashes directory, which would
+// otherwise replay it as a crash
+pub(crate) fn default_replay_report(crashes: &Path) -> PathBuf {
+    crashes.with_file_name("replay.json")
+}
+
+// Replay every crash of `output/crashes` `runs` times, classify them and write the report to
+// `output/triage.json`. Crashes which don't reproduce on every run are moved to `output/flaky`.
+pub(crate) fn triage(output: &Path, runs: usize, timeout: Duration) {
+    let crashes_dir = output.join("crashes");
+    let flaky_dir = output.join("flaky");
+    let crashes = collect_inputs(&crashes_dir);
+
+    let mut report = Vec::new();
+    // (kind, message) -> number of reproducible crashes
+    let mut summary: BTreeMap<(CrashKind, String), usize> = BTreeMap::new();
+    let mut flaky = 0;
+    // Broken invariants, by reason, which replays can't reproduce
+    let mut invariants: BTreeMap<String, usize> = BTreeMap::new();
+    for crash in &crashes {
+        if let Some(reason) = invariant_reason(crash) {
+            let name = crash.file_name().unwrap().to_string_lossy().into_owned();
+            println!("{name}: invariant ({reason}), not replayed");
+            report.push(json!({
+                "file": name,
+                "kind": ObjectiveKind::Invariant.name(),
+                "invariant": reason,
+            }));
+            *invariants.entry(mask_numbers(&reason)).or_default() += 1;
+            continue;
+        }
+        let mut kind = None;
+        let mut message = None;
+        let mut reproduced = 0;
+        for _ in 0..runs {
+            let (run_kind, stderr) = replay(crash, timeout);
+            if run_kind.is_some() {
+                reproduced += 1;
+                kind = kind.or(run_kind);
+                message = message.or_else(|| crash_message(&stderr).map(str::to_string));
+            }
+        }
+
+        let name = crash.file_name().unwrap().to_string_lossy().into_owned();
+        let kind_name = kind.as_ref().map(ToString::to_string);
+        println!(
+            "{name}: {} ({reproduced}/{runs} runs)",
+            kind_name.as_deref().unwrap_or("no crash")
+        );
+        report.push(json!({
+            "file": name,
+            "kind": kind_name,
+            "message": message,
+            "reproduced": reproduced,
+            "runs": runs,
+        }));
+
+        match kind {
+            Some(kind) if reproduced == runs => {
+                *summary
+                    .entry((kind, message.unwrap_or_default()))
+                    .or_default() += 1;
+            }
+            _ => {
+                create_dir_all(&flaky_dir).expect("Unable to create the flaky crashes dir");
+                rename(crash, flaky_dir.join(&name))
+                    .unwrap_or_else(|_| panic!("Unable to move {}", crash.display()));
+                flaky += 1;
+            }
+        }
+    }
+
+    let report_path = output.join("triage.json");
+    write(
+        &report_path,
+        serde_json::to_string_pretty(&report).expect("Failed to serialize the triage report"),
+    )
+    .unwrap_or_else(|_| panic!("Unable to write {}", report_path.display()));
+
+    println!("\nReproducible crashes:");
+    for ((kind, message), count) in &summary {
+        println!("{count:>6}  {kind}  {message}");
+    }
+    if !invariants.is_empty() {
+        println!("\nBroken invariants:");
+        for (reason, count) in &invariants {
+            println!("{count:>6}  {reason}");
+        }
+    }
+    println!(
+        "{} crashes triaged, {flaky} flaky ones moved to {}, report written to {}",
+        crashes.len(),
+        flaky_dir.display(),
+        report_path.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PANIC: &str = "panic: runtime error: index out of range [7] with length 3
+
+goroutine 1 [running]:
+github.com/prometheus/prometheus/promql/parser.(*Lexer).next(0xc000123456)
+	/src/promql/parser/lex.go:42 +0x1d
+main.LLVMFuzzerTestOneInput(0x7f0000000000, 0x3)
+	/src/harness.go:12 +0x2f
+";
+
+    #[test]
+    fn replayed_crash_shares_the_signature_of_the_solution() {
+        // The replay prints the input before the output the solution was saved with
+        let replayed = format!("INPUT: [1, 2, 3]\n{PANIC}");
+        assert_eq!(signature_id(PANIC), signature_id(&replayed));
+        let other_index = PANIC.replace("[7] with length 3", "[12] with length 4");
+        assert_eq!(signature_id(PANIC), signature_id(&other_index));
+        assert_ne!(
+            signature_id(PANIC),
+            signature_id(&PANIC.replace("panic:", "fatal error:"))
+        );
+    }
+
+    #[test]
+    fn kinds_are_read_from_the_output() {
+        assert_eq!(ObjectiveKind::from_output(PANIC), ObjectiveKind::Panic);
+        assert_eq!(
+            ObjectiveKind::from_output("fatal error: concurrent map writes"),
+            ObjectiveKind::FatalError
+        );
+        assert_eq!(
+            ObjectiveKind::from_output("==1==ERROR: AddressSanitizer: heap-buffer-overflow"),
+            ObjectiveKind::Sanitizer
+        );
+        assert_eq!(ObjectiveKind::from_output(""), ObjectiveKind::Signal);
+    }
+
+    #[test]
+    fn only_prefixed_hexadecimal_numbers_are_masked_as_a_whole() {
+        assert_eq!(
+            mask_numbers("nil pointer at 0xc000123abc, index 42"),
+            "nil pointer at N, index N"
+        );
+        assert_eq!(mask_numbers("got 3abc, want 0X1F"), "got Nabc, want N");
+        assert_ne!(mask_numbers("3abc"), mask_numbers("3def"));
+        assert_eq!(mask_numbers("0x"), "N");
+    }
+
+    #[test]
+    fn invariant_signatures_mask_numbers() {
+        assert_eq!(
+            invariant_signature_id("balance is -3"),
+            invariant_signature_id("balance is -12")
+        );
+        assert_ne!(
+            invariant_signature_id("balance is -3"),
+            signature_id("balance is -3")
+        );
+    }
+}